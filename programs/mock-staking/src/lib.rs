@@ -0,0 +1,45 @@
+// ================================================================================================
+// MOCK STAKING PROGRAM
+// ================================================================================================
+// Test-only program exposing the `deposit(amount: u64)` instruction that the vesting program
+// calls when `auto_stake_program` is set. Moves tokens from the owner's account into a vault.
+// ================================================================================================
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+declare_id!("4cfvFCn3YJkbajEi3Dfik6t8SReDb3JSJTzVMpQdj3tp");
+
+#[program]
+pub mod mock_staking {
+    use super::*;
+
+    /// Moves `amount` tokens from the owner's token account into the staking vault.
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        let transfer_instruction = Transfer {
+            from: ctx.accounts.owner_token_account.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), transfer_instruction);
+
+        token::transfer(cpi_ctx, amount)
+    }
+}
+
+/// Account validation for deposit instruction
+/// - owner_token_account: Token account the stake is taken from
+/// - vault: Token account holding staked tokens
+/// - owner: Owner of `owner_token_account`
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut, constraint = owner_token_account.owner == owner.key())]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, constraint = vault.mint == owner_token_account.mint)]
+    pub vault: Account<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
@@ -18,8 +18,9 @@
 #![allow(unexpected_cfgs)]
 
 use anchor_lang::prelude::*;
-use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_lang::system_program;
+use anchor_spl::associated_token::{self, get_associated_token_address, AssociatedToken};
+use anchor_spl::token::{self, CloseAccount, InitializeAccount3, Mint, Token, TokenAccount, Transfer};
 
 // ================================================================================================
 // CONSTANTS
@@ -27,14 +28,66 @@ use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 
 /// Average seconds in a month (30.44 days) for vesting calculations
 pub const SECONDS_PER_MONTH: i64 = 2_629_776;
+/// Number of vesting periods in the grace period
+pub const GRACE_PERIOD_MONTHS: i64 = 6;
 /// Grace period after vesting completion before admin can withdraw unclaimed tokens
-pub const GRACE_PERIOD: i64 = 6 * SECONDS_PER_MONTH;
+pub const GRACE_PERIOD: i64 = GRACE_PERIOD_MONTHS * SECONDS_PER_MONTH;
 /// Maximum allowed delay for vesting start time (prevents far-future exploits)
 pub const MAX_START_DELAY: i64 = 365 * 24 * 60 * 60; 
 /// Maximum number of beneficiaries per vesting schedule (prevents DoS)
 pub const MAX_BENEFICIARIES: usize = 50;
-/// Maximum token decimals supported
-pub const MAX_DECIMALS: u8 = 9;
+/// Maximum vesting duration in months (bounds the time arithmetic)
+pub const MAX_TOTAL_MONTHS: u8 = 120;
+/// Maximum cliff length in months (and for an absolute `cliff_time`, in periods after start)
+pub const MAX_CLIFF_MONTHS: u8 = 48;
+/// Deployment-wide minimum vesting duration in months (schedules may raise it via `min_total_months`)
+pub const MIN_TOTAL_MONTHS: u8 = 1;
+/// Maximum token decimals supported. All math is on raw u64 amounts with u128
+/// intermediates, so decimals only bound how many whole tokens fit in a u64
+/// (~18.4 tokens at 18 decimals); 10^18 is also the largest power of ten below u64::MAX.
+pub const MAX_DECIMALS: u8 = 18;
+/// Version byte of the `export_schedule` wire format
+pub const EXPORT_FORMAT_VERSION: u8 = 1;
+/// Beneficiary entries per `export_schedule` page (keeps pages under the return data limit)
+pub const EXPORT_ENTRIES_PER_PAGE: usize = 15;
+/// Maximum entries returned by `get_beneficiary_page` (keeps the Borsh-encoded page under the return data limit)
+pub const MAX_BENEFICIARY_PAGE_ENTRIES: u32 = 10;
+/// Maximum points returned by `sample_curve` (63 * 16 bytes + 4 stays under the return data limit)
+pub const MAX_CURVE_SAMPLES: u8 = 63;
+/// Maximum items per batch instruction that runs a CPI per item (each milestone account of
+/// `initialize_percentage_milestones` is a `create_account`), kept well under the compute limit
+pub const MAX_BATCH_SIZE: usize = 10;
+/// Maximum addresses per `get_claimable_batch` call (24 * 32 bytes of input fits in one transaction)
+pub const MAX_CLAIMABLE_BATCH: usize = 24;
+/// Instruction discriminator of the staking program's `deposit(amount: u64)` (Anchor sighash of `global:deposit`)
+pub const STAKE_DEPOSIT_DISCRIMINATOR: [u8; 8] = [242, 35, 198, 137, 82, 225, 242, 182];
+/// SPL Memo program (v2) invoked by `claim` when a memo is given
+pub const MEMO_PROGRAM_ID: Pubkey = solana_program::pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+/// Maximum memo length in bytes accepted by `claim`
+pub const MAX_MEMO_LEN: usize = 128;
+/// Delay between `request_escrow_migration` and `migrate_escrow` (2 days, in seconds)
+pub const ESCROW_MIGRATION_TIMELOCK: i64 = 2 * 24 * 60 * 60;
+/// Maximum milestones per beneficiary for `convert_to_milestones`
+pub const MAX_MILESTONES: usize = 24;
+/// Basis points in 100%, for `initialize_percentage_milestones`
+pub const BPS_DENOMINATOR: u16 = 10_000;
+/// Maximum claim blackout windows per schedule (see `add_blackout`)
+pub const MAX_BLACKOUT_WINDOWS: usize = 8;
+/// Escrow balances below this many raw units can be swept with `force_unlock_dust`
+pub const DUST_THRESHOLD: u64 = 1_000;
+/// Operations kept by an `AuditLog` before the oldest is overwritten
+pub const AUDIT_LOG_CAPACITY: usize = 16;
+/// Account size of an `AuditLog` (discriminator, schedule, total_ops, entries)
+pub const AUDIT_LOG_SPACE: usize = 8 + 32 + 8 + (4 + AUDIT_LOG_CAPACITY * (1 + 32 + 8 + 8));
+/// Current `DataAccount` layout version (1 = original layout without a version field)
+pub const DATA_ACCOUNT_VERSION: u8 = 2;
+
+// Macro to calculate the space required for the DataAccount based on the number of beneficiaries.
+macro_rules! calculate_vesting_space {
+    ($beneficiaries_count: expr) => {
+        8 + 8 + 32 + 32 + 32 + 1 + 8 + 8 + 1 + 8 + 8 + 8 + 8 + 1 + 1 + 8 + 1 + 33 + 1 + 8 + 4 + 1 + 1 + 1 + 1 + 1 + 1 + 8 + 4 + 4 + 1 + 1 + 33 + 1 + (4 + MAX_BLACKOUT_WINDOWS * (8 + 8)) + 1 + 4 + 1 + 33 + 33 + 1 + 1 + 33 + 8 + 33 + 8 + 2 + (4 + $beneficiaries_count * (32 + 8 + 8 + 8 + 1 + 1 + 8 + 1 + 1 + 16 + 9 + 4 + 4 + 24 + 1 + 8) + 1)
+    };
+}
 
 declare_id!("94XXemxbSsTsKxdEzsfQX76BmV2Uo2JSbVeSC61a6zDp");
 
@@ -56,90 +109,296 @@ pub mod vesting {
     /// * `beneficiaries` - Vector of beneficiary configurations (max 50)
     /// * `amount` - Total tokens to vest in RAW UNITS (e.g., 1000 tokens with 9 decimals = 1_000_000_000_000)
     /// * `decimals` - Token decimals for reference (all calculations use raw units)
-    pub fn initialize(
-        ctx: Context<Initialize>, 
+    /// * `options` - Schedule-wide settings fixed at initialization
+    pub fn initialize<'info>(
+        ctx: Context<'_, '_, '_, 'info, Initialize<'info>>,
         beneficiaries: Vec<Beneficiary>, 
         amount: u64, // RAW UNITS: Total tokens in smallest denomination
         decimals: u8,
+        options: ScheduleOptions,
     ) -> Result<()> {
-        let data_account = &mut ctx.accounts.data_account;
-        let now = Clock::get()?.unix_timestamp;
-        
-        if data_account.authority == Pubkey::default() {
-            data_account.authority = ctx.accounts.sender.to_account_info().key();
-        } else {
-            require!(
-                data_account.authority == ctx.accounts.sender.key(),
-                VestingError::UnauthorizedAdmin
-            );
-        }
-
-        require!(!beneficiaries.is_empty(), VestingError::NoBeneficiaries);
-        require!(beneficiaries.len() <= MAX_BENEFICIARIES, VestingError::TooManyBeneficiaries);
-        require!(amount > 0, VestingError::InvalidAmount);
-        require!(decimals <= MAX_DECIMALS, VestingError::InvalidDecimals);
+        process_initialize(ctx, beneficiaries, amount, decimals, options, None)
+    }
 
-        let mut seen = std::collections::HashSet::new();
+    /// Same as `initialize`, but every beneficiary releases along a cumulative-percentage
+    /// table such as "25% by month 3, 50% by month 6, 100% by month 12".
+    /// 
+    /// Each table is converted to a `MilestoneSchedule` against the beneficiary's
+    /// `allocated_tokens` (see `percentage_to_milestones`), so beneficiaries claim exactly as
+    /// after `convert_to_milestones`. Cliff settings are dropped. `remaining_accounts` must hold
+    /// each beneficiary's writable milestone PDA (`["milestones", data_account, beneficiary]`)
+    /// in beneficiary order; they are created at the sender's expense.
+    /// 
+    /// # Arguments
+    /// * `beneficiaries` - Vector of beneficiary configurations (max 50)
+    /// * `amount` - Total tokens to vest in RAW UNITS
+    /// * `decimals` - Token decimals for reference (all calculations use raw units)
+    /// * `options` - Schedule-wide settings fixed at initialization
+    /// * `milestones` - One cumulative-percentage table per beneficiary (max 24 entries each)
+    /// * `interpolation` - Whether amounts unlock at each milestone or linearly towards it
+    pub fn initialize_percentage_milestones<'info>(
+        ctx: Context<'_, '_, '_, 'info, Initialize<'info>>,
+        beneficiaries: Vec<Beneficiary>,
+        amount: u64,
+        decimals: u8,
+        options: ScheduleOptions,
+        milestones: Vec<Vec<PercentageMilestone>>,
+        interpolation: MilestoneInterpolation,
+    ) -> Result<()> {
+        process_initialize(
+            ctx,
+            beneficiaries,
+            amount,
+            decimals,
+            options,
+            Some((milestones, interpolation)),
+        )
+    }
 
-        for b in beneficiaries.iter() {
-            // Validate vesting periods
-            require!(b.total_months >= 1, VestingError::InvalidVestingPeriod);
-            require!(b.cliff_months <= 48, VestingError::CliffTooLong);
-            require!(b.cliff_months < b.total_months, VestingError::InvalidCliffPeriod);
-            
-            require!(b.allocated_tokens > 0, VestingError::InvalidAllocation);
-            
-            // Validate time bounds
-            require!(b.start_time >= now, VestingError::InvalidStartTime);
-            require!(
-                b.start_time <= now + MAX_START_DELAY,
-                VestingError::StartTimeTooFar
-            );
+    /// Bootstraps one schedule per mint for a shared cohort, e.g. token A and token B vesting
+    /// to the same people on the same timeline, in a single instruction.
+    /// 
+    /// Each asset gets its own data account and escrow at the PDAs `initialize` would use,
+    /// configured with the shared `beneficiaries` and `options`; only `allocated_tokens` comes
+    /// from the asset's `allocations`, in beneficiary order. `remaining_accounts` holds four
+    /// accounts per asset, in asset order: the mint, the writable data account and escrow PDAs,
+    /// and the sender's writable token account funding the escrow. Admin registries, clock
+    /// sources and audit logs are not supported here; use `initialize` per mint for those.
+    /// 
+    /// # Arguments
+    /// * `beneficiaries` - Shared beneficiary configurations (max 50)
+    /// * `assets` - Mint, amount, decimals and allocations of each schedule (max `MAX_BATCH_SIZE`)
+    /// * `options` - Schedule-wide settings applied to every schedule
+    pub fn initialize_multi<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitializeMulti<'info>>,
+        beneficiaries: Vec<Beneficiary>,
+        assets: Vec<AssetConfig>,
+        options: ScheduleOptions,
+    ) -> Result<()> {
+        check_batch_size(assets.len())?;
+        require!(
+            !assets.is_empty() && ctx.remaining_accounts.len() == assets.len() * 4,
+            VestingError::InvalidAssetAccounts
+        );
 
-             // Validate vesting configuration consistency
-            if b.cliff_months > 0 {
-                require!(b.total_months % b.cliff_months == 0, VestingError::InvalidVestingConfig);
-            }
-            
-            // Prevent duplicate beneficiaries
-            require!(seen.insert(b.key), VestingError::DuplicateBeneficiary);            
+        for (asset, accounts) in assets.iter().zip(ctx.remaining_accounts.chunks(4)) {
+            initialize_asset(
+                ctx.program_id,
+                &ctx.accounts.sender.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                accounts,
+                &beneficiaries,
+                asset,
+                &options,
+            )?;
         }
 
-        // Validate total allocation against available amount (all in raw units)
-        let mut total_allocated = 0u64;
-        for b in beneficiaries.iter() {
-            total_allocated = total_allocated
-                .checked_add(b.allocated_tokens)
-                .ok_or(VestingError::MathOverflow)?;
-        }
-        require!(total_allocated <= amount, VestingError::OverAllocation);
+        Ok(())
+    }
 
-        // Store vesting configuration
-        data_account.beneficiaries = beneficiaries;
+    /// Phase one of a two-phase initialization for beneficiary lists too large for one transaction.
+    /// 
+    /// Creates a data account sized for `total_beneficiaries` and funds the escrow, but leaves
+    /// the schedule inactive. `append_beneficiaries` then fills it in chunks; claims are rejected
+    /// until the list is complete. Schedule options are left at their defaults.
+    /// 
+    /// # Arguments
+    /// * `total_beneficiaries` - Final number of beneficiaries (max 50)
+    /// * `amount` - Total tokens to vest in RAW UNITS
+    /// * `decimals` - Token decimals for reference (all calculations use raw units)
+    pub fn initialize_empty(
+        ctx: Context<InitializeEmpty>,
+        total_beneficiaries: u32,
+        amount: u64, // RAW UNITS
+        decimals: u8,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+
+        require!(total_beneficiaries > 0, VestingError::NoBeneficiaries);
+        require!(
+            total_beneficiaries as usize <= MAX_BENEFICIARIES,
+            VestingError::TooManyBeneficiaries
+        );
+        require!(amount > 0, VestingError::InvalidAmount);
+        require!(decimals <= MAX_DECIMALS, VestingError::InvalidDecimals);
+
+        data_account.authority = ctx.accounts.sender.key();
+        data_account.version = DATA_ACCOUNT_VERSION;
+        data_account.escrow_wallet = ctx.accounts.escrow_wallet.key();
+        data_account.token_mint = ctx.accounts.token_mint.key();
         data_account.token_amount = amount;
         data_account.decimals = decimals;
-        data_account.escrow_wallet = ctx.accounts.escrow_wallet.to_account_info().key();
-        data_account.token_mint = ctx.accounts.token_mint.to_account_info().key();
+        data_account.expected_beneficiaries = total_beneficiaries;
+        data_account.activated = false;
+        data_account.claim_ceiling_time = i64::MAX;
+
+        require!(ctx.accounts.wallet_to_withdraw_from.amount >= amount, VestingError::InsufficientBalance);
 
-        // Transfer tokens to escrow 
-        let transfer_instruction = Transfer{ 
+        let transfer_instruction = Transfer {
             from: ctx.accounts.wallet_to_withdraw_from.to_account_info(),
             to: ctx.accounts.escrow_wallet.to_account_info(),
             authority: ctx.accounts.sender.to_account_info(),
         };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), transfer_instruction);
+        let escrow_balance_before = ctx.accounts.escrow_wallet.amount;
+        token::transfer(cpi_ctx, amount)?;
+        ctx.accounts.escrow_wallet.reload()?;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(VestingInitialized {
+            admin: ctx.accounts.sender.key(),
+            funder: ctx.accounts.sender.key(),
+            token_mint: ctx.accounts.token_mint.key(),
+            total_amount: amount,
+            beneficiaries_count: 0,
+            usd_value: 0,
+            effective_token_amount: data_account.whole_tokens(amount),
+            event_seq,
+        });
 
-        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), transfer_instruction);   
+        emit_escrow_change(
+            data_account,
+            escrow_balance_before,
+            ctx.accounts.escrow_wallet.amount,
+            EscrowChangeReason::Fund,
+        )?;
 
-        require!(ctx.accounts.wallet_to_withdraw_from.amount >= amount, VestingError::InsufficientBalance);
+        Ok(())
+    }
 
-        token::transfer(cpi_ctx, amount)?;
+    /// Phase two of `initialize_empty`: appends a chunk of beneficiaries.
+    /// 
+    /// Once the list reaches `expected_beneficiaries` and the allocations fit the funded
+    /// amount, the schedule is activated and claims become possible.
+    /// 
+    /// # Arguments
+    /// * `data_bump` - Bump seed for data account PDA validation
+    /// * `chunk` - Next beneficiary configurations to append
+    pub fn append_beneficiaries(
+        ctx: Context<UpdateConfig>,
+        _data_bump: u8,
+        mut chunk: Vec<Beneficiary>,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.check_beneficiary_count()?;
+        let now = data_account.current_time()?;
+        let max_start_delay = data_account.max_start_delay();
+        let period = data_account.period_length();
+        let min_months = data_account.min_vesting_months();
+
+        require!(!data_account.activated, VestingError::AlreadyActivated);
+        require!(!chunk.is_empty(), VestingError::NoBeneficiaries);
+
+        let combined_len = data_account
+            .beneficiaries
+            .len()
+            .checked_add(chunk.len())
+            .ok_or(VestingError::MathOverflow)?;
+        require!(
+            combined_len <= data_account.expected_beneficiaries as usize,
+            VestingError::TooManyBeneficiaries
+        );
+
+        let mut seen: std::collections::HashSet<Pubkey> =
+            data_account.beneficiaries.iter().map(|b| b.key).collect();
+        for b in chunk.iter_mut() {
+            validate_beneficiary(b, now, max_start_delay, period, min_months)?;
+            require!(seen.insert(b.key), VestingError::DuplicateBeneficiary);
+            b.assigned = true;
+        }
+
+        data_account.assign_original_indices(&mut chunk)?;
+        data_account.add_active_beneficiaries(&chunk)?;
+        data_account.beneficiaries.extend(chunk);
+        data_account.sync_beneficiary_count();
+
+        let total_allocated = total_allocation(&data_account.beneficiaries)?;
+        require!(total_allocated <= data_account.token_amount, VestingError::OverAllocation);
+
+        if combined_len == data_account.expected_beneficiaries as usize {
+            data_account.activated = true;
+
+            let event_seq = data_account.next_event_seq()?;
+            emit!(ScheduleActivated {
+                admin: ctx.accounts.admin.key(),
+                beneficiaries_count: combined_len as u32,
+                total_allocated,
+                event_seq,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Creates the escrow token account ahead of `initialize_prefunded`.
+    /// 
+    /// The escrow is owned by the (not yet created) data account PDA, so it can be
+    /// funded in a separate step, e.g. from a multisig.
+    pub fn create_escrow(_ctx: Context<CreateEscrow>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Initializes a vesting schedule against an escrow that was funded beforehand.
+    /// 
+    /// Same as `initialize`, but skips the funding transfer. The whole escrow balance
+    /// becomes `token_amount` and must cover all allocations.
+    /// 
+    /// # Arguments
+    /// * `beneficiaries` - Vector of beneficiary configurations (max 50)
+    /// * `decimals` - Token decimals for reference (all calculations use raw units)
+    /// * `options` - Schedule-wide settings fixed at initialization
+    pub fn initialize_prefunded(
+        ctx: Context<InitializePrefunded>,
+        beneficiaries: Vec<Beneficiary>,
+        decimals: u8,
+        options: ScheduleOptions,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let escrow_balance = ctx.accounts.escrow_wallet.amount;
+
+        if data_account.authority != Pubkey::default() {
+            msg!("Schedule already initialized, escrow balance: {}", escrow_balance);
+            return err!(VestingError::AlreadyInitialized);
+        }
+
+        require!(
+            escrow_balance >= total_allocation(&beneficiaries)?,
+            VestingError::UnderCollateralized
+        );
+
+        let total_allocated = data_account.configure(
+            ctx.accounts.sender.key(),
+            ctx.accounts.escrow_wallet.key(),
+            ctx.accounts.token_mint.key(),
+            ctx.accounts.token_mint.freeze_authority.is_some(),
+            beneficiaries,
+            escrow_balance,
+            decimals,
+            &options,
+        )?;
+
+        if let Some(log) = ctx.accounts.audit_log.as_mut() {
+            log.schedule = data_account.key();
+        }
+        record_audit(
+            data_account,
+            ctx.accounts.audit_log.as_mut(),
+            AuditOp::Initialize,
+            ctx.accounts.sender.key(),
+            escrow_balance,
+        )?;
 
-        // Emit initialization event
+        let event_seq = data_account.next_event_seq()?;
         emit!(VestingInitialized {
             admin: ctx.accounts.sender.key(),
+            funder: Pubkey::default(),
             token_mint: ctx.accounts.token_mint.key(),
-            total_amount: amount,
+            total_amount: escrow_balance,
             beneficiaries_count: data_account.beneficiaries.len() as u32,
+            usd_value: data_account.usd_value(total_allocated)?,
+            effective_token_amount: data_account.whole_tokens(escrow_balance),
+            event_seq,
         });
 
         Ok(())
@@ -147,122 +406,106 @@ pub mod vesting {
 
     /// Claims unlocked tokens for a beneficiary according to their vesting schedule.
     /// 
-    /// Note: Vesting is calculated in discrete monthly steps, not continuously per second.
+    /// Note: Vesting is calculated in discrete monthly steps (or `slots_per_period` slots
+    /// in slot mode), not continuously per second.
     /// 
     /// This function calculates the amount of tokens that have vested for the calling
     /// beneficiary and transfers the claimable amount to their wallet. The calculation
     /// considers cliff periods and linear vesting over the specified duration.
+    /// When `max_per_claim` is configured, a single claim transfers at most that amount.
+    /// When `auto_stake_program` is set, the claimed amount is then deposited into it
+    /// (see `deposit_to_stake_program` for the `remaining_accounts` layout).
+    /// When `memo` is given, it is logged through the SPL Memo program right before the
+    /// transfer, for destinations that key deposits on memos (requires `memo_program`).
     /// 
     /// # Arguments
     /// * `data_bump` - Bump seed for data account PDA validation
     /// * `escrow_bump` - Bump seed for escrow wallet PDA validation
+    /// * `memo` - Optional UTF-8 memo of at most `MAX_MEMO_LEN` bytes
     /// 
     /// # Vesting Logic
     /// 1. Check if cliff period has passed
     /// 2. Calculate months elapsed since start time
     /// 3. Compute linear vesting: (months_vested / total_vesting_months) * allocated_tokens
     /// 4. Subtract already claimed tokens to get claimable amount
-    pub fn claim(ctx: Context<Claim>, data_bump: u8, escrow_bump: u8) -> Result<()> {
-        let sender = &ctx.accounts.sender;
-        let escrow_wallet = &ctx.accounts.escrow_wallet;
-        let data_account = &mut ctx.accounts.data_account;
-        let token_mint_key = &ctx.accounts.token_mint.key();
-
-        let token_program = &ctx.accounts.token_program;
-        let beneficiaries_ata = &ctx.accounts.wallet_to_deposit_to;
-
-        // Validate escrow wallet PDA
-        let (expected_escrow_pda, expected_escrow_bump) = Pubkey::find_program_address(
-            &[b"escrow_wallet".as_ref(), token_mint_key.as_ref()],
-            ctx.program_id
-        );
-        require!(ctx.accounts.escrow_wallet.key() == expected_escrow_pda,
-            VestingError::InvalidEscrowWallet
-        );
-        require!(escrow_bump == expected_escrow_bump,
-            VestingError::InvalidEscrowBump);
-
-        // Find beneficiary in the list
-        let index = data_account
-            .beneficiaries
-            .iter()
-            .position(|b| b.key == *sender.key)
-            .ok_or(VestingError::BeneficiaryNotFound)?;
-
-        let beneficiary = data_account.beneficiaries[index];
-        let now = Clock::get()?.unix_timestamp;
-
-        // Calculate vesting periods
-        let cliff_months = beneficiary.cliff_months as u64;
-        let total_months = beneficiary.total_months as u64;                                     
-        let vesting_month = total_months - cliff_months;
-
-        require!(vesting_month > 0, VestingError::InvalidVestingConfig);
- 
-         // Calculate elapsed time with safety cap
-        let months_elapsed = if now >= beneficiary.start_time {
-            let time_diff = now.saturating_sub(beneficiary.start_time);
-            let calculated_months = time_diff.checked_div(SECONDS_PER_MONTH).ok_or(VestingError::MathOverflow)?;
-            calculated_months as u64
-        } else {
-            0u64
-        };
-
-        // Check if cliff period has passed
-        if months_elapsed < cliff_months {
-            return err!(VestingError::CliffNotReached);
-        }
-
-        let months_vested = std::cmp::min(months_elapsed - cliff_months, vesting_month);
-
-        // Calculate unlocked tokens using 128-bit arithmetic for precision
-        let allocated_raw = beneficiary.allocated_tokens as u128; // RAW UNITS
-        let claimed_raw = beneficiary.claimed_tokens as u128;     // RAW UNITS
-                
-        let unlocked = if months_vested >= vesting_month {
-            allocated_raw
-        } else {
-            allocated_raw
-                .checked_mul(months_vested as u128)
-                .ok_or(VestingError::MathOverflow)?
-                .checked_div(vesting_month as u128)
-                .ok_or(VestingError::MathOverflow)?
-        };
-
-        let claimable = unlocked.saturating_sub(claimed_raw );
+    pub fn claim(ctx: Context<Claim>, data_bump: u8, escrow_bump: u8, memo: Option<Vec<u8>>) -> Result<()> {
+        process_claim(ctx, data_bump, escrow_bump, None, None, memo.as_deref(), None)
+    }
 
-        require!(claimable > 0, VestingError::ClaimNotAllowed);
+    /// Same as `claim`, but fails with `ClaimableChanged` unless the claimable amount at
+    /// execution equals `expected`, giving integrations deterministic claim amounts.
+    /// The client should recompute and retry on failure.
+    /// 
+    /// # Arguments
+    /// * `data_bump` - Bump seed for data account PDA validation
+    /// * `escrow_bump` - Bump seed for escrow wallet PDA validation
+    /// * `expected` - Claimable amount computed by the client in RAW UNITS
+    pub fn claim_exact(ctx: Context<Claim>, data_bump: u8, escrow_bump: u8, expected: u64) -> Result<()> {
+        process_claim(ctx, data_bump, escrow_bump, Some(expected), None, None, None)
+    }
 
-        let seeds = &["data_account".as_bytes(), token_mint_key.as_ref(), &[data_bump]];
-        let signer_seeds = &[&seeds[..]];
+    /// Same as `claim`, but delivers into a custodian's vault instead of the sender's ATA.
+    /// `wallet_to_deposit_to` must be a token account for `token_mint` whose address is
+    /// the PDA `owner_program` derives from `vault_seeds` (the bump included as the last seed),
+    /// e.g. a vault keyed by a client id. See `verify_vault_pda`.
+    /// Not available when the schedule has an `auto_stake_program`.
+    /// 
+    /// # Arguments
+    /// * `data_bump` - Bump seed for data account PDA validation
+    /// * `escrow_bump` - Bump seed for escrow wallet PDA validation
+    /// * `owner_program` - Program the vault address is derived from
+    /// * `vault_seeds` - Seeds of the vault address, ending with its bump
+    pub fn claim_to_vault(
+        ctx: Context<Claim>,
+        data_bump: u8,
+        escrow_bump: u8,
+        owner_program: Pubkey,
+        vault_seeds: Vec<Vec<u8>>,
+    ) -> Result<()> {
+        process_claim(ctx, data_bump, escrow_bump, None, Some((&owner_program, &vault_seeds)), None, None)
+    }
 
-        let transfer_instruction = Transfer {
-            from: escrow_wallet.to_account_info(),
-            to: beneficiaries_ata.to_account_info(),
-            authority: data_account.to_account_info(),
-        };
+    /// Same as `claim`, then moves the claimed tokens on from the sender's ATA to `forward_to`
+    /// in the same instruction, e.g. to repay a loan atomically. `forward_to` must be passed as
+    /// the `forward_to` account, a token account for `token_mint`; the sender signs the second
+    /// transfer. Emits `ClaimForwarded` after `TokensClaimed`.
+    /// Not available when the schedule has an `auto_stake_program`.
+    /// 
+    /// # Arguments
+    /// * `data_bump` - Bump seed for data account PDA validation
+    /// * `escrow_bump` - Bump seed for escrow wallet PDA validation
+    /// * `forward_to` - Token account receiving the claimed tokens
+    pub fn claim_and_forward(
+        ctx: Context<Claim>,
+        data_bump: u8,
+        escrow_bump: u8,
+        forward_to: Pubkey,
+    ) -> Result<()> {
+        process_claim(ctx, data_bump, escrow_bump, None, None, None, Some(&forward_to))
+    }
 
-        let cpi_ctx = CpiContext::new_with_signer(
-            token_program.to_account_info(), 
-            transfer_instruction, 
-            signer_seeds
+    /// Closes the sender's empty ATA for the schedule's token and returns its rent to them.
+    /// 
+    /// `claim` creates the ATA on the beneficiary's behalf and at their expense; when they
+    /// end up with nothing to keep in it (e.g. after `forfeit`), this recovers that rent.
+    /// Only beneficiaries of this schedule may call it, and the ATA must hold no tokens.
+    pub fn close_empty_claim_ata(ctx: Context<CloseClaimAta>) -> Result<()> {
+        let sender_key = ctx.accounts.sender.key();
+        require!(
+            ctx.accounts.data_account.beneficiaries.iter().any(|b| b.assigned && b.key == sender_key),
+            VestingError::BeneficiaryNotFound
         );
+        require!(ctx.accounts.claim_ata.amount == 0, VestingError::ClaimAtaNotEmpty);
 
-        let transfer_amount = u64::try_from(claimable).map_err(|_| VestingError::MathOverflow)?;
-
-        require!(escrow_wallet.amount >= transfer_amount, VestingError::InsufficientBalance);
-        
-        data_account.beneficiaries[index].claimed_tokens = data_account.beneficiaries[index].claimed_tokens
-            .checked_add(transfer_amount)
-            .ok_or(VestingError::MathOverflow)?;
-        
-        token::transfer(cpi_ctx, transfer_amount)?;
-
-        emit!(TokensClaimed {
-            beneficiary: sender.key(),
-            amount: transfer_amount,
-            timestamp: now,
-        });
+        let close_instruction = CloseAccount {
+            account: ctx.accounts.claim_ata.to_account_info(),
+            destination: ctx.accounts.sender.to_account_info(),
+            authority: ctx.accounts.sender.to_account_info(),
+        };
+        token::close_account(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            close_instruction,
+        ))?;
 
         Ok(())
     }
@@ -275,73 +518,121 @@ pub mod vesting {
     /// # Arguments
     /// * `data_bump` - Bump seed for data account PDA validation
     /// * `escrow_bump` - Bump seed for escrow wallet PDA validation
+    /// * `skip_on_error` - Skip beneficiaries whose math overflows instead of failing
+    /// * `split` - Treasuries sharing the recovered tokens (None = all to `admin_wallet`)
     /// 
     /// # Withdrawal Logic
     /// 1. Check if grace period has passed for each beneficiary
     /// 2. Calculate unclaimed tokens for expired beneficiaries
-    /// 3. Transfer total unclaimed amount to admin wallet
+    /// 3. Transfer total unclaimed amount to admin wallet, or across the `split` treasuries
     /// 4. Mark beneficiaries as fully claimed to prevent future claims
+    /// 
+    /// Beneficiaries are processed in ascending index (stored) order. Each one with tokens
+    /// recovered emits `BeneficiaryUnclaimedWithdrawn` carrying its index, in that order,
+    /// followed by a single `AllUnclaimedWithdrawn` with the total.
+    /// 
+    /// With `skip_on_error`, a beneficiary whose recovery math fails (only possible with
+    /// corrupted amounts overflowing u64 or claimed above the allocation) is left untouched
+    /// and reported with `BeneficiarySkipped` instead of aborting the whole withdrawal.
+    /// 
+    /// With `split`, `remaining_accounts` holds one writable token account of the mint per
+    /// entry, in order, owned by the entry's `wallet`; `admin_wallet` is then not credited.
+    /// The shares must sum to 10_000 bps (see `split_amounts` for the rounding).
     ///    
     /// # Grace Period Calculation
     /// Withdrawal allowed after: MAX(cliff_end + grace_period, vesting_end + grace_period)
     /// This ensures beneficiaries have sufficient time to claim after both cliff and full vesting
-    pub fn withdraw(
-        ctx: Context<WithdrawUnclaimed>,
+    pub fn withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawUnclaimed<'info>>,
         data_bump: u8,
         escrow_bump: u8,
+        skip_on_error: bool,
+        split: Option<Vec<TreasurySplit>>,
     ) -> Result<()> {
         let data_account = &mut ctx.accounts.data_account;
+        data_account.check_beneficiary_count()?;
         let escrow_wallet = &ctx.accounts.escrow_wallet;
         let admin_wallet = &ctx.accounts.admin_wallet;
         let token_mint_key = &ctx.accounts.token_mint.key();
 
         // Validate escrow wallet PDA
-        let (expected_escrow_pda, expected_escrow_bump) = Pubkey::find_program_address(
-            &[b"escrow_wallet".as_ref(), token_mint_key.as_ref()],
-            ctx.program_id
-        );
-        require!(
-            ctx.accounts.escrow_wallet.key() == expected_escrow_pda,
-            VestingError::InvalidEscrowWallet
-        );
+        verify_escrow_pda(
+            &escrow_wallet.key(),
+            token_mint_key,
+            data_account.escrow_nonce,
+            escrow_bump,
+            ctx.program_id,
+        )?;
         require!(
-            escrow_bump == expected_escrow_bump,
-            VestingError::InvalidEscrowBump
+            escrow_wallet.owner == data_account.key(),
+            VestingError::InvalidEscrowAuthority
         );
 
         require!(
             data_account.authority == ctx.accounts.admin.key(), 
             VestingError::UnauthorizedAdmin
         );
+        data_account.check_co_admin(ctx.accounts.co_admin.as_ref().map(|s| s.key()))?;
+        require!(!data_account.no_withdraw, VestingError::WithdrawDisabled);
+        require!(!data_account.drained, VestingError::ScheduleDrained);
 
-        let now = Clock::get()?.unix_timestamp;
+        let now = data_account.current_time()?;
+        require!(now >= 0, VestingError::InvalidClock);
+        let period = data_account.period_length();
+        let grace_period = GRACE_PERIOD_MONTHS * period;
         let mut total_unclaimed = 0u64;
         let mut _beneficiaries_processed = 0u32;
+        let mut exhausted = 0u32;
 
         for i in 0..data_account.beneficiaries.len() {
-            let beneficiary = &data_account.beneficiaries[i];
-
-            // Calculate when beneficiary can actually start claiming (after cliff)
-            let cliff_end_time = beneficiary.start_time + (beneficiary.cliff_months as i64 * SECONDS_PER_MONTH);
-            // Calculate when full vesting period ends
-            let total_vesting_period = beneficiary.start_time + (beneficiary.total_months as i64 * SECONDS_PER_MONTH);
-
-            let earliest_withdraw_time = std::cmp::max(cliff_end_time + GRACE_PERIOD, total_vesting_period + GRACE_PERIOD);
-
-            // Check if grace period has passed
-            if now > earliest_withdraw_time {
-                let unclaimed_tokens = beneficiary.allocated_tokens
-                    .saturating_sub(beneficiary.claimed_tokens);
+            let beneficiary = data_account.beneficiaries[i];
 
-                if unclaimed_tokens > 0 {
-                    total_unclaimed = total_unclaimed
+            // Only vested tokens past the grace period are recoverable. Nothing is
+            // written until the whole computation for this beneficiary has succeeded.
+            let recovery = recoverable_unclaimed(&beneficiary, now, period, grace_period)
+                .and_then(|unclaimed_tokens| {
+                    let new_total = total_unclaimed
                         .checked_add(unclaimed_tokens)
-                        .ok_or(VestingError::MathOverflow)?;
-                    data_account.beneficiaries[i].claimed_tokens = beneficiary.allocated_tokens;
-                    _beneficiaries_processed = _beneficiaries_processed
-                        .checked_add(1)
-                        .ok_or(VestingError::MathOverflow)?;
+                        .ok_or(error!(VestingError::MathOverflow))?;
+                    let claimed_tokens = beneficiary.claimed_tokens
+                        .checked_add(unclaimed_tokens)
+                        .ok_or(error!(VestingError::MathOverflow))?;
+                    Ok((unclaimed_tokens, new_total, claimed_tokens))
+                });
+            let (unclaimed_tokens, new_total, claimed_tokens) = match recovery {
+                Ok(recovery) => recovery,
+                Err(_) if skip_on_error => {
+                    let event_seq = data_account.next_event_seq()?;
+                    emit!(BeneficiarySkipped {
+                        admin: ctx.accounts.admin.key(),
+                        beneficiary: beneficiary.key,
+                        index: i as u32,
+                        event_seq,
+                    });
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+
+            if unclaimed_tokens > 0 {
+                total_unclaimed = new_total;
+                data_account.beneficiaries[i].claimed_tokens = claimed_tokens;
+                if data_account.beneficiaries[i].claimed_tokens >= beneficiary.allocated_tokens {
+                    exhausted += 1;
                 }
+                _beneficiaries_processed = _beneficiaries_processed
+                    .checked_add(1)
+                    .ok_or(VestingError::MathOverflow)?;
+
+                let event_seq = data_account.next_event_seq()?;
+                emit!(BeneficiaryUnclaimedWithdrawn {
+                    admin: ctx.accounts.admin.key(),
+                    beneficiary: beneficiary.key,
+                    index: i as u32,
+                    original_index: beneficiary.original_index,
+                    amount: unclaimed_tokens,
+                    event_seq,
+                });
             }
         }
 
@@ -355,281 +646,6431 @@ pub mod vesting {
         let seeds = &["data_account".as_bytes(), token_mint_key.as_ref(), &[data_bump]];
         let signer_seeds = &[&seeds[..]];
 
-        let transfer_instruction = Transfer {
-            from: escrow_wallet.to_account_info(),
-            to: admin_wallet.to_account_info(),
-            authority: data_account.to_account_info(),
+        // Destination token accounts and their shares, validated before anything moves
+        let payouts = match split.as_ref() {
+            None => vec![(admin_wallet.to_account_info(), total_unclaimed)],
+            Some(split) => {
+                require!(
+                    ctx.remaining_accounts.len() == split.len(),
+                    VestingError::InvalidTreasurySplit
+                );
+                let amounts = split_amounts(total_unclaimed, split)?;
+                let mut payouts = Vec::with_capacity(split.len());
+                for ((entry, info), amount) in split.iter().zip(ctx.remaining_accounts).zip(amounts) {
+                    let treasury = Account::<TokenAccount>::try_from(info)?;
+                    require!(
+                        treasury.owner == entry.wallet && treasury.mint == *token_mint_key,
+                        VestingError::InvalidTreasurySplit
+                    );
+                    payouts.push((info.clone(), amount));
+                }
+                payouts
+            }
         };
 
-        let cpi_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            transfer_instruction,
-            signer_seeds
-        );
+        let escrow_balance_before = escrow_wallet.amount;
+        for (destination, amount) in payouts {
+            if amount == 0 {
+                continue;
+            }
+            let transfer_instruction = Transfer {
+                from: escrow_wallet.to_account_info(),
+                to: destination.clone(),
+                authority: data_account.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                transfer_instruction,
+                signer_seeds
+            );
+            token::transfer(cpi_ctx, amount)?;
 
-        token::transfer(cpi_ctx, total_unclaimed)?;
+            if split.is_some() {
+                let event_seq = data_account.next_event_seq()?;
+                emit!(TreasuryPaid {
+                    admin: ctx.accounts.admin.key(),
+                    treasury: destination.key(),
+                    amount,
+                    event_seq,
+                });
+            }
+        }
+        ctx.accounts.escrow_wallet.reload()?;
+        verify_escrow_debit(escrow_balance_before, ctx.accounts.escrow_wallet.amount, total_unclaimed)?;
+
+        let event_seq = data_account.next_event_seq()?;
         emit!(AllUnclaimedWithdrawn {
            admin: ctx.accounts.admin.key(),
            total_amount: total_unclaimed,
            beneficiaries_processed: _beneficiaries_processed,
-           timestamp: now,
+           timestamp: Clock::get()?.unix_timestamp,
+           event_seq,
     });
 
+        record_audit(
+            data_account,
+            ctx.accounts.audit_log.as_mut(),
+            AuditOp::Withdraw,
+            ctx.accounts.admin.key(),
+            total_unclaimed,
+        )?;
+
+        record_exhausted(data_account, exhausted)?;
+        emit_escrow_change(
+            data_account,
+            escrow_balance_before,
+            ctx.accounts.escrow_wallet.amount,
+            EscrowChangeReason::Withdraw,
+        )?;
+        emit_schedule_state(data_account, ctx.accounts.escrow_wallet.amount)?;
+
         Ok(())
     }
 
-    /// Changes the admin of the vesting program.
+    /// Withdraws part of a single expired beneficiary's unclaimed tokens back to admin.
     /// 
-    /// This function allows the current admin to transfer ownership of the vesting program
-    /// to a new admin. The new admin must be a valid Solana address and must not be the same
-    /// as the current admin.
-    pub fn change_admin(
-        ctx: Context<ChangeAdmin>,
-        _data_bump: u8,
-    )-> Result<()> {
-        let  data_account = &mut ctx.accounts.data_account;
-        require!(data_account.authority == ctx.accounts.current_admin.key(), VestingError::UnauthorizedAdmin);
+    /// Same grace period rules as `withdraw`, but only `amount` is recovered and the
+    /// beneficiary's `claimed_tokens` is incremented by that much, leaving the rest
+    /// in escrow (e.g. as a buffer for a disputed beneficiary).
+    /// 
+    /// # Arguments
+    /// * `beneficiary` - Expired beneficiary to recover from
+    /// * `amount` - Tokens to recover in RAW UNITS (at most their unclaimed balance)
+    /// * `data_bump` - Bump seed for data account PDA validation
+    /// * `escrow_bump` - Bump seed for escrow wallet PDA validation
+    pub fn withdraw_amount(
+        ctx: Context<WithdrawAmount>,
+        beneficiary: Pubkey,
+        amount: u64,
+        data_bump: u8,
+        escrow_bump: u8,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.check_beneficiary_count()?;
+        let escrow_wallet = &ctx.accounts.escrow_wallet;
+        let token_mint_key = &ctx.accounts.token_mint.key();
 
-        data_account.authority = ctx.accounts.new_admin.key();
+        // Validate escrow wallet PDA
+        verify_escrow_pda(
+            &escrow_wallet.key(),
+            token_mint_key,
+            data_account.escrow_nonce,
+            escrow_bump,
+            ctx.program_id,
+        )?;
+        require!(
+            escrow_wallet.owner == data_account.key(),
+            VestingError::InvalidEscrowAuthority
+        );
 
-        emit!(AdminChanged {
-            old_admin: ctx.accounts.current_admin.key(),
-            new_admin: ctx.accounts.new_admin.key(),
-            timestamp: Clock::get()?.unix_timestamp
-        });
+        data_account.check_co_admin(ctx.accounts.co_admin.as_ref().map(|s| s.key()))?;
+        require!(!data_account.no_withdraw, VestingError::WithdrawDisabled);
+        require!(!data_account.drained, VestingError::ScheduleDrained);
+        require!(amount > 0, VestingError::InvalidAmount);
 
-        Ok(())  
-}
+        let index = data_account
+            .beneficiaries
+            .iter()
+            .position(|b| b.assigned && b.key == beneficiary)
+            .ok_or(VestingError::BeneficiaryNotFound)?;
+
+        let entry = data_account.beneficiaries[index];
+        let now = data_account.current_time()?;
+        let period = data_account.period_length();
+
+        // Only vested tokens past the grace period are recoverable
+        let unclaimed_tokens =
+            recoverable_unclaimed(&entry, now, period, GRACE_PERIOD_MONTHS * period)?;
+        require!(unclaimed_tokens > 0, VestingError::NoUnclaimedTokens);
+        require!(amount <= unclaimed_tokens, VestingError::AmountExceedsUnclaimed);
+        require!(escrow_wallet.amount >= amount, VestingError::InsufficientBalance);
+
+        data_account.beneficiaries[index].claimed_tokens = entry.claimed_tokens
+            .checked_add(amount)
+            .ok_or(VestingError::MathOverflow)?;
+
+        let seeds = &["data_account".as_bytes(), token_mint_key.as_ref(), &[data_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let transfer_instruction = Transfer {
+            from: escrow_wallet.to_account_info(),
+            to: ctx.accounts.admin_wallet.to_account_info(),
+            authority: data_account.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            transfer_instruction,
+            signer_seeds
+        );
+
+        let escrow_balance_before = escrow_wallet.amount;
+        token::transfer(cpi_ctx, amount)?;
+        ctx.accounts.escrow_wallet.reload()?;
+        verify_escrow_debit(escrow_balance_before, ctx.accounts.escrow_wallet.amount, amount)?;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(PartialUnclaimedWithdrawn {
+            admin: ctx.accounts.admin.key(),
+            beneficiary,
+            amount,
+            remaining_unclaimed: unclaimed_tokens - amount,
+            timestamp: Clock::get()?.unix_timestamp,
+            event_seq,
+        });
+
+        record_audit(
+            data_account,
+            ctx.accounts.audit_log.as_mut(),
+            AuditOp::WithdrawAmount,
+            ctx.accounts.admin.key(),
+            amount,
+        )?;
+
+        emit_escrow_change(
+            data_account,
+            escrow_balance_before,
+            ctx.accounts.escrow_wallet.amount,
+            EscrowChangeReason::Withdraw,
+        )?;
+
+        let updated = data_account.beneficiaries[index];
+        if updated.claimed_tokens >= updated.allocated_tokens {
+            record_exhausted(data_account, 1)?;
+        }
+        emit_schedule_state(data_account, ctx.accounts.escrow_wallet.amount)?;
+
+        Ok(())
+    }
+
+    /// Recovers a beneficiary's vested-but-unclaimed tokens after they stayed inactive for
+    /// the schedule's `inactivity_window_seconds`.
+    /// 
+    /// Inactivity is measured from the last claim, or from the end of the cliff if the
+    /// beneficiary never claimed (see `last_activity_time`). Unlike grace-period recovery this
+    /// works mid-vesting, but takes only what has vested so far: the unvested remainder keeps
+    /// vesting, and can be recovered the same way later while the beneficiary stays inactive.
+    /// 
+    /// # Arguments
+    /// * `beneficiary` - Inactive beneficiary to recover from
+    /// * `data_bump` - Bump seed for data account PDA validation
+    /// * `escrow_bump` - Bump seed for escrow wallet PDA validation
+    pub fn withdraw_inactive(
+        ctx: Context<WithdrawInactive>,
+        beneficiary: Pubkey,
+        data_bump: u8,
+        escrow_bump: u8,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.check_beneficiary_count()?;
+        let escrow_wallet = &ctx.accounts.escrow_wallet;
+        let token_mint_key = &ctx.accounts.token_mint.key();
+
+        // Validate escrow wallet PDA
+        verify_escrow_pda(
+            &escrow_wallet.key(),
+            token_mint_key,
+            data_account.escrow_nonce,
+            escrow_bump,
+            ctx.program_id,
+        )?;
+        require!(
+            escrow_wallet.owner == data_account.key(),
+            VestingError::InvalidEscrowAuthority
+        );
+
+        data_account.check_co_admin(ctx.accounts.co_admin.as_ref().map(|s| s.key()))?;
+        require!(!data_account.no_withdraw, VestingError::WithdrawDisabled);
+        require!(!data_account.drained, VestingError::ScheduleDrained);
+        require!(
+            data_account.inactivity_window_seconds > 0,
+            VestingError::InactivityRecoveryDisabled
+        );
+
+        let index = data_account
+            .beneficiaries
+            .iter()
+            .position(|b| b.assigned && b.key == beneficiary)
+            .ok_or(VestingError::BeneficiaryNotFound)?;
+
+        let entry = data_account.beneficiaries[index];
+        let now = data_account.current_time()?;
+        let last_activity = last_activity_time(&entry, data_account.period_length())?;
+        require!(
+            now.saturating_sub(last_activity) > data_account.inactivity_window_seconds as i64,
+            VestingError::BeneficiaryNotInactive
+        );
+
+        let amount = data_account
+            .compute_vested_for(&entry, now)?
+            .saturating_sub(entry.claimed_tokens);
+        require!(amount > 0, VestingError::NoUnclaimedTokens);
+        require!(escrow_wallet.amount >= amount, VestingError::InsufficientBalance);
+
+        data_account.beneficiaries[index].claimed_tokens = entry.claimed_tokens
+            .checked_add(amount)
+            .ok_or(VestingError::MathOverflow)?;
+
+        let seeds = &["data_account".as_bytes(), token_mint_key.as_ref(), &[data_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let transfer_instruction = Transfer {
+            from: escrow_wallet.to_account_info(),
+            to: ctx.accounts.admin_wallet.to_account_info(),
+            authority: data_account.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            transfer_instruction,
+            signer_seeds
+        );
+
+        let escrow_balance_before = escrow_wallet.amount;
+        token::transfer(cpi_ctx, amount)?;
+        ctx.accounts.escrow_wallet.reload()?;
+        verify_escrow_debit(escrow_balance_before, ctx.accounts.escrow_wallet.amount, amount)?;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(InactiveUnclaimedWithdrawn {
+            admin: ctx.accounts.admin.key(),
+            beneficiary,
+            amount,
+            last_activity,
+            timestamp: Clock::get()?.unix_timestamp,
+            event_seq,
+        });
+
+        record_audit(
+            data_account,
+            ctx.accounts.audit_log.as_mut(),
+            AuditOp::WithdrawInactive,
+            ctx.accounts.admin.key(),
+            amount,
+        )?;
+
+        emit_escrow_change(
+            data_account,
+            escrow_balance_before,
+            ctx.accounts.escrow_wallet.amount,
+            EscrowChangeReason::Withdraw,
+        )?;
+
+        let updated = data_account.beneficiaries[index];
+        if updated.claimed_tokens >= updated.allocated_tokens {
+            record_exhausted(data_account, 1)?;
+        }
+        emit_schedule_state(data_account, ctx.accounts.escrow_wallet.amount)?;
+
+        Ok(())
+    }
+
+    /// Moves the entire escrow balance to the admin's wallet and marks the schedule drained.
+    /// 
+    /// Last-resort recovery for incidents such as a discovered exploit. Bypasses the grace
+    /// period, so it is heavily gated: the schedule must have been initialized with
+    /// `emergency_enabled` and be paused via `set_paused`. Once drained, every claim and
+    /// withdraw is rejected permanently.
+    /// 
+    /// # Arguments
+    /// * `data_bump` - Bump seed for data account PDA validation
+    /// * `escrow_bump` - Bump seed for escrow wallet PDA validation
+    pub fn emergency_drain(
+        ctx: Context<WithdrawUnclaimed>,
+        data_bump: u8,
+        escrow_bump: u8,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let escrow_wallet = &ctx.accounts.escrow_wallet;
+        let token_mint_key = &ctx.accounts.token_mint.key();
+
+        // Validate escrow wallet PDA
+        verify_escrow_pda(
+            &escrow_wallet.key(),
+            token_mint_key,
+            data_account.escrow_nonce,
+            escrow_bump,
+            ctx.program_id,
+        )?;
+        require!(
+            escrow_wallet.owner == data_account.key(),
+            VestingError::InvalidEscrowAuthority
+        );
+
+        require!(
+            data_account.authority == ctx.accounts.admin.key(),
+            VestingError::UnauthorizedAdmin
+        );
+        data_account.check_co_admin(ctx.accounts.co_admin.as_ref().map(|s| s.key()))?;
+        require!(data_account.emergency_enabled, VestingError::EmergencyDisabled);
+        require!(data_account.paused, VestingError::ScheduleNotPaused);
+        require!(!data_account.drained, VestingError::ScheduleDrained);
+
+        let amount = escrow_wallet.amount;
+        if amount > 0 {
+            let seeds = &["data_account".as_bytes(), token_mint_key.as_ref(), &[data_bump]];
+            let signer_seeds = &[&seeds[..]];
+
+            let transfer_instruction = Transfer {
+                from: escrow_wallet.to_account_info(),
+                to: ctx.accounts.admin_wallet.to_account_info(),
+                authority: data_account.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                transfer_instruction,
+                signer_seeds
+            );
+
+            token::transfer(cpi_ctx, amount)?;
+            ctx.accounts.escrow_wallet.reload()?;
+            verify_escrow_debit(amount, ctx.accounts.escrow_wallet.amount, amount)?;
+        }
+
+        data_account.drained = true;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(EmergencyDrain {
+            admin: ctx.accounts.admin.key(),
+            recipient: ctx.accounts.admin_wallet.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+            event_seq,
+        });
+
+        record_audit(
+            data_account,
+            ctx.accounts.audit_log.as_mut(),
+            AuditOp::EmergencyDrain,
+            ctx.accounts.admin.key(),
+            amount,
+        )?;
+
+        emit_escrow_change(
+            data_account,
+            amount,
+            ctx.accounts.escrow_wallet.amount,
+            EscrowChangeReason::EmergencyDrain,
+        )?;
+        emit_schedule_state(data_account, ctx.accounts.escrow_wallet.amount)?;
+
+        Ok(())
+    }
+
+    /// Sweeps a leftover escrow balance below `DUST_THRESHOLD` to the admin wallet.
+    /// 
+    /// Only once every allocation has been claimed or recovered (`active_beneficiaries == 0`),
+    /// so nothing a beneficiary is owed can be taken. Rounding or transfer fees can leave
+    /// such dust behind; sweeping it leaves the escrow empty so the schedule can be wound down.
+    /// Unlike `withdraw_amount`, this also works on `no_withdraw` schedules.
+    /// 
+    /// # Arguments
+    /// * `data_bump` - Bump seed for data account PDA validation
+    /// * `escrow_bump` - Bump seed for escrow wallet PDA validation
+    pub fn force_unlock_dust(
+        ctx: Context<WithdrawUnclaimed>,
+        data_bump: u8,
+        escrow_bump: u8,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let escrow_wallet = &ctx.accounts.escrow_wallet;
+        let token_mint_key = &ctx.accounts.token_mint.key();
+
+        // Validate escrow wallet PDA
+        verify_escrow_pda(
+            &escrow_wallet.key(),
+            token_mint_key,
+            data_account.escrow_nonce,
+            escrow_bump,
+            ctx.program_id,
+        )?;
+
+        require!(
+            data_account.authority == ctx.accounts.admin.key(),
+            VestingError::UnauthorizedAdmin
+        );
+        data_account.check_co_admin(ctx.accounts.co_admin.as_ref().map(|s| s.key()))?;
+        data_account.check_beneficiary_count()?;
+        require!(data_account.activated, VestingError::ScheduleNotActivated);
+        require!(data_account.active_beneficiaries == 0, VestingError::VestingNotComplete);
+
+        let amount = escrow_wallet.amount;
+        require!(amount > 0 && amount < DUST_THRESHOLD, VestingError::NotDust);
+
+        let seeds = &["data_account".as_bytes(), token_mint_key.as_ref(), &[data_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let transfer_instruction = Transfer {
+            from: escrow_wallet.to_account_info(),
+            to: ctx.accounts.admin_wallet.to_account_info(),
+            authority: data_account.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            transfer_instruction,
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx, amount)?;
+        ctx.accounts.escrow_wallet.reload()?;
+        verify_escrow_debit(amount, ctx.accounts.escrow_wallet.amount, amount)?;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(DustSwept {
+            admin: ctx.accounts.admin.key(),
+            recipient: ctx.accounts.admin_wallet.key(),
+            amount,
+            event_seq,
+        });
+
+        record_audit(
+            data_account,
+            ctx.accounts.audit_log.as_mut(),
+            AuditOp::DustSweep,
+            ctx.accounts.admin.key(),
+            amount,
+        )?;
+
+        emit_escrow_change(
+            data_account,
+            amount,
+            ctx.accounts.escrow_wallet.amount,
+            EscrowChangeReason::DustSweep,
+        )?;
+        emit_schedule_state(data_account, ctx.accounts.escrow_wallet.amount)?;
+
+        Ok(())
+    }
+
+    /// Changes the admin of the vesting program.
+    /// 
+    /// This function allows the current admin to transfer ownership of the vesting program
+    /// to a new admin. The new admin must be a valid Solana address and must not be the same
+    /// as the current admin.
+    pub fn change_admin(
+        ctx: Context<ChangeAdmin>,
+        _data_bump: u8,
+    )-> Result<()> {
+        let  data_account = &mut ctx.accounts.data_account;
+        require!(data_account.authority == ctx.accounts.current_admin.key(), VestingError::UnauthorizedAdmin);
+
+        data_account.authority = ctx.accounts.new_admin.key();
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(AdminChanged {
+            old_admin: ctx.accounts.current_admin.key(),
+            new_admin: ctx.accounts.new_admin.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+            event_seq,
+        });
+
+        record_audit(
+            data_account,
+            ctx.accounts.audit_log.as_mut(),
+            AuditOp::ChangeAdmin,
+            ctx.accounts.current_admin.key(),
+            0,
+        )?;
+
+        Ok(())  
+}
+
+    /// Asserts that a beneficiary's claimable amount equals an expected value.
+    /// 
+    /// Intended for reconciliation tooling (CI, monitoring) that wants to pin the
+    /// expected vesting state transactionally. No tokens are transferred.
+    /// 
+    /// # Arguments
+    /// * `beneficiary` - Beneficiary address to check
+    /// * `expected` - Expected claimable amount in RAW UNITS
+    pub fn assert_claimable(
+        ctx: Context<ReadSchedule>,
+        beneficiary: Pubkey,
+        expected: u64,
+    ) -> Result<()> {
+        let data_account = &ctx.accounts.data_account;
+
+        let entry = data_account
+            .beneficiaries
+            .iter()
+            .find(|b| b.assigned && b.key == beneficiary)
+            .ok_or(VestingError::BeneficiaryNotFound)?;
+
+        let now = data_account.current_time()?;
+        let claimable = data_account
+            .compute_vested_for(entry, now)?
+            .saturating_sub(entry.claimed_tokens);
+
+        require!(claimable == expected, VestingError::ClaimableAssertionFailed);
+
+        Ok(())
+    }
+
+    /// Returns the current claimable amount of each address, in input order - RAW UNITS.
+    /// 
+    /// Lets custodians fetch many beneficiaries in one call. Non-members and unassigned
+    /// slots report 0. Larger lists than `MAX_CLAIMABLE_BATCH` are split across calls by
+    /// the client.
+    /// 
+    /// # Arguments
+    /// * `beneficiaries` - Addresses to query
+    pub fn get_claimable_batch(ctx: Context<ReadSchedule>, beneficiaries: Vec<Pubkey>) -> Result<Vec<u64>> {
+        require!(beneficiaries.len() <= MAX_CLAIMABLE_BATCH, VestingError::BatchTooLarge);
+        let data_account = &ctx.accounts.data_account;
+        let now = data_account.current_time()?;
+
+        beneficiaries
+            .iter()
+            .map(|key| {
+                match data_account.beneficiaries.iter().find(|b| b.assigned && b.key == *key) {
+                    Some(entry) => Ok(data_account
+                        .compute_vested_for(entry, now)?
+                        .saturating_sub(entry.claimed_tokens)),
+                    None => Ok(0),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns whether `beneficiary` could claim right now and, if not, the first reason
+    /// `claim` would reject them, so wallets can explain a failure before submitting.
+    /// 
+    /// Follows the same checks as `claim` in the same order (see `claim_eligibility`). Fails
+    /// with `MilestonesRequired` for milestone beneficiaries, whose unlocks live in side accounts.
+    /// 
+    /// # Arguments
+    /// * `beneficiary` - Address to check
+    pub fn get_claim_eligibility(
+        ctx: Context<ReadSchedule>,
+        beneficiary: Pubkey,
+    ) -> Result<ClaimEligibility> {
+        let data_account = &ctx.accounts.data_account;
+        claim_eligibility(
+            data_account,
+            beneficiary,
+            data_account.current_time()?,
+            Clock::get()?.unix_timestamp,
+        )
+    }
+
+    /// Returns what `beneficiary` would actually receive by claiming right now: the claimable
+    /// amount after the minimum-claim and `max_per_claim` rules, or 0 with the reason `claim`
+    /// would fail (pause, cooldown, blackout, minimum, ...).
+    /// 
+    /// Adds the escrow balance check `claim` makes before transferring to the checks of
+    /// `get_claim_eligibility`. Claims go through the SPL Token program, which takes no transfer
+    /// fee, so the amount sent is the amount received.
+    /// 
+    /// # Arguments
+    /// * `beneficiary` - Address to check
+    pub fn get_net_claimable(
+        ctx: Context<ReadScheduleWithEscrow>,
+        beneficiary: Pubkey,
+    ) -> Result<NetClaimable> {
+        let data_account = &ctx.accounts.data_account;
+        let status = claim_eligibility(
+            data_account,
+            beneficiary,
+            data_account.current_time()?,
+            Clock::get()?.unix_timestamp,
+        )?;
+        Ok(net_claimable(status, ctx.accounts.escrow_wallet.amount))
+    }
+
+    /// Adds new beneficiaries to an existing vesting schedule.
+    /// 
+    /// The data account is reallocated to `calculate_vesting_space!(combined_len)` and
+    /// the combined list must still respect `MAX_BENEFICIARIES`. New allocations are
+    /// covered by unallocated escrow tokens plus an optional top-up from the admin.
+    /// 
+    /// # Arguments
+    /// * `beneficiaries` - Beneficiary configurations to append
+    /// * `amount` - Additional tokens to fund in RAW UNITS (may be 0)
+    /// * `data_bump` - Bump seed for data account PDA validation
+    pub fn add_beneficiaries(
+        ctx: Context<AddBeneficiaries>,
+        mut beneficiaries: Vec<Beneficiary>,
+        amount: u64,
+        _data_bump: u8,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.check_beneficiary_count()?;
+        let now = data_account.current_time()?;
+        let max_start_delay = data_account.max_start_delay();
+        let period = data_account.period_length();
+        let min_months = data_account.min_vesting_months();
+
+        // Two-phase schedules are filled with `append_beneficiaries` until activated
+        require!(data_account.activated, VestingError::ScheduleNotActivated);
+        require!(!beneficiaries.is_empty(), VestingError::NoBeneficiaries);
+
+        let combined_len = data_account
+            .beneficiaries
+            .len()
+            .checked_add(beneficiaries.len())
+            .ok_or(VestingError::MathOverflow)?;
+        require!(combined_len <= MAX_BENEFICIARIES, VestingError::TooManyBeneficiaries);
+
+        let mut seen: std::collections::HashSet<Pubkey> =
+            data_account.beneficiaries.iter().map(|b| b.key).collect();
+
+        for b in beneficiaries.iter_mut() {
+            validate_beneficiary(b, now, max_start_delay, period, min_months)?;
+            require!(seen.insert(b.key), VestingError::DuplicateBeneficiary);
+            b.assigned = true;
+        }
+
+        let new_token_amount = data_account
+            .token_amount
+            .checked_add(amount)
+            .ok_or(VestingError::MathOverflow)?;
+
+        let mut total_allocated = 0u64;
+        for b in data_account.beneficiaries.iter().chain(beneficiaries.iter()) {
+            total_allocated = total_allocated
+                .checked_add(b.allocated_tokens)
+                .ok_or(VestingError::MathOverflow)?;
+        }
+        require!(total_allocated <= new_token_amount, VestingError::OverAllocation);
+        if data_account.no_withdraw {
+            require!(total_allocated == new_token_amount, VestingError::UnallocatedSurplus);
+        }
+        // Only `AdminSweep` lets admin allocate the existing surplus to new beneficiaries
+        if data_account.surplus_policy != SurplusPolicy::AdminSweep {
+            require!(
+                total_allocation(&beneficiaries)? <= amount,
+                VestingError::SurplusPolicyViolation
+            );
+        }
+
+        let added_count = beneficiaries.len() as u32;
+        data_account.assign_original_indices(&mut beneficiaries)?;
+        data_account.add_active_beneficiaries(&beneficiaries)?;
+        data_account.beneficiaries.extend(beneficiaries);
+        data_account.sync_beneficiary_count();
+        data_account.token_amount = new_token_amount;
+
+        let escrow_balance_before = ctx.accounts.escrow_wallet.amount;
+        if amount > 0 {
+            require!(ctx.accounts.wallet_to_withdraw_from.amount >= amount, VestingError::InsufficientBalance);
+
+            let transfer_instruction = Transfer {
+                from: ctx.accounts.wallet_to_withdraw_from.to_account_info(),
+                to: ctx.accounts.escrow_wallet.to_account_info(),
+                authority: ctx.accounts.admin.to_account_info(),
+            };
+
+            let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), transfer_instruction);
+
+            token::transfer(cpi_ctx, amount)?;
+            ctx.accounts.escrow_wallet.reload()?;
+        }
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(BeneficiariesAdded {
+            admin: ctx.accounts.admin.key(),
+            added_count,
+            beneficiaries_count: data_account.beneficiaries.len() as u32,
+            funded_amount: amount,
+            event_seq,
+        });
+
+        record_audit(
+            data_account,
+            ctx.accounts.audit_log.as_mut(),
+            AuditOp::AddBeneficiaries,
+            ctx.accounts.admin.key(),
+            amount,
+        )?;
+
+        emit_escrow_change(
+            data_account,
+            escrow_balance_before,
+            ctx.accounts.escrow_wallet.amount,
+            EscrowChangeReason::Fund,
+        )?;
+        emit_schedule_state(data_account, ctx.accounts.escrow_wallet.amount)?;
+
+        Ok(())
+    }
+
+    /// Reserves allocations for beneficiaries whose wallets are not known yet.
+    /// 
+    /// Each slot is stored as an unassigned beneficiary with a default key and cannot be
+    /// claimed until `assign_slot` fills in the wallet. Slots are funded from unallocated
+    /// escrow tokens; top up first (e.g. via `add_beneficiaries`) if needed.
+    /// 
+    /// # Arguments
+    /// * `slots` - Allocation and schedule of each reserved slot
+    /// * `data_bump` - Bump seed for data account PDA validation
+    pub fn reserve_slots(
+        ctx: Context<ReserveSlots>,
+        slots: Vec<SlotReservation>,
+        _data_bump: u8,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.check_beneficiary_count()?;
+        let now = data_account.current_time()?;
+        let max_start_delay = data_account.max_start_delay();
+        let period = data_account.period_length();
+        let min_months = data_account.min_vesting_months();
+
+        require!(data_account.activated, VestingError::ScheduleNotActivated);
+        require!(
+            data_account.surplus_policy == SurplusPolicy::AdminSweep,
+            VestingError::SurplusPolicyViolation
+        );
+        require!(!slots.is_empty(), VestingError::NoBeneficiaries);
+
+        let combined_len = data_account
+            .beneficiaries
+            .len()
+            .checked_add(slots.len())
+            .ok_or(VestingError::MathOverflow)?;
+        require!(combined_len <= MAX_BENEFICIARIES, VestingError::TooManyBeneficiaries);
+
+        let mut reserved: Vec<Beneficiary> = slots
+            .iter()
+            .map(|slot| Beneficiary {
+                allocated_tokens: slot.amount,
+                start_time: slot.start_time,
+                cliff_months: slot.cliff_months,
+                total_months: slot.total_months,
+                assigned: false,
+                ..Default::default()
+            })
+            .collect();
+        for b in reserved.iter() {
+            validate_beneficiary(b, now, max_start_delay, period, min_months)?;
+        }
+
+        let total_allocated = total_allocation(&data_account.beneficiaries)?
+            .checked_add(total_allocation(&reserved)?)
+            .ok_or(VestingError::MathOverflow)?;
+        require!(total_allocated <= data_account.token_amount, VestingError::OverAllocation);
+
+        let reserved_count = reserved.len() as u32;
+        data_account.assign_original_indices(&mut reserved)?;
+        data_account.add_active_beneficiaries(&reserved)?;
+        data_account.beneficiaries.extend(reserved);
+        data_account.sync_beneficiary_count();
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(SlotsReserved {
+            admin: ctx.accounts.admin.key(),
+            reserved_count,
+            beneficiaries_count: data_account.beneficiaries.len() as u32,
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Assigns a wallet to a slot reserved by `reserve_slots`, making it claimable.
+    /// 
+    /// # Arguments
+    /// * `slot_index` - Index of the slot in the beneficiaries list
+    /// * `beneficiary` - Wallet that will own the slot
+    pub fn assign_slot(
+        ctx: Context<UpdateConfig>,
+        _data_bump: u8,
+        slot_index: u32,
+        beneficiary: Pubkey,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.check_beneficiary_count()?;
+
+        require!(beneficiary != Pubkey::default(), VestingError::InvalidAddress);
+        require!(
+            !data_account.beneficiaries.iter().any(|b| b.assigned && b.key == beneficiary),
+            VestingError::DuplicateBeneficiary
+        );
+
+        let slot = data_account
+            .beneficiaries
+            .get_mut(slot_index as usize)
+            .ok_or(VestingError::InvalidSlotIndex)?;
+        require!(!slot.assigned, VestingError::SlotAlreadyAssigned);
+
+        slot.key = beneficiary;
+        slot.assigned = true;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(SlotAssigned {
+            admin: ctx.accounts.admin.key(),
+            slot_index,
+            beneficiary,
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Forfeits the calling beneficiary's remaining allocation.
+    /// 
+    /// The beneficiary irrevocably declines their grant: every token not yet claimed
+    /// (vested or not) is returned to the admin wallet and the entry is marked as
+    /// fully claimed so no further claims are possible.
+    /// 
+    /// With `settlement_amount`, the beneficiary instead accepts a negotiated exit: their
+    /// allocation is capped at `settlement_amount` (between the claimed amount and the original
+    /// allocation) and only the difference is returned. Vesting follows the original curve up
+    /// to the cap, `min(original_vested, settlement_amount)`: a settlement below the amount
+    /// vested so far is claimable in full at once, one above it keeps vesting until reached.
+    /// Not available to milestone beneficiaries.
+    /// 
+    /// # Arguments
+    /// * `data_bump` - Bump seed for data account PDA validation
+    /// * `escrow_bump` - Bump seed for escrow wallet PDA validation
+    /// * `settlement_amount` - Allocation to keep - RAW UNITS (None = forfeit everything unclaimed)
+    pub fn forfeit(
+        ctx: Context<Forfeit>,
+        data_bump: u8,
+        escrow_bump: u8,
+        settlement_amount: Option<u64>,
+    ) -> Result<()> {
+        let beneficiary_key = ctx.accounts.beneficiary.key();
+        let escrow_wallet = &ctx.accounts.escrow_wallet;
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.check_beneficiary_count()?;
+        let token_mint_key = &ctx.accounts.token_mint.key();
+
+        // Validate escrow wallet PDA
+        verify_escrow_pda(
+            &escrow_wallet.key(),
+            token_mint_key,
+            data_account.escrow_nonce,
+            escrow_bump,
+            ctx.program_id,
+        )?;
+        require!(
+            escrow_wallet.owner == data_account.key(),
+            VestingError::InvalidEscrowAuthority
+        );
+
+        let index = data_account
+            .beneficiaries
+            .iter()
+            .position(|b| b.assigned && b.key == beneficiary_key)
+            .ok_or(VestingError::BeneficiaryNotFound)?;
+
+        let beneficiary = data_account.beneficiaries[index];
+        let retained = match settlement_amount {
+            Some(settlement) => {
+                require!(
+                    settlement >= beneficiary.claimed_tokens,
+                    VestingError::SettlementBelowClaimed
+                );
+                require!(
+                    settlement <= beneficiary.allocated_tokens,
+                    VestingError::SettlementAboveAllocation
+                );
+                require!(
+                    beneficiary.curve != VestingCurve::Milestones,
+                    VestingError::InvalidMilestones
+                );
+                settlement
+            }
+            None => beneficiary.claimed_tokens,
+        };
+        let forfeited = beneficiary.allocated_tokens.saturating_sub(retained);
+
+        require!(forfeited > 0, VestingError::NothingToForfeit);
+        require!(escrow_wallet.amount >= forfeited, VestingError::InsufficientBalance);
+
+        if settlement_amount.is_some() {
+            // The first settlement records the curve the cap applies to
+            if beneficiary.settled_from == 0 {
+                data_account.beneficiaries[index].settled_from = beneficiary.allocated_tokens;
+            }
+            // The returned tokens leave escrow for good, so they no longer count as funded
+            data_account.beneficiaries[index].allocated_tokens = retained;
+            data_account.token_amount = data_account
+                .token_amount
+                .checked_sub(forfeited)
+                .ok_or(VestingError::MathOverflow)?;
+        } else {
+            data_account.beneficiaries[index].claimed_tokens = beneficiary.allocated_tokens;
+        }
+        let exhausted = retained == beneficiary.claimed_tokens;
+
+        let seeds = &["data_account".as_bytes(), token_mint_key.as_ref(), &[data_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let transfer_instruction = Transfer {
+            from: escrow_wallet.to_account_info(),
+            to: ctx.accounts.admin_wallet.to_account_info(),
+            authority: data_account.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            transfer_instruction,
+            signer_seeds
+        );
+
+        let escrow_balance_before = escrow_wallet.amount;
+        token::transfer(cpi_ctx, forfeited)?;
+        ctx.accounts.escrow_wallet.reload()?;
+        verify_escrow_debit(escrow_balance_before, ctx.accounts.escrow_wallet.amount, forfeited)?;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(BeneficiaryForfeited {
+            beneficiary: beneficiary_key,
+            amount: forfeited,
+            settlement_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+            event_seq,
+        });
+
+        record_exhausted(data_account, exhausted as u32)?;
+        emit_escrow_change(
+            data_account,
+            escrow_balance_before,
+            ctx.accounts.escrow_wallet.amount,
+            EscrowChangeReason::Forfeit,
+        )?;
+        emit_schedule_state(data_account, ctx.accounts.escrow_wallet.amount)?;
+
+        Ok(())
+    }
+
+    /// Sets the maximum amount a single `claim` may transfer.
+    /// 
+    /// Beneficiaries with a larger claimable amount must split it across several
+    /// claims. A value of 0 disables the limit.
+    /// 
+    /// # Arguments
+    /// * `max_per_claim` - Per-claim limit in RAW UNITS (0 = unlimited)
+    pub fn set_max_per_claim(
+        ctx: Context<UpdateConfig>,
+        _data_bump: u8,
+        max_per_claim: u64,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.max_per_claim = max_per_claim;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(MaxPerClaimUpdated {
+            admin: ctx.accounts.admin.key(),
+            max_per_claim,
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Sets the minimum amount a single `claim` must be able to transfer.
+    /// 
+    /// Claims below the threshold are rejected unless they exhaust the
+    /// beneficiary's allocation. A value of 0 disables the threshold.
+    /// 
+    /// # Arguments
+    /// * `min_claim_amount` - Minimum claimable amount in RAW UNITS (0 = disabled)
+    pub fn set_min_claim_amount(
+        ctx: Context<UpdateConfig>,
+        _data_bump: u8,
+        min_claim_amount: u64,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.min_claim_amount = min_claim_amount;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(MinClaimAmountUpdated {
+            admin: ctx.accounts.admin.key(),
+            min_claim_amount,
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Sets the minimum time between two claims of the same beneficiary.
+    /// 
+    /// The claim that exhausts a beneficiary's allocation is never blocked.
+    /// A value of 0 disables the cooldown.
+    /// 
+    /// # Arguments
+    /// * `claim_cooldown_seconds` - Cooldown in seconds (0 = disabled)
+    pub fn set_claim_cooldown(
+        ctx: Context<UpdateConfig>,
+        _data_bump: u8,
+        claim_cooldown_seconds: u64,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.claim_cooldown_seconds = claim_cooldown_seconds;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(ClaimCooldownUpdated {
+            admin: ctx.accounts.admin.key(),
+            claim_cooldown_seconds,
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Sets the escrow collateral ratio `check_collateral` alarms below.
+    /// 
+    /// The ratio is in basis points of the outstanding obligations (allocated minus claimed),
+    /// so 10_000 (the default) only alarms on an actual shortfall and 10_500 keeps a 5% buffer.
+    /// 
+    /// # Arguments
+    /// * `min_collateral_bps` - Alarm threshold in basis points (at least 10_000)
+    pub fn set_min_collateral_bps(
+        ctx: Context<UpdateConfig>,
+        _data_bump: u8,
+        min_collateral_bps: u16,
+    ) -> Result<()> {
+        require!(
+            min_collateral_bps >= BPS_DENOMINATOR,
+            VestingError::InvalidCollateralThreshold
+        );
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.min_collateral_bps = min_collateral_bps;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(MinCollateralUpdated {
+            admin: ctx.accounts.admin.key(),
+            min_collateral_bps,
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Compares the escrow balance with the outstanding obligations scaled by the schedule's
+    /// `min_collateral_bps` and emits `CollateralBelowThreshold` when it falls short.
+    /// 
+    /// Permissionless, for monitoring bots; the escrow is checked by address, so no bumps
+    /// are needed.
+    pub fn check_collateral(ctx: Context<CheckCollateral>) -> Result<CollateralStatus> {
+        let escrow_balance = ctx.accounts.escrow_wallet.amount;
+        let data_account = &mut ctx.accounts.data_account;
+        let min_collateral_bps = data_account.collateral_threshold_bps();
+        let status = collateral_status(&data_account.beneficiaries, escrow_balance, min_collateral_bps)?;
+
+        if !status.collateralized {
+            let event_seq = data_account.next_event_seq()?;
+            emit!(CollateralBelowThreshold {
+                escrow_balance,
+                obligations: status.obligations,
+                required: status.required,
+                shortfall: status.shortfall,
+                min_collateral_bps,
+                event_seq,
+            });
+        }
+
+        Ok(status)
+    }
+
+    /// Distributes the unallocated surplus to beneficiaries pro-rata to their allocations.
+    /// 
+    /// Only for schedules initialized with `SurplusPolicy::ProRataBeneficiaries`, and only
+    /// once every beneficiary has finished vesting. Exhausted entries (fully claimed, forfeited
+    /// or recovered by `withdraw`) are closed for good and take no part. See `surplus_shares`;
+    /// the rounding dust stays unallocated. The increase is claimable immediately. Also callable
+    /// by the fund manager.
+    pub fn distribute_surplus(ctx: Context<DistributeSurplus>, _data_bump: u8) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.check_role(AdminRole::FundManager, ctx.accounts.operator.key())?;
+        data_account.check_beneficiary_count()?;
+        require!(
+            data_account.surplus_policy == SurplusPolicy::ProRataBeneficiaries,
+            VestingError::SurplusPolicyViolation
+        );
+
+        let now = data_account.current_time()?;
+        let period = data_account.period_length();
+        for b in data_account.beneficiaries.iter().filter(|b| b.claimed_tokens < b.allocated_tokens) {
+            // Milestone sums are fixed, so their share could never be claimed
+            require!(b.curve != VestingCurve::Milestones, VestingError::SurplusPolicyViolation);
+            require!(now >= vesting_end_time(b, period)?, VestingError::VestingNotComplete);
+        }
+
+        let total_allocated = total_allocation(&data_account.beneficiaries)?;
+        let surplus = data_account.token_amount.saturating_sub(total_allocated);
+        require!(surplus > 0, VestingError::NoSurplus);
+        let shares = surplus_shares(&data_account.beneficiaries, surplus)?;
+
+        let mut distributed = 0u64;
+        for (b, share) in data_account.beneficiaries.iter_mut().zip(shares) {
+            b.allocated_tokens = b.allocated_tokens
+                .checked_add(share)
+                .ok_or(VestingError::MathOverflow)?;
+            // Keep a settled cap reachable once the original curve has fully vested
+            if b.settled_from > 0 {
+                b.settled_from = b.settled_from.checked_add(share).ok_or(VestingError::MathOverflow)?;
+            }
+            distributed = distributed.checked_add(share).ok_or(VestingError::MathOverflow)?;
+        }
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(SurplusDistributed {
+            admin: ctx.accounts.operator.key(),
+            surplus,
+            distributed,
+            event_seq,
+        });
+        emit_schedule_state(data_account, ctx.accounts.escrow_wallet.amount)?;
+
+        Ok(())
+    }
+
+    /// Caps `claim` at what had vested by `claim_ceiling_time`.
+    /// 
+    /// Releases liquidity in waves without changing accrual: admin advances the ceiling
+    /// to open each wave. `i64::MAX` (the default) disables the cap.
+    /// 
+    /// # Arguments
+    /// * `claim_ceiling_time` - Ceiling in the schedule's time unit (slot when `use_slots`)
+    pub fn set_claim_ceiling(
+        ctx: Context<UpdateConfig>,
+        _data_bump: u8,
+        claim_ceiling_time: i64,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.claim_ceiling_time = claim_ceiling_time;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(ClaimCeilingUpdated {
+            admin: ctx.accounts.admin.key(),
+            claim_ceiling_time,
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Pauses or resumes claims.
+    /// 
+    /// While paused, `claim` is rejected. Pausing is also a precondition of `emergency_drain`.
+    /// Also callable by the claim manager.
+    /// 
+    /// # Arguments
+    /// * `paused` - New pause state
+    pub fn set_paused(ctx: Context<RoleOperation>, _data_bump: u8, paused: bool) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.check_role(AdminRole::ClaimManager, ctx.accounts.operator.key())?;
+        data_account.paused = paused;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(PausedUpdated {
+            admin: ctx.accounts.operator.key(),
+            paused,
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Sets or clears the co-admin whose signature `withdraw`, `withdraw_amount` and
+    /// `emergency_drain` then require alongside the admin's (2-of-2 recovery).
+    /// 
+    /// Once a co-admin is set, replacing or clearing it also needs their signature.
+    /// 
+    /// # Arguments
+    /// * `co_admin` - New co-admin, or None to return to single-admin control
+    pub fn set_co_admin(ctx: Context<SetCoAdmin>, _data_bump: u8, co_admin: Option<Pubkey>) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.check_co_admin(ctx.accounts.co_admin.as_ref().map(|s| s.key()))?;
+        data_account.co_admin = co_admin;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(CoAdminUpdated {
+            admin: ctx.accounts.admin.key(),
+            co_admin,
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Grants or revokes an operational role.
+    /// 
+    /// The holder can call that role's instructions in place of the admin, but nothing else:
+    /// withdrawals, forfeits and every other setter stay admin-only.
+    /// 
+    /// # Arguments
+    /// * `role` - Role to update
+    /// * `holder` - New holder, or None to revoke the role
+    pub fn set_role(
+        ctx: Context<UpdateConfig>,
+        _data_bump: u8,
+        role: AdminRole,
+        holder: Option<Pubkey>,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        match role {
+            AdminRole::ClaimManager => data_account.claim_manager = holder,
+            AdminRole::FundManager => data_account.fund_manager = holder,
+        }
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(RoleUpdated {
+            admin: ctx.accounts.admin.key(),
+            role,
+            holder,
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Creates the admin's `AdminRegistry` if needed and sets its schedule limit.
+    /// 
+    /// Schedules initialized with the registry passed count against it, and `initialize`
+    /// fails with `MaxSchedulesReached` once `max_schedules` are counted. Lowering the
+    /// limit below the current count only blocks new schedules.
+    /// 
+    /// # Arguments
+    /// * `max_schedules` - Maximum schedules counted against the registry at once
+    pub fn set_schedule_limit(ctx: Context<SetScheduleLimit>, max_schedules: u32) -> Result<()> {
+        let registry = &mut ctx.accounts.admin_registry;
+        registry.admin = ctx.accounts.admin.key();
+        registry.max_schedules = max_schedules;
+
+        Ok(())
+    }
+
+    /// Releases a finished schedule's slot in the `AdminRegistry` it was counted against.
+    /// 
+    /// Schedule accounts are never closed, so this stands in for closing one: every
+    /// allocation must be fully claimed or withdrawn (`ScheduleHealth::Closed`), and the
+    /// slot is released at most once.
+    pub fn release_schedule_slot(ctx: Context<ReleaseScheduleSlot>, _data_bump: u8) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let now = data_account.current_time()?;
+        require!(
+            schedule_health(data_account, now)? == ScheduleHealth::Closed,
+            VestingError::ScheduleNotClosed
+        );
+
+        let registry = &mut ctx.accounts.admin_registry;
+        registry.schedule_count = registry.schedule_count
+            .checked_sub(1)
+            .ok_or(VestingError::MathOverflow)?;
+        data_account.admin_registry = None;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(ScheduleSlotReleased {
+            admin: ctx.accounts.admin.key(),
+            registry: registry.key(),
+            schedule_count: registry.schedule_count,
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Creates a `ClockSource` controlled by the signer, for schedules that should follow a
+    /// governed or test-controlled clock instead of the Clock sysvar (see `Initialize::clock_source`).
+    /// 
+    /// # Arguments
+    /// * `unix_timestamp` - Initial time (not negative; it only moves forward from here)
+    pub fn create_clock_source(ctx: Context<CreateClockSource>, unix_timestamp: i64) -> Result<()> {
+        require!(unix_timestamp >= 0, VestingError::InvalidClock);
+        let clock_source = &mut ctx.accounts.clock_source;
+        clock_source.authority = ctx.accounts.authority.key();
+        clock_source.unix_timestamp = unix_timestamp;
+
+        Ok(())
+    }
+
+    /// Moves a `ClockSource` forward. Time never goes backwards, so vested amounts never shrink.
+    /// Schedules pick up the new time with `sync_clock`.
+    /// 
+    /// # Arguments
+    /// * `unix_timestamp` - New time (at least the current one)
+    pub fn advance_clock_source(ctx: Context<AdvanceClockSource>, unix_timestamp: i64) -> Result<()> {
+        let clock_source = &mut ctx.accounts.clock_source;
+        require!(
+            unix_timestamp >= clock_source.unix_timestamp,
+            VestingError::ClockSourceRewound
+        );
+        clock_source.unix_timestamp = unix_timestamp;
+
+        Ok(())
+    }
+
+    /// Copies the time of the schedule's `clock_override` into the schedule. Permissionless.
+    pub fn sync_clock(ctx: Context<SyncClock>) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.override_time = ctx.accounts.clock_source.unix_timestamp;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(ClockSynced {
+            clock_source: ctx.accounts.clock_source.key(),
+            timestamp: data_account.override_time,
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Adds a claim blackout window `[start, end)` in unix timestamps (also for slot schedules).
+    /// 
+    /// `claim` is rejected with `BlackoutActive` inside any window; vesting keeps accruing,
+    /// so everything vested meanwhile is claimable once the window ends. Windows must be
+    /// added in order: `start` may not precede the end of the last window.
+    /// 
+    /// # Arguments
+    /// * `start` - First second of the blackout
+    /// * `end` - First second after the blackout (must be in the future)
+    pub fn add_blackout(ctx: Context<UpdateConfig>, _data_bump: u8, start: i64, end: i64) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(start < end && end > now, VestingError::InvalidBlackoutWindow);
+        if let Some(last) = data_account.blackout_windows.last() {
+            require!(start >= last.end, VestingError::InvalidBlackoutWindow);
+        }
+        require!(
+            data_account.blackout_windows.len() < MAX_BLACKOUT_WINDOWS,
+            VestingError::TooManyBlackoutWindows
+        );
+        data_account.blackout_windows.push(BlackoutWindow { start, end });
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(BlackoutAdded {
+            admin: ctx.accounts.admin.key(),
+            start,
+            end,
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Removes every claim blackout window.
+    pub fn clear_blackouts(ctx: Context<UpdateConfig>, _data_bump: u8) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.blackout_windows.clear();
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(BlackoutsCleared {
+            admin: ctx.accounts.admin.key(),
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Repairs the accounting invariant by setting `token_amount` to the sum of allocations.
+    /// 
+    /// Admin-only repair tool for state left inconsistent by an upgrade. It can only lower
+    /// `token_amount`; a sum above it fails with `TokenAmountWouldIncrease`.
+    pub fn recompute_token_amount(ctx: Context<UpdateConfig>, _data_bump: u8) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let old_token_amount = data_account.token_amount;
+        let new_token_amount = total_allocation(&data_account.beneficiaries)?;
+
+        require!(new_token_amount <= old_token_amount, VestingError::TokenAmountWouldIncrease);
+        data_account.token_amount = new_token_amount;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(TokenAmountRecomputed {
+            admin: ctx.accounts.admin.key(),
+            old_token_amount,
+            new_token_amount,
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Switches a linear beneficiary between per-period and continuous accrual.
+    /// 
+    /// Signed by the admin or the beneficiary. The switch must not take back anything: the
+    /// amount vested at `now` under the new mode must be at least the amount under the old one,
+    /// otherwise it fails with `ModeSwitchWouldReduce`. Monthly to continuous always passes;
+    /// continuous to monthly only where both agree (before the cliff, on a period boundary or
+    /// once fully vested). Not available on `continuous_linear` schedules, which fix the mode.
+    /// 
+    /// # Arguments
+    /// * `beneficiary` - Beneficiary to switch
+    /// * `mode` - New accrual mode
+    /// * `data_bump` - Bump seed for data account PDA validation
+    pub fn set_vesting_mode(
+        ctx: Context<SetVestingMode>,
+        beneficiary: Pubkey,
+        mode: VestingMode,
+        _data_bump: u8,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.check_beneficiary_count()?;
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == data_account.authority || signer == beneficiary,
+            VestingError::UnauthorizedAdmin
+        );
+        require!(!data_account.continuous_linear, VestingError::InvalidVestingConfig);
+
+        let now = data_account.current_time()?;
+        let index = data_account
+            .beneficiaries
+            .iter()
+            .position(|b| b.assigned && b.key == beneficiary)
+            .ok_or(VestingError::BeneficiaryNotFound)?;
+        let mut entry = data_account.beneficiaries[index];
+        require!(entry.curve == VestingCurve::Linear, VestingError::InvalidVestingConfig);
+
+        let vested_before = data_account.compute_vested_for(&entry, now)?;
+        entry.mode = mode;
+        let vested_after = data_account.compute_vested_for(&entry, now)?;
+        require!(vested_after >= vested_before, VestingError::ModeSwitchWouldReduce);
+        data_account.beneficiaries[index].mode = mode;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(VestingModeChanged {
+            signer,
+            beneficiary,
+            mode,
+            vested_before,
+            vested_after,
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Replaces a beneficiary's time-based release with an explicit milestone list.
+    /// 
+    /// Only allowed before the beneficiary's `start_time`. Milestones must be in ascending
+    /// order, fall within the original vesting window (so grace period rules still apply)
+    /// and sum to `allocated_tokens`. They are stored in a `MilestoneSchedule` side account
+    /// that `claim` must then be given for this beneficiary.
+    /// 
+    /// # Arguments
+    /// * `beneficiary` - Beneficiary to convert
+    /// * `milestones` - Unlock times (schedule unit) and amounts in RAW UNITS (max 24)
+    /// * `data_bump` - Bump seed for data account PDA validation
+    pub fn convert_to_milestones(
+        ctx: Context<ConvertToMilestones>,
+        beneficiary: Pubkey,
+        milestones: Vec<Milestone>,
+        _data_bump: u8,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.check_beneficiary_count()?;
+        let now = data_account.current_time()?;
+        let period = data_account.period_length();
+
+        let entry = data_account
+            .beneficiaries
+            .iter_mut()
+            .find(|b| b.assigned && b.key == beneficiary)
+            .ok_or(VestingError::BeneficiaryNotFound)?;
+        require!(now < entry.start_time, VestingError::VestingAlreadyStarted);
+
+        require!(
+            !milestones.is_empty() && milestones.len() <= MAX_MILESTONES,
+            VestingError::InvalidMilestones
+        );
+        let end_time = vesting_end_time(entry, period)?;
+        let mut previous = entry.start_time;
+        let mut total = 0u64;
+        for m in milestones.iter() {
+            require!(
+                m.unlock_time >= previous && m.unlock_time <= end_time && m.amount > 0,
+                VestingError::InvalidMilestones
+            );
+            previous = m.unlock_time;
+            total = total.checked_add(m.amount).ok_or(VestingError::MathOverflow)?;
+        }
+        require!(total == entry.allocated_tokens, VestingError::InvalidMilestones);
+
+        entry.curve = VestingCurve::Milestones;
+        entry.cliff_months = 0;
+        entry.cliff_time = None;
+        let milestones_count = milestones.len() as u32;
+
+        let milestone_schedule = &mut ctx.accounts.milestone_schedule;
+        milestone_schedule.beneficiary = beneficiary;
+        milestone_schedule.milestones = milestones;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(ConvertedToMilestones {
+            admin: ctx.accounts.admin.key(),
+            beneficiary,
+            milestones_count,
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Starts the timelock for rotating the escrow with `migrate_escrow`.
+    pub fn request_escrow_migration(ctx: Context<UpdateConfig>, _data_bump: u8) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let requested_at = Clock::get()?.unix_timestamp;
+        data_account.escrow_migration_requested_at = requested_at;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(EscrowMigrationRequested {
+            admin: ctx.accounts.admin.key(),
+            unlock_time: requested_at + ESCROW_MIGRATION_TIMELOCK,
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Moves the full escrow balance into a fresh escrow PDA and closes the old one.
+    /// 
+    /// Recovery path for an escrow account that can no longer be used as-is. Requires a
+    /// prior `request_escrow_migration` and `ESCROW_MIGRATION_TIMELOCK` to have passed.
+    /// The old escrow must still allow transfers (i.e. not be frozen) for its balance to move.
+    /// 
+    /// # Arguments
+    /// * `old_escrow_bump` - Bump seed of the current escrow PDA
+    /// * `data_bump` - Bump seed for data account PDA
+    pub fn migrate_escrow(ctx: Context<MigrateEscrow>, old_escrow_bump: u8, data_bump: u8) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        let old_escrow = &ctx.accounts.old_escrow;
+        let new_escrow = &ctx.accounts.new_escrow;
+        let token_mint_key = &ctx.accounts.token_mint.key();
+
+        verify_escrow_pda(
+            &old_escrow.key(),
+            token_mint_key,
+            data_account.escrow_nonce,
+            old_escrow_bump,
+            ctx.program_id,
+        )?;
+
+        let requested_at = data_account.escrow_migration_requested_at;
+        require!(
+            requested_at > 0
+                && Clock::get()?.unix_timestamp >= requested_at + ESCROW_MIGRATION_TIMELOCK,
+            VestingError::EscrowMigrationLocked
+        );
+
+        let seeds = &["data_account".as_bytes(), token_mint_key.as_ref(), &[data_bump]];
+        let signer_seeds = &[&seeds[..]];
+
+        let amount = old_escrow.amount;
+        if amount > 0 {
+            let transfer_instruction = Transfer {
+                from: old_escrow.to_account_info(),
+                to: new_escrow.to_account_info(),
+                authority: data_account.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                transfer_instruction,
+                signer_seeds,
+            );
+            token::transfer(cpi_ctx, amount)?;
+        }
+
+        let close_instruction = CloseAccount {
+            account: old_escrow.to_account_info(),
+            destination: ctx.accounts.admin.to_account_info(),
+            authority: data_account.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            close_instruction,
+            signer_seeds,
+        );
+        token::close_account(cpi_ctx)?;
+
+        data_account.escrow_wallet = new_escrow.key();
+        data_account.escrow_nonce = data_account
+            .escrow_nonce
+            .checked_add(1)
+            .ok_or(VestingError::MathOverflow)?;
+        data_account.escrow_migration_requested_at = 0;
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(EscrowMigrated {
+            admin: ctx.accounts.admin.key(),
+            old_escrow: old_escrow.key(),
+            new_escrow: new_escrow.key(),
+            amount,
+            escrow_nonce: data_account.escrow_nonce,
+            event_seq,
+        });
+
+        // The schedule's escrow is now the new account
+        ctx.accounts.new_escrow.reload()?;
+        emit_escrow_change(
+            data_account,
+            amount,
+            ctx.accounts.new_escrow.amount,
+            EscrowChangeReason::Migration,
+        )?;
+
+        Ok(())
+    }
+
+    /// Moves every beneficiary's start time so the earliest one lands on `new_anchor_time`.
+    /// 
+    /// All start times are shifted by the same delta, preserving relative offsets
+    /// within the cohort. Only allowed while no beneficiary has started vesting.
+    /// 
+    /// # Arguments
+    /// * `new_anchor_time` - New start time of the earliest beneficiary
+    pub fn reschedule_all(
+        ctx: Context<UpdateConfig>,
+        _data_bump: u8,
+        new_anchor_time: i64,
+    ) -> Result<()> {
+        let data_account = &mut ctx.accounts.data_account;
+        data_account.check_beneficiary_count()?;
+        let now = data_account.current_time()?;
+        let max_start_delay = data_account.max_start_delay();
+
+        let old_anchor_time = data_account
+            .beneficiaries
+            .iter()
+            .map(|b| b.start_time)
+            .min()
+            .ok_or(VestingError::NoBeneficiaries)?;
+        require!(now < old_anchor_time, VestingError::VestingAlreadyStarted);
+
+        let delta = new_anchor_time
+            .checked_sub(old_anchor_time)
+            .ok_or(VestingError::MathOverflow)?;
+
+        for b in data_account.beneficiaries.iter_mut() {
+            b.start_time = b.start_time.checked_add(delta).ok_or(VestingError::MathOverflow)?;
+            if let Some(cliff_time) = b.cliff_time {
+                b.cliff_time = Some(cliff_time.checked_add(delta).ok_or(VestingError::MathOverflow)?);
+            }
+            require!(b.start_time >= now, VestingError::InvalidStartTime);
+            require!(
+                b.start_time <= now + max_start_delay,
+                VestingError::StartTimeTooFar
+            );
+        }
+
+        let event_seq = data_account.next_event_seq()?;
+        emit!(ScheduleRescheduled {
+            admin: ctx.accounts.admin.key(),
+            old_anchor_time,
+            new_anchor_time,
+            beneficiaries_count: data_account.beneficiaries.len() as u32,
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Migrates a data account created with the original (v1) layout to the current layout.
+    /// 
+    /// Reallocates the account (the admin pays the extra rent), keeps the schedule and
+    /// all beneficiaries, and sets every field added since v1 to its disabled default.
+    /// Only accounts still in the v1 layout can be migrated.
+    pub fn migrate_v1_to_v2(ctx: Context<MigrateDataAccount>, _data_bump: u8) -> Result<()> {
+        let data_account = &ctx.accounts.data_account;
+        let admin = &ctx.accounts.admin;
+
+        let legacy = {
+            let data = data_account.try_borrow_data()?;
+            require!(
+                data.len() > 8 && data[..8] == DataAccount::DISCRIMINATOR,
+                anchor_lang::error::ErrorCode::AccountDidNotDeserialize
+            );
+
+            // Accounts that predate the `version` field only parse in the v1 layout
+            let version = match DataAccount::try_deserialize(&mut &data[..]) {
+                Ok(current) => current.version,
+                Err(_) => 1,
+            };
+            require!(version == 1, VestingError::AlreadyMigrated);
+
+            DataAccountV1::deserialize(&mut &data[8..])
+                .map_err(|_| error!(anchor_lang::error::ErrorCode::AccountDidNotDeserialize))?
+        };
+        require!(legacy.authority == admin.key(), VestingError::UnauthorizedAdmin);
+
+        let beneficiaries: Vec<Beneficiary> = legacy
+            .beneficiaries
+            .iter()
+            .enumerate()
+            .map(|(i, b)| Beneficiary {
+                key: b.key,
+                allocated_tokens: b.allocated_tokens,
+                claimed_tokens: b.claimed_tokens,
+                start_time: b.start_time,
+                cliff_months: b.cliff_months,
+                total_months: b.total_months,
+                last_claim_time: 0,
+                curve: VestingCurve::Linear,
+                assigned: true,
+                notify_tag: [0; 16],
+                cliff_time: None,
+                claim_count: 0,
+                original_index: i as u32,
+                reference: [0; 24],
+                mode: VestingMode::Monthly,
+                settled_from: 0,
+            })
+            .collect();
+
+        let active_beneficiaries = active_count(&beneficiaries);
+        let mut migrated = DataAccount {
+            token_amount: legacy.token_amount,
+            authority: legacy.authority,
+            escrow_wallet: legacy.escrow_wallet,
+            token_mint: legacy.token_mint,
+            beneficiaries,
+            decimals: legacy.decimals,
+            version: DATA_ACCOUNT_VERSION,
+            expected_beneficiaries: legacy.beneficiaries.len() as u32,
+            beneficiary_count: legacy.beneficiaries.len() as u32,
+            active_beneficiaries,
+            activated: true,
+            claim_ceiling_time: i64::MAX,
+            next_original_index: legacy.beneficiaries.len() as u32,
+            ..Default::default()
+        };
+
+        // Grow the account and top up rent for the new size
+        let new_space = calculate_vesting_space!(migrated.beneficiaries.len());
+        let rent_due = Rent::get()?
+            .minimum_balance(new_space)
+            .saturating_sub(data_account.lamports());
+        if rent_due > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: admin.to_account_info(),
+                        to: data_account.to_account_info(),
+                    },
+                ),
+                rent_due,
+            )?;
+        }
+        data_account.to_account_info().realloc(new_space, false)?;
+
+        let event_seq = migrated.next_event_seq()?;
+        {
+            let mut data = data_account.try_borrow_mut_data()?;
+            migrated.try_serialize(&mut &mut data[..])?;
+        }
+
+        emit!(DataAccountMigrated {
+            admin: admin.key(),
+            version: DATA_ACCOUNT_VERSION,
+            beneficiaries_count: migrated.beneficiaries.len() as u32,
+            event_seq,
+        });
+
+        Ok(())
+    }
+
+    /// Exports one page of the schedule in a stable, versioned wire format.
+    /// 
+    /// Light clients can decode this without depending on the Anchor account layout.
+    /// See `encode_schedule_page` for the exact format.
+    /// 
+    /// # Arguments
+    /// * `page` - Page index (0-based), `EXPORT_ENTRIES_PER_PAGE` beneficiaries per page
+    pub fn export_schedule(ctx: Context<ReadSchedule>, page: u16) -> Result<Vec<u8>> {
+        encode_schedule_page(&ctx.accounts.data_account, page)
+    }
+
+    /// Returns a keccak-256 fingerprint of the schedule's promised terms.
+    /// 
+    /// Beneficiaries compare it with the hash published alongside their agreement; any change
+    /// to a key, allocation or timeline, and any added or removed beneficiary, changes it.
+    /// See `schedule_hash_preimage` for the exact bytes hashed.
+    pub fn get_schedule_hash(ctx: Context<ReadSchedule>) -> Result<[u8; 32]> {
+        Ok(schedule_hash(&ctx.accounts.data_account))
+    }
+
+    /// Returns the caller-supplied beneficiary's schedule entry for the mint's data account.
+    /// 
+    /// Lets a beneficiary who only knows their key and the mint look up their allocation.
+    /// 
+    /// # Arguments
+    /// * `beneficiary` - Beneficiary address to look up
+    pub fn find_my_schedule(ctx: Context<ReadSchedule>, beneficiary: Pubkey) -> Result<Beneficiary> {
+        ctx.accounts
+            .data_account
+            .beneficiaries
+            .iter()
+            .find(|b| b.assigned && b.key == beneficiary)
+            .copied()
+            .ok_or(error!(VestingError::NotAMember))
+    }
+
+    /// Returns the number of beneficiary entries (including unassigned slots).
+    pub fn get_beneficiary_count(ctx: Context<ReadSchedule>) -> Result<u32> {
+        Ok(ctx.accounts.data_account.beneficiaries.len() as u32)
+    }
+
+    /// Returns up to `limit` beneficiary entries starting at index `start`.
+    /// 
+    /// For paginated UIs. `limit` is clamped to `MAX_BENEFICIARY_PAGE_ENTRIES` and to the
+    /// end of the list, so a page past the last entry is shorter (or empty when `start == len`).
+    /// 
+    /// # Arguments
+    /// * `start` - Index of the first entry (at most the beneficiary count)
+    /// * `limit` - Maximum entries to return
+    pub fn get_beneficiary_page(
+        ctx: Context<ReadSchedule>,
+        start: u32,
+        limit: u32,
+    ) -> Result<Vec<Beneficiary>> {
+        let beneficiaries = &ctx.accounts.data_account.beneficiaries;
+        let start = start as usize;
+        require!(start <= beneficiaries.len(), VestingError::InvalidPage);
+
+        let limit = std::cmp::min(limit, MAX_BENEFICIARY_PAGE_ENTRIES) as usize;
+        let end = std::cmp::min(start + limit, beneficiaries.len());
+        Ok(beneficiaries[start..end].to_vec())
+    }
+
+    /// Returns the amount vested for `beneficiary` at a past or present `timestamp` - RAW UNITS.
+    /// 
+    /// Uses the same math as `claim`, ignoring what has been claimed, so reporting tools can
+    /// reconstruct vested balances at period-end dates.
+    /// 
+    /// # Arguments
+    /// * `beneficiary` - Beneficiary address to query
+    /// * `timestamp` - Point in time, in the schedule's unit (slot when `use_slots`); must not be in the future
+    pub fn get_vested_at(ctx: Context<ReadSchedule>, beneficiary: Pubkey, timestamp: i64) -> Result<u64> {
+        let data_account = &ctx.accounts.data_account;
+        require!(timestamp <= data_account.current_time()?, VestingError::TimestampInFuture);
+
+        let entry = data_account
+            .beneficiaries
+            .iter()
+            .find(|b| b.assigned && b.key == beneficiary)
+            .ok_or(VestingError::BeneficiaryNotFound)?;
+
+        data_account.compute_vested_for(entry, timestamp)
+    }
+
+    /// Returns the total vested across all entries (reserved slots included) at a past or
+    /// present `timestamp` - RAW UNITS, e.g. for expense accrual.
+    /// 
+    /// Sums the same per-entry amounts as `get_vested_at`. Fails with `MilestonesRequired`
+    /// when an entry has been converted to milestones, as those live in side accounts.
+    /// 
+    /// # Arguments
+    /// * `timestamp` - Point in time, in the schedule's unit (slot when `use_slots`); must not be in the future
+    pub fn get_total_vested_at(ctx: Context<ReadSchedule>, timestamp: i64) -> Result<u64> {
+        let data_account = &ctx.accounts.data_account;
+        require!(timestamp <= data_account.current_time()?, VestingError::TimestampInFuture);
+
+        let mut total = 0u64;
+        for entry in data_account.beneficiaries.iter() {
+            total = total
+                .checked_add(data_account.compute_vested_for(entry, timestamp)?)
+                .ok_or(VestingError::MathOverflow)?;
+        }
+        Ok(total)
+    }
+
+    /// Returns the next period boundary and how much more of `beneficiary`'s allocation will
+    /// have vested by then, for "wait until X to claim Y more" projections.
+    /// 
+    /// Once fully vested, returns `additional` 0 with `next_boundary` at the vesting end.
+    /// 
+    /// # Arguments
+    /// * `beneficiary` - Beneficiary address to query
+    pub fn get_next_period_claimable(
+        ctx: Context<ReadSchedule>,
+        beneficiary: Pubkey,
+    ) -> Result<NextPeriodClaimable> {
+        let data_account = &ctx.accounts.data_account;
+        let now = data_account.current_time()?;
+        let period = data_account.period_length();
+
+        let entry = data_account
+            .beneficiaries
+            .iter()
+            .find(|b| b.assigned && b.key == beneficiary)
+            .ok_or(VestingError::BeneficiaryNotFound)?;
+
+        let vesting_end = vesting_end_time(entry, period)?;
+        if now >= vesting_end {
+            return Ok(NextPeriodClaimable { next_boundary: vesting_end, additional: 0 });
+        }
+
+        let next_boundary = next_period_boundary(entry, now, period)?;
+        let additional = data_account
+            .compute_vested_for(entry, next_boundary)?
+            .saturating_sub(data_account.compute_vested_for(entry, now)?);
+        Ok(NextPeriodClaimable { next_boundary, additional })
+    }
+
+    /// Returns `points` evenly spaced samples of `beneficiary`'s cumulative vested amount,
+    /// from `start_time` to the end of vesting inclusive, for chart UIs.
+    /// 
+    /// Uses the same math as `claim`. `points` is clamped to `MAX_CURVE_SAMPLES`.
+    /// 
+    /// # Arguments
+    /// * `beneficiary` - Beneficiary address to sample
+    /// * `points` - Number of samples (at least 2)
+    pub fn sample_curve(ctx: Context<ReadSchedule>, beneficiary: Pubkey, points: u8) -> Result<Vec<CurveSample>> {
+        let data_account = &ctx.accounts.data_account;
+        let entry = data_account
+            .beneficiaries
+            .iter()
+            .find(|b| b.assigned && b.key == beneficiary)
+            .ok_or(VestingError::BeneficiaryNotFound)?;
+
+        sample_vesting_curve(data_account, entry, points)
+    }
+
+    /// Returns aggregate totals and a single health status for dashboards.
+    pub fn get_schedule_summary(ctx: Context<ReadSchedule>) -> Result<ScheduleSummary> {
+        let data_account = &ctx.accounts.data_account;
+        let now = data_account.current_time()?;
+
+        let total_claimed = data_account.total_claimed()?;
+
+        Ok(ScheduleSummary {
+            health: schedule_health(data_account, now)?,
+            beneficiaries_count: data_account.beneficiaries.len() as u32,
+            token_amount: data_account.token_amount,
+            total_allocated: total_allocation(&data_account.beneficiaries)?,
+            total_claimed,
+        })
+    }
+
+    /// Returns every key with control over the schedule in one read: the admin and the
+    /// optional co-admin and delegated roles (None = unset).
+    pub fn get_roles(ctx: Context<ReadSchedule>) -> Result<ScheduleRoles> {
+        let data_account = &ctx.accounts.data_account;
+        Ok(ScheduleRoles {
+            authority: data_account.authority,
+            co_admin: data_account.co_admin,
+            claim_manager: data_account.claim_manager,
+            fund_manager: data_account.fund_manager,
+        })
+    }
+
+    /// Returns the lamports held by the data account and the escrow (their rent-exempt
+    /// reserves), i.e. the SOL an admin would recover by closing the schedule.
+    pub fn get_rent_recoverable(ctx: Context<ReadScheduleWithEscrow>) -> Result<u64> {
+        let data_lamports = ctx.accounts.data_account.to_account_info().lamports();
+        let escrow_lamports = ctx.accounts.escrow_wallet.to_account_info().lamports();
+        Ok(data_lamports.checked_add(escrow_lamports).ok_or(VestingError::MathOverflow)?)
+    }
+
+    /// Returns whether every beneficiary has reached the end of their vesting period.
+    pub fn is_complete(ctx: Context<ReadSchedule>) -> Result<bool> {
+        let data_account = &ctx.accounts.data_account;
+        let now = data_account.current_time()?;
+        let period = data_account.period_length();
+
+        let mut last_end_time = i64::MIN;
+        for b in data_account.beneficiaries.iter() {
+            last_end_time = std::cmp::max(last_end_time, vesting_end_time(b, period)?);
+        }
+
+        Ok(now >= last_end_time)
+    }
+
+    /// Returns the program-wide limits and timing constants this build was compiled with,
+    /// so clients need not hardcode them. Per-schedule overrides (`min_total_months`,
+    /// `slots_per_period`, ...) are stored on each `DataAccount`.
+    pub fn get_constants(_ctx: Context<ReadConstants>) -> Result<ProgramConstants> {
+        Ok(ProgramConstants {
+            seconds_per_month: SECONDS_PER_MONTH,
+            grace_period_months: GRACE_PERIOD_MONTHS,
+            grace_period: GRACE_PERIOD,
+            max_start_delay: MAX_START_DELAY,
+            max_beneficiaries: MAX_BENEFICIARIES as u32,
+            max_total_months: MAX_TOTAL_MONTHS,
+            min_total_months: MIN_TOTAL_MONTHS,
+            max_cliff_months: MAX_CLIFF_MONTHS,
+            max_decimals: MAX_DECIMALS,
+            max_milestones: MAX_MILESTONES as u32,
+            max_memo_len: MAX_MEMO_LEN as u32,
+            max_beneficiary_page_entries: MAX_BENEFICIARY_PAGE_ENTRIES,
+            max_curve_samples: MAX_CURVE_SAMPLES,
+            export_format_version: EXPORT_FORMAT_VERSION,
+            export_entries_per_page: EXPORT_ENTRIES_PER_PAGE as u32,
+            escrow_migration_timelock: ESCROW_MIGRATION_TIMELOCK,
+            data_account_version: DATA_ACCOUNT_VERSION,
+            max_blackout_windows: MAX_BLACKOUT_WINDOWS as u32,
+            dust_threshold: DUST_THRESHOLD,
+            max_claimable_batch: MAX_CLAIMABLE_BATCH as u32,
+            max_batch_size: MAX_BATCH_SIZE as u32,
+        })
+    }
+
+    /// Diagnostic for integrators: checks that the passed accounts and bumps are this
+    /// program's PDAs for `token_mint`.
+    /// 
+    /// Fails with `DataPdaMismatch` or `EscrowPdaMismatch` instead of the generic seeds or
+    /// deserialization errors a misconfigured client would hit in other instructions.
+    /// 
+    /// # Arguments
+    /// * `data_bump` - Expected bump of the data account PDA
+    /// * `escrow_bump` - Expected bump of the current escrow PDA
+    pub fn verify_pdas(ctx: Context<VerifyPdas>, data_bump: u8, escrow_bump: u8) -> Result<()> {
+        let token_mint_key = ctx.accounts.token_mint.key();
+
+        let (expected_data, expected_data_bump) = Pubkey::find_program_address(
+            &[b"data_account", token_mint_key.as_ref()],
+            ctx.program_id,
+        );
+        require!(
+            ctx.accounts.data_account.key() == expected_data && data_bump == expected_data_bump,
+            VestingError::DataPdaMismatch
+        );
+
+        // The escrow seeds depend on how often it has been rotated
+        let escrow_nonce = {
+            let data = ctx.accounts.data_account.try_borrow_data()?;
+            DataAccount::try_deserialize(&mut &data[..])?.escrow_nonce
+        };
+        let (expected_escrow, expected_escrow_bump) =
+            escrow_pda(&token_mint_key, escrow_nonce, ctx.program_id);
+        require!(
+            ctx.accounts.escrow_wallet.key() == expected_escrow && escrow_bump == expected_escrow_bump,
+            VestingError::EscrowPdaMismatch
+        );
+
+        Ok(())
+    }
+}
+
+// ================================================================================================
+// INITIALIZATION
+// ================================================================================================
+
+/// Shared body of `initialize` and `initialize_percentage_milestones`. When `percentage` is
+/// set, every beneficiary is converted to milestone release from its table (see
+/// `create_percentage_milestones`) and the interpolation is stored on the schedule.
+pub fn process_initialize<'info>(
+    ctx: Context<'_, '_, '_, 'info, Initialize<'info>>,
+    beneficiaries: Vec<Beneficiary>,
+    amount: u64, // RAW UNITS
+    decimals: u8,
+    options: ScheduleOptions,
+    percentage: Option<(Vec<Vec<PercentageMilestone>>, MilestoneInterpolation)>,
+) -> Result<()> {
+    let data_account = &mut ctx.accounts.data_account;
+
+    // Retried deployments hit an already-configured schedule: fail cleanly instead of re-funding
+    if data_account.authority != Pubkey::default() {
+        msg!(
+            "Schedule already initialized, escrow balance: {}",
+            ctx.accounts.escrow_wallet.amount
+        );
+        return err!(VestingError::AlreadyInitialized);
+    }
+
+    // Set before `configure` so start times are validated against the override clock
+    if let Some(clock_source) = ctx.accounts.clock_source.as_ref() {
+        require!(!options.use_slots, VestingError::InvalidClockSource);
+        data_account.clock_override = Some(clock_source.key());
+        data_account.override_time = clock_source.unix_timestamp;
+    }
+
+    let total_allocated = data_account.configure(
+        ctx.accounts.sender.key(),
+        ctx.accounts.escrow_wallet.key(),
+        ctx.accounts.token_mint.key(),
+        ctx.accounts.token_mint.freeze_authority.is_some(),
+        beneficiaries,
+        amount,
+        decimals,
+        &options,
+    )?;
+
+    if let Some((tables, interpolation)) = percentage {
+        create_percentage_milestones(
+            ctx.program_id,
+            data_account,
+            &ctx.accounts.sender.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.remaining_accounts,
+            &tables,
+        )?;
+        data_account.milestone_interpolation = interpolation;
+    }
+
+    if let Some(registry) = ctx.accounts.admin_registry.as_mut() {
+        require!(
+            registry.schedule_count < registry.max_schedules,
+            VestingError::MaxSchedulesReached
+        );
+        registry.schedule_count = registry.schedule_count
+            .checked_add(1)
+            .ok_or(VestingError::MathOverflow)?;
+        data_account.admin_registry = Some(registry.key());
+    }
+
+    // Transfer tokens to escrow, signed by the separate treasury funder if one was passed
+    let funder = match ctx.accounts.funder.as_ref() {
+        Some(funder) => funder.to_account_info(),
+        None => ctx.accounts.sender.to_account_info(),
+    };
+    let transfer_instruction = Transfer{ 
+        from: ctx.accounts.wallet_to_withdraw_from.to_account_info(),
+        to: ctx.accounts.escrow_wallet.to_account_info(),
+        authority: funder.clone(),
+    };
+
+    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), transfer_instruction);   
+
+    require!(ctx.accounts.wallet_to_withdraw_from.amount >= amount, VestingError::InsufficientBalance);
+
+    let escrow_balance_before = ctx.accounts.escrow_wallet.amount;
+    token::transfer(cpi_ctx, amount)?;
+    ctx.accounts.escrow_wallet.reload()?;
+
+    if let Some(log) = ctx.accounts.audit_log.as_mut() {
+        log.schedule = data_account.key();
+    }
+    record_audit(
+        data_account,
+        ctx.accounts.audit_log.as_mut(),
+        AuditOp::Initialize,
+        ctx.accounts.sender.key(),
+        amount,
+    )?;
+
+    // Emit initialization event
+    let event_seq = data_account.next_event_seq()?;
+    emit!(VestingInitialized {
+        admin: ctx.accounts.sender.key(),
+        funder: funder.key(),
+        token_mint: ctx.accounts.token_mint.key(),
+        total_amount: amount,
+        beneficiaries_count: data_account.beneficiaries.len() as u32,
+        usd_value: data_account.usd_value(total_allocated)?,
+        effective_token_amount: data_account.whole_tokens(amount),
+        event_seq,
+    });
+
+    emit_escrow_change(
+        data_account,
+        escrow_balance_before,
+        ctx.accounts.escrow_wallet.amount,
+        EscrowChangeReason::Fund,
+    )?;
+
+    Ok(())
+}
+
+/// Rejects batches over `MAX_BATCH_SIZE` items with `BatchTooLarge`, so clients get an
+/// actionable error instead of running out of compute part-way through.
+pub fn check_batch_size(len: usize) -> Result<()> {
+    if len > MAX_BATCH_SIZE {
+        msg!("Batch of {} exceeds the maximum of {}", len, MAX_BATCH_SIZE);
+        return err!(VestingError::BatchTooLarge);
+    }
+    Ok(())
+}
+
+/// Converts every configured entry to milestone release from `tables[i]` and creates its
+/// `MilestoneSchedule` PDA at `milestone_accounts[i]`, paid by `payer`.
+pub fn create_percentage_milestones<'info>(
+    program_id: &Pubkey,
+    data_account: &mut Account<'info, DataAccount>,
+    payer: &AccountInfo<'info>,
+    system_program_info: &AccountInfo<'info>,
+    milestone_accounts: &[AccountInfo<'info>],
+    tables: &[Vec<PercentageMilestone>],
+) -> Result<()> {
+    let beneficiaries_count = data_account.beneficiaries.len();
+    check_batch_size(beneficiaries_count)?;
+    require!(
+        tables.len() == beneficiaries_count && milestone_accounts.len() == beneficiaries_count,
+        VestingError::InvalidMilestones
+    );
+    let data_key = data_account.key();
+    let period = data_account.period_length();
+    let rent = Rent::get()?;
+
+    let entries = data_account.beneficiaries.iter_mut().zip(tables).zip(milestone_accounts);
+    for ((entry, table), account) in entries {
+        let milestones = percentage_to_milestones(entry, table, period)?;
+
+        let seeds: &[&[u8]] = &[b"milestones", data_key.as_ref(), entry.key.as_ref()];
+        let (expected, bump) = Pubkey::find_program_address(seeds, program_id);
+        require_keys_eq!(account.key(), expected, VestingError::InvalidMilestones);
+
+        // Same layout as `ConvertToMilestones` allocates
+        let space = 8 + 32 + (4 + milestones.len() * (8 + 8));
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program_info.clone(),
+                system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+                &[&[b"milestones", data_key.as_ref(), entry.key.as_ref(), &[bump]]],
+            ),
+            rent.minimum_balance(space),
+            space as u64,
+            program_id,
+        )?;
+        let milestone_schedule = MilestoneSchedule { beneficiary: entry.key, milestones };
+        let mut data = account.try_borrow_mut_data()?;
+        milestone_schedule.try_serialize(&mut &mut data[..])?;
+
+        entry.curve = VestingCurve::Milestones;
+        entry.cliff_months = 0;
+        entry.cliff_time = None;
+    }
+
+    Ok(())
+}
+
+/// Creates, configures and funds the schedule of one `initialize_multi` asset. `accounts` is
+/// `[token_mint, data_account, escrow_wallet, wallet_to_withdraw_from]`; the PDAs are checked
+/// against the mint and created at `sender`'s expense with the sizes `Initialize` uses.
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_asset<'info>(
+    program_id: &Pubkey,
+    sender: &AccountInfo<'info>,
+    system_program_info: &AccountInfo<'info>,
+    token_program_info: &AccountInfo<'info>,
+    accounts: &[AccountInfo<'info>],
+    beneficiaries: &[Beneficiary],
+    asset: &AssetConfig,
+    options: &ScheduleOptions,
+) -> Result<()> {
+    let [mint_info, data_info, escrow_info, source_info] = accounts else {
+        return err!(VestingError::InvalidAssetAccounts);
+    };
+    require_keys_eq!(mint_info.key(), asset.mint, VestingError::InvalidAssetAccounts);
+    let token_mint = Account::<Mint>::try_from(mint_info)?;
+    let source = Account::<TokenAccount>::try_from(source_info)?;
+    require!(
+        source.owner == sender.key() && source.mint == asset.mint,
+        VestingError::InvalidSender
+    );
+    require!(source.amount >= asset.amount, VestingError::InsufficientBalance);
+
+    let (data_key, data_bump) =
+        Pubkey::find_program_address(&[b"data_account", asset.mint.as_ref()], program_id);
+    let (escrow_key, escrow_bump) = escrow_pda(&asset.mint, 0, program_id);
+    require_keys_eq!(data_info.key(), data_key, VestingError::InvalidAssetAccounts);
+    require_keys_eq!(escrow_info.key(), escrow_key, VestingError::InvalidAssetAccounts);
+
+    require!(asset.allocations.len() == beneficiaries.len(), VestingError::InvalidAmount);
+    let allocated = beneficiaries
+        .iter()
+        .zip(asset.allocations.iter())
+        .map(|(b, &allocated_tokens)| Beneficiary { allocated_tokens, ..*b })
+        .collect();
+
+    let rent = Rent::get()?;
+    let space = calculate_vesting_space!(beneficiaries.len());
+    system_program::create_account(
+        CpiContext::new_with_signer(
+            system_program_info.clone(),
+            system_program::CreateAccount { from: sender.clone(), to: data_info.clone() },
+            &[&[b"data_account", asset.mint.as_ref(), &[data_bump]]],
+        ),
+        rent.minimum_balance(space),
+        space as u64,
+        program_id,
+    )?;
+    system_program::create_account(
+        CpiContext::new_with_signer(
+            system_program_info.clone(),
+            system_program::CreateAccount { from: sender.clone(), to: escrow_info.clone() },
+            &[&[b"escrow_wallet", asset.mint.as_ref(), &[escrow_bump]]],
+        ),
+        rent.minimum_balance(TokenAccount::LEN),
+        TokenAccount::LEN as u64,
+        token_program_info.key,
+    )?;
+    token::initialize_account3(CpiContext::new(
+        token_program_info.clone(),
+        InitializeAccount3 {
+            account: escrow_info.clone(),
+            mint: mint_info.clone(),
+            authority: data_info.clone(),
+        },
+    ))?;
+
+    let mut data_account = DataAccount::default();
+    let total_allocated = data_account.configure(
+        sender.key(),
+        escrow_key,
+        asset.mint,
+        token_mint.freeze_authority.is_some(),
+        allocated,
+        asset.amount,
+        asset.decimals,
+        options,
+    )?;
+    record_audit(&data_account, None, AuditOp::Initialize, sender.key(), asset.amount)?;
+
+    token::transfer(
+        CpiContext::new(
+            token_program_info.clone(),
+            Transfer {
+                from: source_info.clone(),
+                to: escrow_info.clone(),
+                authority: sender.clone(),
+            },
+        ),
+        asset.amount,
+    )?;
+
+    let event_seq = data_account.next_event_seq()?;
+    emit!(VestingInitialized {
+        admin: sender.key(),
+        funder: sender.key(),
+        token_mint: asset.mint,
+        total_amount: asset.amount,
+        beneficiaries_count: data_account.beneficiaries.len() as u32,
+        usd_value: data_account.usd_value(total_allocated)?,
+        effective_token_amount: data_account.whole_tokens(asset.amount),
+        event_seq,
+    });
+    let event_seq = data_account.next_event_seq()?;
+    emit!(EscrowBalanceChanged {
+        schedule: data_key,
+        old_balance: 0,
+        new_balance: asset.amount,
+        reason: EscrowChangeReason::Fund,
+        timestamp: Clock::get()?.unix_timestamp,
+        event_seq,
+    });
+
+    let mut data = data_info.try_borrow_mut_data()?;
+    data_account.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
+
+// ================================================================================================
+// CLAIM PROCESSING
+// ================================================================================================
+
+/// Shared body of `claim`, `claim_exact`, `claim_to_vault` and `claim_and_forward`. When
+/// `expected` is set, the computed claimable amount must match it exactly. When `vault` is set,
+/// the destination is the custodian vault derived from `(owner_program, seeds)` instead of the
+/// sender's ATA. When `memo` is set, it is emitted through the Memo program before the transfer.
+/// When `forward` is set, what reached the sender's ATA is then moved to that token account.
+pub fn process_claim(
+    ctx: Context<Claim>,
+    data_bump: u8,
+    escrow_bump: u8,
+    expected: Option<u64>,
+    vault: Option<(&Pubkey, &[Vec<u8>])>,
+    memo: Option<&[u8]>,
+    forward: Option<&Pubkey>,
+) -> Result<()> {
+    let sender = &ctx.accounts.sender;
+    let escrow_wallet = &ctx.accounts.escrow_wallet;
+    let data_account = &mut ctx.accounts.data_account;
+    data_account.check_beneficiary_count()?;
+    let token_mint_key = &ctx.accounts.token_mint.key();
+
+    let token_program = &ctx.accounts.token_program;
+    let beneficiaries_ata = &ctx.accounts.wallet_to_deposit_to;
+
+    // Validate escrow wallet PDA
+    verify_escrow_pda(
+        &escrow_wallet.key(),
+        token_mint_key,
+        data_account.escrow_nonce,
+        escrow_bump,
+        ctx.program_id,
+    )?;
+    require!(
+        escrow_wallet.owner == data_account.key(),
+        VestingError::InvalidEscrowAuthority
+    );
+
+    require!(data_account.activated, VestingError::ScheduleNotActivated);
+    require!(!data_account.drained, VestingError::ScheduleDrained);
+    require!(!data_account.paused, VestingError::SchedulePaused);
+    require!(
+        ctx.accounts.claim_receipt.is_some() == data_account.store_receipts,
+        VestingError::InvalidClaimReceipt
+    );
+    if let Some(memo) = memo {
+        require!(
+            memo.len() <= MAX_MEMO_LEN && std::str::from_utf8(memo).is_ok(),
+            VestingError::InvalidMemo
+        );
+        require!(ctx.accounts.memo_program.is_some(), VestingError::InvalidMemoProgram);
+    }
+    if let Some(forward) = forward {
+        // Like a vault claim, forwarding would pull the tokens the staking deposit expects
+        require!(data_account.auto_stake_program.is_none(), VestingError::InvalidForwardAccount);
+        let forward_to = ctx
+            .accounts
+            .forward_to
+            .as_ref()
+            .ok_or(VestingError::InvalidForwardAccount)?;
+        require!(
+            forward_to.key() == *forward && forward_to.mint == *token_mint_key,
+            VestingError::InvalidForwardAccount
+        );
+    }
+
+    // Find beneficiary in the list
+    let index = data_account
+        .beneficiaries
+        .iter()
+        .position(|b| b.assigned && b.key == *sender.key)
+        .ok_or(VestingError::BeneficiaryNotFound)?;
+
+    let beneficiary = data_account.beneficiaries[index];
+    let now = data_account.current_time()?;
+    // Vesting math assumes time since the epoch; a negative clock would yield garbage amounts
+    require!(now >= 0, VestingError::InvalidClock);
+    let period = data_account.period_length();
+
+    if let Some((owner_program, vault_seeds)) = vault {
+        // Staking deposits move tokens out of the sender's ATA, which a vault claim bypasses
+        require!(data_account.auto_stake_program.is_none(), VestingError::InvalidDepositAccount);
+        verify_vault_pda(&beneficiaries_ata.key(), owner_program, vault_seeds)?;
+        require!(
+            *beneficiaries_ata.owner == token::ID,
+            VestingError::InvalidDepositAccount
+        );
+        let data = beneficiaries_ata.try_borrow_data()?;
+        let vault_account = TokenAccount::try_deserialize(&mut &data[..])?;
+        require!(vault_account.mint == *token_mint_key, VestingError::InvalidDepositAccount);
+    } else {
+        // Validate the destination ATA, creating it only if the schedule allows
+        require!(
+            beneficiaries_ata.key() == get_associated_token_address(sender.key, token_mint_key),
+            VestingError::InvalidDepositAccount
+        );
+    }
+    if vault.is_none() && beneficiaries_ata.data_is_empty() {
+        require!(!data_account.require_existing_ata, VestingError::AtaDoesNotExist);
+        // Creation is opt-in per claim: without the ATA program only an existing ATA is accepted
+        let associated_token_program = ctx
+            .accounts
+            .associated_token_program
+            .as_ref()
+            .ok_or(VestingError::AtaDoesNotExist)?;
+
+        associated_token::create(CpiContext::new(
+            associated_token_program.to_account_info(),
+            associated_token::Create {
+                payer: sender.to_account_info(),
+                associated_token: beneficiaries_ata.to_account_info(),
+                authority: sender.to_account_info(),
+                mint: ctx.accounts.token_mint.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: token_program.to_account_info(),
+            },
+        ))?;
+    }
+
+    // Check if cliff period has passed
+    let cliff_reached = match beneficiary.cliff_time {
+        Some(cliff_time) => now >= cliff_time,
+        None => months_elapsed(beneficiary.start_time, now, period)? >= beneficiary.cliff_months as u64,
+    };
+    require!(cliff_reached, VestingError::CliffNotReached);
+
+    // Waved liquidity: nothing vested after the claim ceiling is claimable yet
+    let vested_as_of = std::cmp::min(now, data_account.claim_ceiling_time);
+    let unlocked = if beneficiary.curve == VestingCurve::Milestones {
+        let milestone_schedule = ctx
+            .accounts
+            .milestone_schedule
+            .as_ref()
+            .ok_or(VestingError::MilestonesRequired)?;
+        milestone_schedule.vested_at(
+            beneficiary.start_time,
+            vested_as_of,
+            data_account.milestone_interpolation,
+        )?
+    } else {
+        data_account.compute_vested_for(&beneficiary, vested_as_of)?
+    };
+    let claimable = unlocked.saturating_sub(beneficiary.claimed_tokens);
+
+    #[cfg(feature = "debug-claim")]
+    msg!("{}", claim_debug_line(&beneficiary, vested_as_of, period, unlocked, claimable)?);
+
+    require!(claimable > 0, VestingError::ClaimNotAllowed);
+
+    // `claim_exact` callers pinned the amount they computed off-chain
+    if let Some(expected) = expected {
+        require!(claimable == expected, VestingError::ClaimableChanged);
+    }
+
+    // Reject dust claims, except the one that exhausts the allocation
+    let exhausts_allocation = beneficiary
+        .claimed_tokens
+        .checked_add(claimable)
+        .ok_or(VestingError::MathOverflow)?
+        >= beneficiary.allocated_tokens;
+    require!(
+        claimable >= data_account.min_claim_amount || exhausts_allocation,
+        VestingError::BelowMinClaim
+    );
+
+    // Apply the per-claim transfer limit, if configured
+    let transfer_amount = if data_account.max_per_claim > 0 {
+        std::cmp::min(claimable, data_account.max_per_claim)
+    } else {
+        claimable
+    };
+
+    // Claims are suspended during blackout windows; accrual is not
+    let unix_now = Clock::get()?.unix_timestamp;
+    require!(!data_account.in_blackout(unix_now), VestingError::BlackoutActive);
+
+    // Enforce the claim cooldown, except for the claim that exhausts the allocation
+    let final_claim = beneficiary
+        .claimed_tokens
+        .checked_add(transfer_amount)
+        .ok_or(VestingError::MathOverflow)?
+        >= beneficiary.allocated_tokens;
+    if data_account.claim_cooldown_seconds > 0 && beneficiary.last_claim_time > 0 && !final_claim {
+        let ready_at = beneficiary
+            .last_claim_time
+            .saturating_add(data_account.claim_cooldown_seconds as i64);
+        if unix_now < ready_at {
+            msg!("Claim cooldown active, {} seconds remaining", ready_at - unix_now);
+            return err!(VestingError::ClaimCooldownActive);
+        }
+    }
+
+    let seeds = &["data_account".as_bytes(), token_mint_key.as_ref(), &[data_bump]];
+    let signer_seeds = &[&seeds[..]];
+
+    let transfer_instruction = Transfer {
+        from: escrow_wallet.to_account_info(),
+        to: beneficiaries_ata.to_account_info(),
+        authority: data_account.to_account_info(),
+    };
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(), 
+        transfer_instruction, 
+        signer_seeds
+    );
+
+    // Allocations are always funded, so a short escrow was emptied outside the program (e.g. by
+    // a freeze or delegate). Checked before any accounting is written; the failed transaction
+    // would revert it anyway.
+    if escrow_wallet.amount < transfer_amount {
+        msg!(
+            "Escrow holds {} but the claim needs {}",
+            escrow_wallet.amount,
+            transfer_amount
+        );
+        return err!(VestingError::EscrowUnderfunded);
+    }
+
+    data_account.beneficiaries[index].claimed_tokens = data_account.beneficiaries[index].claimed_tokens
+        .checked_add(transfer_amount)
+        .ok_or(VestingError::MathOverflow)?;
+    data_account.beneficiaries[index].last_claim_time = unix_now;
+    data_account.beneficiaries[index].claim_count = data_account.beneficiaries[index].claim_count
+        .checked_add(1)
+        .ok_or(VestingError::MathOverflow)?;
+    
+    let escrow_balance_before = escrow_wallet.amount;
+    let deposit_balance_before = token_account_balance(&beneficiaries_ata.to_account_info())?;
+    if let (Some(memo), Some(memo_program)) = (memo, ctx.accounts.memo_program.as_ref()) {
+        invoke_memo(&memo_program.to_account_info(), memo)?;
+    }
+    token::transfer(cpi_ctx, transfer_amount)?;
+    ctx.accounts.escrow_wallet.reload()?;
+    verify_escrow_debit(escrow_balance_before, ctx.accounts.escrow_wallet.amount, transfer_amount)?;
+
+    // What actually arrived, e.g. less than `transfer_amount` if the mint takes a transfer fee
+    let net_received = token_account_balance(&beneficiaries_ata.to_account_info())?
+        .saturating_sub(deposit_balance_before);
+
+    // Forward what reached the sender's ATA into the configured staking program
+    if let Some(stake_program) = data_account.auto_stake_program {
+        deposit_to_stake_program(&stake_program, ctx.remaining_accounts, net_received)?;
+    }
+
+    // `claim_and_forward`: move what arrived on to the destination, signed by the sender
+    let forwarded = match (forward, ctx.accounts.forward_to.as_ref()) {
+        (Some(_), Some(forward_to)) => {
+            token::transfer(
+                CpiContext::new(
+                    token_program.to_account_info(),
+                    Transfer {
+                        from: beneficiaries_ata.to_account_info(),
+                        to: forward_to.to_account_info(),
+                        authority: sender.to_account_info(),
+                    },
+                ),
+                net_received,
+            )?;
+            Some(forward_to.key())
+        }
+        _ => None,
+    };
+
+    let updated = data_account.beneficiaries[index];
+    let event_seq = data_account.next_event_seq()?;
+    emit!(TokensClaimed {
+        beneficiary: sender.key(),
+        amount: transfer_amount,
+        net_received,
+        timestamp: unix_now,
+        usd_value: data_account.usd_value(transfer_amount)?,
+        cumulative_claimed: updated.claimed_tokens,
+        remaining_allocation: updated.allocated_tokens.saturating_sub(updated.claimed_tokens),
+        notify_tag: updated.notify_tag,
+        claim_count: updated.claim_count,
+        original_index: updated.original_index,
+        reference: updated.reference,
+        event_seq,
+    });
+    if let Some(forward_to) = forwarded {
+        let forward_seq = data_account.next_event_seq()?;
+        emit!(ClaimForwarded {
+            beneficiary: sender.key(),
+            forward_to,
+            amount: net_received,
+            event_seq: forward_seq,
+        });
+    }
+
+    if let Some(receipt) = ctx.accounts.claim_receipt.as_mut() {
+        receipt.set_inner(ClaimReceipt {
+            schedule: data_account.key(),
+            beneficiary: sender.key(),
+            amount: transfer_amount,
+            timestamp: unix_now,
+            total_claimed: updated.claimed_tokens,
+            event_seq,
+        });
+    }
+    record_audit(
+        data_account,
+        ctx.accounts.audit_log.as_mut(),
+        AuditOp::Claim,
+        sender.key(),
+        transfer_amount,
+    )?;
+
+    if updated.claimed_tokens >= updated.allocated_tokens {
+        record_exhausted(data_account, 1)?;
+
+        // Free the slot for `add_beneficiaries`. The last entry moves into `index`;
+        // clients caching indices must refetch after a `BeneficiaryCompacted` event.
+        if data_account.compact_on_complete && !data_account.store_receipts {
+            let moved_from = data_account.beneficiaries.len() as u32 - 1;
+            data_account.beneficiaries.swap_remove(index);
+            data_account.sync_beneficiary_count();
+            // The claimed tokens have left escrow, so they no longer count as funded
+            data_account.token_amount = data_account
+                .token_amount
+                .checked_sub(updated.allocated_tokens)
+                .ok_or(VestingError::MathOverflow)?;
+
+            let event_seq = data_account.next_event_seq()?;
+            emit!(BeneficiaryCompacted {
+                beneficiary: sender.key(),
+                index: index as u32,
+                original_index: updated.original_index,
+                moved_from,
+                event_seq,
+            });
+        }
+    }
+    emit_escrow_change(
+        data_account,
+        escrow_balance_before,
+        ctx.accounts.escrow_wallet.amount,
+        EscrowChangeReason::Claim,
+    )?;
+    emit_schedule_state(data_account, ctx.accounts.escrow_wallet.amount)?;
+
+    Ok(())
+}
+
+/// Non-mutating mirror of the guards in `process_claim` for `get_claim_eligibility`. `now` is
+/// in the schedule's unit (slot when `use_slots`), `unix_now` is the unix timestamp used for
+/// blackout windows and the cooldown.
+pub fn claim_eligibility(
+    data_account: &DataAccount,
+    beneficiary: Pubkey,
+    now: i64,
+    unix_now: i64,
+) -> Result<ClaimEligibility> {
+    if !data_account.activated {
+        return Ok(ClaimEligibility::NotActivated);
+    }
+    if data_account.drained {
+        return Ok(ClaimEligibility::Drained);
+    }
+    if data_account.paused {
+        return Ok(ClaimEligibility::Paused);
+    }
+
+    let entry = match data_account
+        .beneficiaries
+        .iter()
+        .find(|b| b.assigned && b.key == beneficiary)
+    {
+        Some(entry) => entry,
+        None => return Ok(ClaimEligibility::NotAMember),
+    };
+    if entry.claimed_tokens >= entry.allocated_tokens {
+        return Ok(ClaimEligibility::FullyClaimed);
+    }
+
+    let period = data_account.period_length();
+    let cliff_reached = match entry.cliff_time {
+        Some(cliff_time) => now >= cliff_time,
+        None => months_elapsed(entry.start_time, now, period)? >= entry.cliff_months as u64,
+    };
+    if !cliff_reached {
+        let remaining = cliff_end_time(entry, period)?.saturating_sub(now);
+        return Ok(ClaimEligibility::CliffNotReached { remaining });
+    }
+
+    let vested_as_of = std::cmp::min(now, data_account.claim_ceiling_time);
+    let claimable = data_account
+        .compute_vested_for(entry, vested_as_of)?
+        .saturating_sub(entry.claimed_tokens);
+    if claimable == 0 {
+        return Ok(ClaimEligibility::NothingVestedYet);
+    }
+
+    let exhausts_allocation = entry.claimed_tokens.saturating_add(claimable) >= entry.allocated_tokens;
+    if claimable < data_account.min_claim_amount && !exhausts_allocation {
+        return Ok(ClaimEligibility::BelowMinClaim { claimable });
+    }
+
+    let amount = if data_account.max_per_claim > 0 {
+        std::cmp::min(claimable, data_account.max_per_claim)
+    } else {
+        claimable
+    };
+
+    if let Some(window) = data_account
+        .blackout_windows
+        .iter()
+        .find(|w| w.start <= unix_now && unix_now < w.end)
+    {
+        return Ok(ClaimEligibility::BlackoutActive { seconds_remaining: window.end - unix_now });
+    }
+
+    let final_claim = entry.claimed_tokens.saturating_add(amount) >= entry.allocated_tokens;
+    if data_account.claim_cooldown_seconds > 0 && entry.last_claim_time > 0 && !final_claim {
+        let ready_at = entry
+            .last_claim_time
+            .saturating_add(data_account.claim_cooldown_seconds as i64);
+        if unix_now < ready_at {
+            return Ok(ClaimEligibility::CooldownActive { seconds_remaining: ready_at - unix_now });
+        }
+    }
+
+    Ok(ClaimEligibility::Eligible { amount })
+}
+
+/// Resolves a `claim_eligibility` result against the escrow balance for `get_net_claimable`.
+/// An eligible amount the escrow cannot cover becomes `EscrowUnderfunded`, as in `claim`.
+pub fn net_claimable(status: ClaimEligibility, escrow_balance: u64) -> NetClaimable {
+    match status {
+        ClaimEligibility::Eligible { amount } if amount > escrow_balance => NetClaimable {
+            amount: 0,
+            status: ClaimEligibility::EscrowUnderfunded { escrow_balance },
+        },
+        ClaimEligibility::Eligible { amount } => NetClaimable { amount, status },
+        _ => NetClaimable { amount: 0, status },
+    }
+}
+
+/// Intermediate claim values for support, logged by `claim` in `debug-claim` builds only.
+/// `months_vested` counts whole periods past the cliff, capped at the vesting length, as
+/// `compute_vested` does for a relative cliff.
+#[cfg(feature = "debug-claim")]
+pub fn claim_debug_line(
+    beneficiary: &Beneficiary,
+    now: i64,
+    period: i64,
+    unlocked: u64,
+    claimable: u64,
+) -> Result<String> {
+    let months_elapsed = months_elapsed(beneficiary.start_time, now, period)?;
+    let months_vested = std::cmp::min(
+        months_elapsed.saturating_sub(beneficiary.cliff_months as u64),
+        beneficiary.total_months.saturating_sub(beneficiary.cliff_months) as u64,
+    );
+    Ok(format!(
+        "claim debug: months_elapsed={} months_vested={} unlocked={} claimed={} claimable={}",
+        months_elapsed, months_vested, unlocked, beneficiary.claimed_tokens, claimable
+    ))
+}
+
+/// Logs `memo` through the SPL Memo program. No signer accounts are attached, so the memo
+/// only attests to its text, which the destination reads from the transaction.
+pub fn invoke_memo(memo_program: &AccountInfo, memo: &[u8]) -> Result<()> {
+    let instruction = solana_program::instruction::Instruction {
+        program_id: MEMO_PROGRAM_ID,
+        accounts: vec![],
+        data: memo.to_vec(),
+    };
+    solana_program::program::invoke(&instruction, std::slice::from_ref(memo_program))?;
+    Ok(())
+}
+
+// ================================================================================================
+// VESTING MATH
+// ================================================================================================
+
+/// Escrow PDA for `token_mint` at rotation `escrow_nonce`.
+/// Nonce 0 is the original escrow; each `migrate_escrow` appends the next nonce byte.
+pub fn escrow_pda(token_mint: &Pubkey, escrow_nonce: u8, program_id: &Pubkey) -> (Pubkey, u8) {
+    if escrow_nonce == 0 {
+        Pubkey::find_program_address(&[b"escrow_wallet", token_mint.as_ref()], program_id)
+    } else {
+        Pubkey::find_program_address(&[b"escrow_wallet", token_mint.as_ref(), &[escrow_nonce]], program_id)
+    }
+}
+
+/// Verifies that `escrow_wallet` is the canonical escrow PDA for `token_mint` and `escrow_nonce`.
+pub fn verify_escrow_pda(
+    escrow_wallet: &Pubkey,
+    token_mint: &Pubkey,
+    escrow_nonce: u8,
+    escrow_bump: u8,
+    program_id: &Pubkey,
+) -> Result<()> {
+    let (expected_escrow_pda, expected_escrow_bump) = escrow_pda(token_mint, escrow_nonce, program_id);
+    require!(*escrow_wallet == expected_escrow_pda, VestingError::InvalidEscrowWallet);
+    require!(escrow_bump == expected_escrow_bump, VestingError::InvalidEscrowBump);
+    Ok(())
+}
+
+/// Checks that an escrow transfer of `amount` reduced the escrow balance by exactly `amount`.
+/// Guards `claimed_tokens` accounting against token programs that move a different amount.
+pub fn verify_escrow_debit(balance_before: u64, balance_after: u64, amount: u64) -> Result<()> {
+    require!(
+        balance_before.checked_sub(balance_after) == Some(amount),
+        VestingError::InvariantViolation
+    );
+    Ok(())
+}
+
+/// Verifies that `vault` is the address `owner_program` derives from `seeds`, the last of
+/// which is the bump. `create_program_address` rejects on-curve results, so the vault
+/// can only be signed for by `owner_program`. This program's own PDAs are refused.
+pub fn verify_vault_pda(vault: &Pubkey, owner_program: &Pubkey, seeds: &[Vec<u8>]) -> Result<()> {
+    require!(*owner_program != crate::ID, VestingError::InvalidVaultDerivation);
+    let seeds: Vec<&[u8]> = seeds.iter().map(|seed| seed.as_slice()).collect();
+    let derived = Pubkey::create_program_address(&seeds, owner_program)
+        .map_err(|_| VestingError::InvalidVaultDerivation)?;
+    require!(derived == *vault, VestingError::InvalidVaultDerivation);
+    Ok(())
+}
+
+/// Reads the balance of an SPL token account passed as an unchecked account.
+pub fn token_account_balance(account: &AccountInfo) -> Result<u64> {
+    let data = account.try_borrow_data()?;
+    Ok(TokenAccount::try_deserialize(&mut &data[..])?.amount)
+}
+
+/// Decrements `active_beneficiaries` by `count` entries that just became fully claimed and
+/// emits `ScheduleExhausted` once none remain. Called right before `emit_schedule_state`.
+pub fn record_exhausted(data_account: &mut Account<DataAccount>, count: u32) -> Result<()> {
+    if count == 0 {
+        return Ok(());
+    }
+    data_account.active_beneficiaries = data_account
+        .active_beneficiaries
+        .checked_sub(count)
+        .ok_or(VestingError::CountMismatch)?;
+
+    if data_account.active_beneficiaries == 0 {
+        let event_seq = data_account.next_event_seq()?;
+        emit!(ScheduleExhausted {
+            schedule: data_account.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+            event_seq,
+        });
+    }
+    Ok(())
+}
+
+/// Appends an operation to the schedule's `AuditLog`. The log must be passed exactly when
+/// the schedule was initialized with `audit_log`.
+pub fn record_audit(
+    data_account: &DataAccount,
+    audit_log: Option<&mut Account<AuditLog>>,
+    op: AuditOp,
+    actor: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    require!(audit_log.is_some() == data_account.audit_log, VestingError::InvalidAuditLog);
+    if let Some(log) = audit_log {
+        log.push(AuditEntry {
+            op,
+            actor,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        })?;
+    }
+    Ok(())
+}
+
+/// Emits `ScheduleStateChanged` with the schedule's post-operation totals.
+/// `escrow_balance` must be read after any transfer (i.e. after `reload`).
+pub fn emit_schedule_state(data_account: &mut Account<DataAccount>, escrow_balance: u64) -> Result<()> {
+    let total_allocated = total_allocation(&data_account.beneficiaries)?;
+    let total_claimed = data_account.total_claimed()?;
+    let event_seq = data_account.next_event_seq()?;
+    emit!(ScheduleStateChanged {
+        schedule: data_account.key(),
+        total_allocated,
+        total_claimed,
+        escrow_balance,
+        timestamp: Clock::get()?.unix_timestamp,
+        event_seq,
+    });
+    Ok(())
+}
+
+/// Emits `EscrowBalanceChanged` when the escrow balance went from `old_balance` to
+/// `new_balance` (the reloaded escrow amount). Does nothing if the balance is unchanged.
+pub fn emit_escrow_change(
+    data_account: &mut Account<DataAccount>,
+    old_balance: u64,
+    new_balance: u64,
+    reason: EscrowChangeReason,
+) -> Result<()> {
+    if old_balance == new_balance {
+        return Ok(());
+    }
+    let event_seq = data_account.next_event_seq()?;
+    emit!(EscrowBalanceChanged {
+        schedule: data_account.key(),
+        old_balance,
+        new_balance,
+        reason,
+        timestamp: Clock::get()?.unix_timestamp,
+        event_seq,
+    });
+    Ok(())
+}
+
+/// Escrow collateral against the unclaimed part of every allocation for `check_collateral`.
+/// 
+/// `required = ceil(obligations * min_collateral_bps / BPS_DENOMINATOR)`, so the escrow is
+/// collateralized exactly when `escrow * BPS_DENOMINATOR >= obligations * min_collateral_bps`.
+/// Fails with `MathOverflow` if `required` does not fit a `u64`.
+pub fn collateral_status(
+    beneficiaries: &[Beneficiary],
+    escrow_balance: u64,
+    min_collateral_bps: u16,
+) -> Result<CollateralStatus> {
+    let mut obligations = 0u64;
+    for b in beneficiaries.iter() {
+        obligations = obligations
+            .checked_add(b.allocated_tokens.saturating_sub(b.claimed_tokens))
+            .ok_or(VestingError::MathOverflow)?;
+    }
+
+    let required = (obligations as u128 * min_collateral_bps as u128)
+        .div_ceil(BPS_DENOMINATOR as u128);
+    let required = u64::try_from(required).map_err(|_| VestingError::MathOverflow)?;
+
+    Ok(CollateralStatus {
+        collateralized: escrow_balance >= required,
+        obligations,
+        required,
+        shortfall: required.saturating_sub(escrow_balance),
+    })
+}
+
+/// Shares of `total` for a `withdraw` treasury split, in `split` order - RAW UNITS.
+/// 
+/// Each entry gets `floor(total * bps / 10_000)` and the last also takes the rounding remainder,
+/// so the shares always sum to `total`. Fails with `InvalidTreasurySplit` unless there are
+/// 1 to `MAX_BATCH_SIZE` entries with non-zero `bps` summing to exactly `BPS_DENOMINATOR`.
+pub fn split_amounts(total: u64, split: &[TreasurySplit]) -> Result<Vec<u64>> {
+    require!(
+        !split.is_empty() && split.len() <= MAX_BATCH_SIZE,
+        VestingError::InvalidTreasurySplit
+    );
+    let mut bps_sum = 0u32;
+    for entry in split.iter() {
+        require!(entry.bps > 0, VestingError::InvalidTreasurySplit);
+        bps_sum += entry.bps as u32;
+    }
+    require!(bps_sum == BPS_DENOMINATOR as u32, VestingError::InvalidTreasurySplit);
+
+    let mut amounts: Vec<u64> = split
+        .iter()
+        .map(|entry| (total as u128 * entry.bps as u128 / BPS_DENOMINATOR as u128) as u64)
+        .collect();
+    let distributed: u64 = amounts.iter().sum();
+    if let Some(last) = amounts.last_mut() {
+        *last += total - distributed;
+    }
+    Ok(amounts)
+}
+
+/// Earliest time (exclusive) at which admin may recover a beneficiary's unclaimed tokens:
+/// MAX(cliff_end, vesting_end) + grace_period, from the same helpers as the claim path.
+pub fn earliest_withdraw_time(beneficiary: &Beneficiary, period: i64, grace_period: i64) -> Result<i64> {
+    let cliff_end = cliff_end_time(beneficiary, period)?;
+    let vesting_end = vesting_end_time(beneficiary, period)?;
+    std::cmp::max(cliff_end, vesting_end)
+        .checked_add(grace_period)
+        .ok_or(error!(VestingError::MathOverflow))
+}
+
+/// Tokens admin may recover from `beneficiary` at `now` - RAW UNITS.
+/// 
+/// Zero until `earliest_withdraw_time`; afterwards only the vested-but-unclaimed part, never
+/// an unvested remainder. Milestones lie within the vesting window, so milestone beneficiaries
+/// are fully vested by then. Fails with `InvariantViolation` if more than that was claimed.
+pub fn recoverable_unclaimed(
+    beneficiary: &Beneficiary,
+    now: i64,
+    period: i64,
+    grace_period: i64,
+) -> Result<u64> {
+    if now <= earliest_withdraw_time(beneficiary, period, grace_period)? {
+        return Ok(0);
+    }
+
+    let vested = if beneficiary.curve == VestingCurve::Milestones {
+        beneficiary.allocated_tokens
+    } else {
+        compute_vested(beneficiary, now, period)?
+    };
+    // Fully vested by now, so claims above the vested amount mean corrupted accounting
+    vested
+        .checked_sub(beneficiary.claimed_tokens)
+        .ok_or(error!(VestingError::InvariantViolation))
+}
+
+/// Unix timestamp from which `beneficiary`'s inactivity is measured: their last claim, or the
+/// end of their cliff if they never claimed (timestamp schedules only).
+pub fn last_activity_time(beneficiary: &Beneficiary, period: i64) -> Result<i64> {
+    if beneficiary.last_claim_time > 0 {
+        Ok(beneficiary.last_claim_time)
+    } else {
+        cliff_end_time(beneficiary, period)
+    }
+}
+
+/// Number of entries that still have unclaimed tokens.
+pub fn active_count(beneficiaries: &[Beneficiary]) -> u32 {
+    beneficiaries
+        .iter()
+        .filter(|b| b.claimed_tokens < b.allocated_tokens)
+        .count() as u32
+}
+
+/// Shares of `surplus` for `distribute_surplus`, in `beneficiaries` order - RAW UNITS.
+/// 
+/// Each entry not yet exhausted gets `surplus * allocated / open_allocated` (rounded down),
+/// where `open_allocated` sums the allocations of those entries. Exhausted entries get 0:
+/// reopening them would undo a forfeit or pay a `withdraw` recovery twice. Fails with
+/// `NoSurplus` when every entry is exhausted.
+pub fn surplus_shares(beneficiaries: &[Beneficiary], surplus: u64) -> Result<Vec<u64>> {
+    let is_open = |b: &Beneficiary| b.claimed_tokens < b.allocated_tokens;
+    let mut open_allocated = 0u64;
+    for b in beneficiaries.iter().filter(|b| is_open(b)) {
+        open_allocated = open_allocated
+            .checked_add(b.allocated_tokens)
+            .ok_or(VestingError::MathOverflow)?;
+    }
+    require!(open_allocated > 0, VestingError::NoSurplus);
+
+    beneficiaries
+        .iter()
+        .map(|b| {
+            if !is_open(b) {
+                return Ok(0);
+            }
+            let share = (surplus as u128)
+                .checked_mul(b.allocated_tokens as u128)
+                .ok_or(VestingError::MathOverflow)?
+                / open_allocated as u128;
+            Ok(share as u64)
+        })
+        .collect()
+}
+
+/// Sum of all allocations in RAW UNITS.
+pub fn total_allocation(beneficiaries: &[Beneficiary]) -> Result<u64> {
+    let mut total = 0u64;
+    for b in beneficiaries.iter() {
+        total = total.checked_add(b.allocated_tokens).ok_or(VestingError::MathOverflow)?;
+    }
+    Ok(total)
+}
+
+/// Time at which the cliff ends: `cliff_time` when set, otherwise `cliff_months` after start.
+pub fn cliff_end_time(beneficiary: &Beneficiary, period: i64) -> Result<i64> {
+    if let Some(cliff_time) = beneficiary.cliff_time {
+        return Ok(cliff_time);
+    }
+    (beneficiary.cliff_months as i64)
+        .checked_mul(period)
+        .and_then(|cliff| beneficiary.start_time.checked_add(cliff))
+        .ok_or(error!(VestingError::MathOverflow))
+}
+
+/// Time at which the beneficiary's allocation is fully vested.
+pub fn vesting_end_time(beneficiary: &Beneficiary, period: i64) -> Result<i64> {
+    let cliff_end = cliff_end_time(beneficiary, period)?;
+    let vesting_months = beneficiary.total_months.saturating_sub(beneficiary.cliff_months) as i64;
+    vesting_months
+        .checked_mul(period)
+        .and_then(|duration| cliff_end.checked_add(duration))
+        .ok_or(error!(VestingError::MathOverflow))
+}
+
+/// First period boundary strictly after `now`. Boundaries are whole periods from `cliff_time`
+/// when set, otherwise from `start_time`; before that anchor, the anchor itself is next.
+pub fn next_period_boundary(beneficiary: &Beneficiary, now: i64, period: i64) -> Result<i64> {
+    let anchor = beneficiary.cliff_time.unwrap_or(beneficiary.start_time);
+    if now < anchor {
+        return Ok(anchor);
+    }
+    let periods = (now - anchor) / period + 1;
+    periods
+        .checked_mul(period)
+        .and_then(|offset| anchor.checked_add(offset))
+        .ok_or(error!(VestingError::MathOverflow))
+}
+
+/// Converts a cumulative-percentage table into `beneficiary`'s milestones. `month` counts
+/// periods from `start_time` (0 = at start); months and `cumulative_bps` must strictly increase
+/// and the last entry must be 100% at `total_months`. Each milestone's amount is the increase
+/// in `allocated * cumulative_bps / BPS_DENOMINATOR` (rounded down), so the last reaches
+/// exactly `allocated_tokens`; a step that rounds to 0 is rejected.
+pub fn percentage_to_milestones(
+    beneficiary: &Beneficiary,
+    table: &[PercentageMilestone],
+    period: i64,
+) -> Result<Vec<Milestone>> {
+    require!(
+        !table.is_empty() && table.len() <= MAX_MILESTONES,
+        VestingError::InvalidMilestones
+    );
+    let last = table[table.len() - 1];
+    require!(
+        last.month == beneficiary.total_months && last.cumulative_bps == BPS_DENOMINATOR,
+        VestingError::InvalidMilestones
+    );
+
+    let mut milestones = Vec::with_capacity(table.len());
+    let mut previous: Option<PercentageMilestone> = None;
+    let mut previous_amount = 0u64;
+    for p in table.iter() {
+        if let Some(previous) = previous {
+            require!(
+                p.month > previous.month && p.cumulative_bps > previous.cumulative_bps,
+                VestingError::InvalidMilestones
+            );
+        }
+        let cumulative = (beneficiary.allocated_tokens as u128)
+            .checked_mul(p.cumulative_bps as u128)
+            .ok_or(VestingError::MathOverflow)?
+            / BPS_DENOMINATOR as u128;
+        let amount = (cumulative as u64).saturating_sub(previous_amount);
+        require!(amount > 0, VestingError::InvalidMilestones);
+
+        let unlock_time = (p.month as i64)
+            .checked_mul(period)
+            .and_then(|offset| beneficiary.start_time.checked_add(offset))
+            .ok_or(VestingError::MathOverflow)?;
+        milestones.push(Milestone { unlock_time, amount });
+        previous = Some(*p);
+        previous_amount = cumulative as u64;
+    }
+    Ok(milestones)
+}
+
+/// Samples `DataAccount::compute_vested_for` at `points` evenly spaced times from `start_time`
+/// to `vesting_end_time` inclusive (`points` clamped to `MAX_CURVE_SAMPLES`). Sample times
+/// are rounded down, except the last one, which is exactly the vesting end.
+pub fn sample_vesting_curve(
+    data_account: &DataAccount,
+    beneficiary: &Beneficiary,
+    points: u8,
+) -> Result<Vec<CurveSample>> {
+    require!(points >= 2, VestingError::InvalidSampleCount);
+    let points = std::cmp::min(points, MAX_CURVE_SAMPLES) as i128;
+
+    let start = beneficiary.start_time;
+    let span = vesting_end_time(beneficiary, data_account.period_length())?
+        .checked_sub(start)
+        .ok_or(VestingError::MathOverflow)? as i128;
+
+    (0..points)
+        .map(|i| {
+            let timestamp = start + (span * i / (points - 1)) as i64;
+            Ok(CurveSample {
+                timestamp,
+                cumulative_vested: data_account.compute_vested_for(beneficiary, timestamp)?,
+            })
+        })
+        .collect()
+}
+
+/// Derives the overall schedule status at `now`. Checked in order:
+/// - Closed: every allocation has been claimed or recovered.
+/// - Recoverable: some unclaimed tokens are past the grace period (admin can `withdraw`).
+/// - NotStarted / InCliff / Vesting: before the first start / first cliff end / last vesting end.
+/// - FullyVested (no-withdraw schedules) or InGracePeriod (everyone else) afterwards.
+pub fn schedule_health(data_account: &DataAccount, now: i64) -> Result<ScheduleHealth> {
+    let period = data_account.period_length();
+    let grace_period = GRACE_PERIOD_MONTHS * period;
+
+    let mut outstanding = false;
+    let mut recoverable = false;
+    let mut first_start = i64::MAX;
+    let mut first_cliff_end = i64::MAX;
+    let mut last_end = i64::MIN;
+
+    for b in data_account.beneficiaries.iter() {
+        let unclaimed = b.allocated_tokens.saturating_sub(b.claimed_tokens);
+        if unclaimed > 0 {
+            outstanding = true;
+            if !data_account.no_withdraw && now > earliest_withdraw_time(b, period, grace_period)? {
+                recoverable = true;
+            }
+        }
+
+        let cliff_end = cliff_end_time(b, period)?;
+
+        first_start = std::cmp::min(first_start, b.start_time);
+        first_cliff_end = std::cmp::min(first_cliff_end, cliff_end);
+        last_end = std::cmp::max(last_end, vesting_end_time(b, period)?);
+    }
+
+    let health = if !outstanding {
+        ScheduleHealth::Closed
+    } else if recoverable {
+        ScheduleHealth::Recoverable
+    } else if now < first_start {
+        ScheduleHealth::NotStarted
+    } else if now < first_cliff_end {
+        ScheduleHealth::InCliff
+    } else if now < last_end {
+        ScheduleHealth::Vesting
+    } else if data_account.no_withdraw {
+        ScheduleHealth::FullyVested
+    } else {
+        ScheduleHealth::InGracePeriod
+    };
+
+    Ok(health)
+}
+
+/// Validates a single beneficiary configuration against the schedule rules.
+/// `now` and `max_start_delay` are expressed in the schedule's time unit.
+/// `min_months` is the schedule's `min_vesting_months`.
+pub fn validate_beneficiary(
+    b: &Beneficiary,
+    now: i64,
+    max_start_delay: i64,
+    period: i64,
+    min_months: u8,
+) -> Result<()> {
+    // Validate vesting periods
+    require!(b.total_months >= 1, VestingError::InvalidVestingPeriod);
+    require!(b.total_months >= min_months, VestingError::VestingTooShort);
+    require!(b.total_months <= MAX_TOTAL_MONTHS, VestingError::TotalMonthsTooLong);
+    require!(b.cliff_months <= MAX_CLIFF_MONTHS, VestingError::CliffTooLong);
+    require!(b.cliff_months < b.total_months, VestingError::InvalidCliffPeriod);
+
+    require!(b.allocated_tokens > 0, VestingError::InvalidAllocation);
+    // Only `forfeit` records a settlement
+    require!(b.settled_from == 0, VestingError::InvalidAllocation);
+    // A preset last claim would start the cooldown and the inactivity window early
+    require!(b.last_claim_time == 0, VestingError::InvalidClaimHistory);
+    // Reported in `TokensClaimed`, so only claims may count up
+    require!(b.claim_count == 0, VestingError::InvalidClaimHistory);
+
+    // Milestone release is only set up through `convert_to_milestones`
+    require!(b.curve != VestingCurve::Milestones, VestingError::InvalidMilestones);
+
+    // Validate time bounds
+    require!(b.start_time >= now, VestingError::InvalidStartTime);
+    require!(
+        b.start_time <= now + max_start_delay,
+        VestingError::StartTimeTooFar
+    );
+
+    // Validate vesting configuration consistency
+    if b.cliff_months > 0 {
+        require!(b.total_months % b.cliff_months == 0, VestingError::InvalidVestingConfig);
+    }
+
+    // An absolute cliff date must fall after start and within the cliff length limit
+    if let Some(cliff_time) = b.cliff_time {
+        require!(cliff_time > b.start_time, VestingError::InvalidCliffTime);
+        require!(
+            cliff_time - b.start_time <= MAX_CLIFF_MONTHS as i64 * period,
+            VestingError::CliffTooLong
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns the number of whole periods elapsed since `start_time` (0 before start).
+/// `period` is `SECONDS_PER_MONTH` for timestamp schedules or `slots_per_period` for slot schedules.
+pub fn months_elapsed(start_time: i64, now: i64, period: i64) -> Result<u64> {
+    if now < start_time {
+        return Ok(0);
+    }
+    let time_diff = now.saturating_sub(start_time);
+    let calculated_months = time_diff.checked_div(period).ok_or(VestingError::MathOverflow)?;
+    Ok(calculated_months as u64)
+}
+
+/// Computes the cumulative amount vested for a beneficiary at `now` - RAW UNITS.
+/// 
+/// Vesting is calculated in discrete steps of `period` after the cliff. With
+/// t = months_vested / vesting_months, the unlocked share follows the beneficiary's
+/// `curve` (t, 1 - (1 - t)^2 or t^2), evaluated as an exact ratio in 128-bit arithmetic.
+/// Returns 0 while the cliff has not elapsed. With `cliff_time` set, the cliff ends at that
+/// absolute time and the `total_months - cliff_months` vesting periods are counted from it.
+/// 
+/// 
+/// Steps are whole periods, so at the exact instant the cliff ends nothing has vested yet:
+/// the first step unlocks one `period` later. See `compute_vested_with` for the
+/// `unlock_first_period_at_cliff` variant.
+/// 
+/// With `total_months` a `u8`, the numerator stays below 2^16, so `allocated * numerator`
+/// is bounded by 2^80 and the ratio never exceeds 1: the final `u64` conversion cannot fail.
+pub fn compute_vested(beneficiary: &Beneficiary, now: i64, period: i64) -> Result<u64> {
+    compute_vested_with(beneficiary, now, period, false)
+}
+
+/// `compute_vested`, optionally counting the first vesting step as elapsed as soon as the
+/// cliff ends (`months_vested = max(months_after_cliff, 1)`), for schedules initialized with
+/// `unlock_first_period_at_cliff`. With no cliff, the first step unlocks at `start_time`.
+pub fn compute_vested_with(
+    beneficiary: &Beneficiary,
+    now: i64,
+    period: i64,
+    unlock_first_period_at_cliff: bool,
+) -> Result<u64> {
+    let cliff_months = beneficiary.cliff_months as u64;
+    let total_months = beneficiary.total_months as u64;
+    let vesting_month = total_months.saturating_sub(cliff_months);
+
+    require!(vesting_month > 0, VestingError::InvalidVestingConfig);
+
+    let months_after_cliff = match beneficiary.cliff_time {
+        Some(cliff_time) => {
+            if now < cliff_time {
+                return Ok(0);
+            }
+            months_elapsed(cliff_time, now, period)?
+        }
+        None => {
+            let months_elapsed = months_elapsed(beneficiary.start_time, now, period)?;
+            if now < beneficiary.start_time || months_elapsed < cliff_months {
+                return Ok(0);
+            }
+            months_elapsed - cliff_months
+        }
+    };
+
+    let months_after_cliff = if unlock_first_period_at_cliff {
+        std::cmp::max(months_after_cliff, 1)
+    } else {
+        months_after_cliff
+    };
+    let months_vested = std::cmp::min(months_after_cliff, vesting_month);
+
+    // Calculate unlocked tokens using 128-bit arithmetic for precision
+    let allocated_raw = beneficiary.allocated_tokens as u128; // RAW UNITS
+
+    let unlocked = if months_vested >= vesting_month {
+        allocated_raw
+    } else {
+        let m = months_vested as u128;
+        let n = vesting_month as u128;
+        // unlocked = allocated * numerator / denominator
+        let (numerator, denominator) = match beneficiary.curve {
+            VestingCurve::Linear => (m, n),
+            // 1 - (1 - m/n)^2 = m * (2n - m) / n^2
+            VestingCurve::FrontLoaded => (m * (2 * n - m), n * n),
+            VestingCurve::BackLoaded => (m * m, n * n),
+            // Partial unlocks live in the side account; see `MilestoneSchedule::vested_at`
+            VestingCurve::Milestones => return err!(VestingError::MilestonesRequired),
+        };
+        allocated_raw
+            .checked_mul(numerator)
+            .ok_or(VestingError::MathOverflow)?
+            .checked_div(denominator)
+            .ok_or(VestingError::MathOverflow)?
+    };
+
+    Ok(u64::try_from(unlocked).map_err(|_| VestingError::MathOverflow)?)
+}
+
+/// Linear vesting that accrues every second (every slot in slot mode) after the cliff instead
+/// of in whole periods, for schedules initialized with `continuous_linear` - RAW UNITS.
+/// 
+/// The rate is kept in Q64.64 fixed point, rounded up: `rate = ceil(allocated * 2^64 / duration)`
+/// with `duration = (total_months - cliff_months) * period`. The vested amount is
+/// `floor(rate * elapsed / 2^64)`, evaluated as `int(rate) * elapsed + (frac(rate) * elapsed >> 64)`
+/// so every product fits in 128 bits, and floored to `u64` only once, at the end.
+/// 
+/// Precision: the rounding error of the rate is below 2^-64 per clock unit, so the result
+/// exceeds the exact `floor(allocated * elapsed / duration)` only when `elapsed * duration`
+/// reaches 2^64, and then by at most one raw unit. Durations below 2^32 units (every
+/// timestamp schedule, as `MAX_TOTAL_MONTHS * SECONDS_PER_MONTH < 2^29`) are always exact.
+/// 
+/// Final total: the result is non-decreasing in `now`, clamped to `allocated`, and equal to
+/// `allocated` from `elapsed >= duration` on, so the claims of a beneficiary sum to exactly
+/// their allocation. `unlock_first_period_at_cliff` has no effect on this mode.
+pub fn compute_vested_continuous(beneficiary: &Beneficiary, now: i64, period: i64) -> Result<u64> {
+    let vesting_months = beneficiary.total_months.saturating_sub(beneficiary.cliff_months) as i128;
+    require!(vesting_months > 0 && period > 0, VestingError::InvalidVestingConfig);
+
+    let cliff_end = cliff_end_time(beneficiary, period)?;
+    if now <= cliff_end {
+        return Ok(0);
+    }
+
+    let duration = vesting_months * period as i128;
+    let elapsed = now as i128 - cliff_end as i128;
+    if elapsed >= duration {
+        return Ok(beneficiary.allocated_tokens);
+    }
+
+    let allocated = beneficiary.allocated_tokens as u128; // RAW UNITS
+    let (duration, elapsed) = (duration as u128, elapsed as u128);
+    let rate = (allocated << 64).div_ceil(duration); // Q64.64 raw units per clock unit
+
+    let whole = (rate >> 64) * elapsed;
+    let fraction = ((rate & u64::MAX as u128) * elapsed) >> 64;
+    let vested = std::cmp::min(whole + fraction, allocated);
+
+    Ok(vested as u64)
+}
+
+// ================================================================================================
+// AUTO-STAKE CPI
+// ================================================================================================
+
+/// Deposits `amount` freshly claimed tokens into the schedule's staking program.
+/// 
+/// Expected `remaining_accounts` layout for `claim`:
+/// 0. The staking program (must equal `auto_stake_program`)
+/// 1. .. Accounts of its `deposit(amount: u64)` instruction, in order
+/// 
+/// Tokens are first transferred to the beneficiary's ATA as usual; the deposit then moves
+/// them on under the beneficiary's signature. `amount` is what that ATA actually received,
+/// so the deposit never asks for more. The data account PDA never signs this CPI.
+pub fn deposit_to_stake_program(
+    stake_program: &Pubkey,
+    remaining_accounts: &[AccountInfo],
+    amount: u64,
+) -> Result<()> {
+    let (program_info, deposit_accounts) = remaining_accounts
+        .split_first()
+        .ok_or(VestingError::InvalidStakeProgram)?;
+    require!(
+        program_info.key() == *stake_program && program_info.executable,
+        VestingError::InvalidStakeProgram
+    );
+
+    let mut data = STAKE_DEPOSIT_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+
+    let instruction = solana_program::instruction::Instruction {
+        program_id: *stake_program,
+        accounts: deposit_accounts
+            .iter()
+            .map(|a| solana_program::instruction::AccountMeta {
+                pubkey: a.key(),
+                is_signer: a.is_signer,
+                is_writable: a.is_writable,
+            })
+            .collect(),
+        data,
+    };
+    solana_program::program::invoke(&instruction, remaining_accounts)?;
+
+    Ok(())
+}
+
+// ================================================================================================
+// WIRE FORMAT
+// ================================================================================================
+
+/// Encodes one page of the schedule for `export_schedule`. All integers are little-endian.
+/// 
+/// Page layout (format version 1):
+/// - version: u8 (`EXPORT_FORMAT_VERSION`)
+/// - page: u16
+/// - total_pages: u16
+/// - beneficiary_count: u16
+/// - page 0 only: token_amount u64, authority [32], token_mint [32], decimals u8
+/// - entries: key [32], allocated_tokens u64, claimed_tokens u64, start_time i64,
+///   cliff_months u8, total_months u8
+pub fn encode_schedule_page(data_account: &DataAccount, page: u16) -> Result<Vec<u8>> {
+    let count = data_account.beneficiaries.len();
+    let total_pages = std::cmp::max(1, count.div_ceil(EXPORT_ENTRIES_PER_PAGE));
+    require!((page as usize) < total_pages, VestingError::InvalidPage);
+
+    let mut out = Vec::with_capacity(1000);
+    out.push(EXPORT_FORMAT_VERSION);
+    out.extend_from_slice(&page.to_le_bytes());
+    out.extend_from_slice(&(total_pages as u16).to_le_bytes());
+    out.extend_from_slice(&(count as u16).to_le_bytes());
+
+    if page == 0 {
+        out.extend_from_slice(&data_account.token_amount.to_le_bytes());
+        out.extend_from_slice(data_account.authority.as_ref());
+        out.extend_from_slice(data_account.token_mint.as_ref());
+        out.push(data_account.decimals);
+    }
+
+    let start = page as usize * EXPORT_ENTRIES_PER_PAGE;
+    let end = std::cmp::min(start + EXPORT_ENTRIES_PER_PAGE, count);
+    for b in data_account.beneficiaries[start..end].iter() {
+        out.extend_from_slice(b.key.as_ref());
+        out.extend_from_slice(&b.allocated_tokens.to_le_bytes());
+        out.extend_from_slice(&b.claimed_tokens.to_le_bytes());
+        out.extend_from_slice(&b.start_time.to_le_bytes());
+        out.push(b.cliff_months);
+        out.push(b.total_months);
+    }
+
+    Ok(out)
+}
+
+/// Canonical bytes hashed by `get_schedule_hash`. All integers are little-endian.
+/// 
+/// - token_mint: [32]
+/// - beneficiary_count: u32, counting assigned entries only
+/// - entries, sorted by key bytes ascending (so storage order does not matter): key [32],
+///   allocated_tokens u64, start_time i64, cliff_months u8, total_months u8
+/// 
+/// Unassigned `reserve_slots` entries have no key yet and are left out until assigned.
+pub fn schedule_hash_preimage(data_account: &DataAccount) -> Vec<u8> {
+    let mut entries: Vec<&Beneficiary> = data_account.beneficiaries.iter().filter(|b| b.assigned).collect();
+    entries.sort_by(|a, b| a.key.as_ref().cmp(b.key.as_ref()));
+
+    let mut out = Vec::with_capacity(32 + 4 + entries.len() * 50);
+    out.extend_from_slice(data_account.token_mint.as_ref());
+    out.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    for b in entries {
+        out.extend_from_slice(b.key.as_ref());
+        out.extend_from_slice(&b.allocated_tokens.to_le_bytes());
+        out.extend_from_slice(&b.start_time.to_le_bytes());
+        out.push(b.cliff_months);
+        out.push(b.total_months);
+    }
+    out
+}
+
+/// keccak-256 of `schedule_hash_preimage`.
+pub fn schedule_hash(data_account: &DataAccount) -> [u8; 32] {
+    solana_program::keccak::hash(&schedule_hash_preimage(data_account)).to_bytes()
+}
+
+// ================================================================================================
+// CLIENT HELPERS
+// ================================================================================================
+
+/// Derives the data account and escrow wallet PDAs for `token_mint` using the canonical seeds.
+/// `escrow_nonce` is the schedule's `DataAccount::escrow_nonce` (0 until the first `migrate_escrow`).
+/// Returns `(data_account, data_bump, escrow_wallet, escrow_bump)`.
+/// 
+/// Available to off-chain clients that depend on this crate with `no-entrypoint` (or `cpi`).
+#[cfg(any(feature = "no-entrypoint", test))]
+pub fn derive_pdas(program_id: &Pubkey, token_mint: &Pubkey, escrow_nonce: u8) -> (Pubkey, u8, Pubkey, u8) {
+    let (data_account, data_bump) =
+        Pubkey::find_program_address(&[b"data_account", token_mint.as_ref()], program_id);
+    let (escrow_wallet, escrow_bump) = escrow_pda(token_mint, escrow_nonce, program_id);
+    (data_account, data_bump, escrow_wallet, escrow_bump)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derive_pdas_matches_on_chain_seeds() {
+        for _ in 0..8 {
+            let token_mint = Pubkey::new_unique();
+            let (data_account, data_bump, escrow_wallet, escrow_bump) =
+                derive_pdas(&crate::ID, &token_mint, 0);
+
+            // Same derivation the `seeds = [b"data_account", ...]` constraints and signer seeds use
+            let expected_data = Pubkey::create_program_address(
+                &[b"data_account", token_mint.as_ref(), &[data_bump]],
+                &crate::ID,
+            )
+            .unwrap();
+            assert_eq!(data_account, expected_data);
+
+            assert!(verify_escrow_pda(&escrow_wallet, &token_mint, 0, escrow_bump, &crate::ID).is_ok());
+        }
+    }
+
+    #[test]
+    fn derive_pdas_separates_escrows_by_nonce() {
+        let token_mint = Pubkey::new_unique();
+        let (data_account, _, original, _) = derive_pdas(&crate::ID, &token_mint, 0);
+
+        let mut escrows = vec![original];
+        for escrow_nonce in 1..4 {
+            let (rotated_data, _, escrow_wallet, escrow_bump) =
+                derive_pdas(&crate::ID, &token_mint, escrow_nonce);
+            // The data account stays put across rotations; each nonce gets its own escrow
+            assert_eq!(rotated_data, data_account);
+            assert!(!escrows.contains(&escrow_wallet));
+            assert!(
+                verify_escrow_pda(&escrow_wallet, &token_mint, escrow_nonce, escrow_bump, &crate::ID).is_ok()
+            );
+            escrows.push(escrow_wallet);
+        }
+    }
+
+    #[test]
+    fn check_batch_size_accepts_up_to_the_maximum() {
+        assert!(check_batch_size(0).is_ok());
+        assert!(check_batch_size(MAX_BATCH_SIZE).is_ok());
+        assert!(check_batch_size(MAX_BATCH_SIZE + 1).is_err());
+    }
+
+    #[test]
+    fn verify_escrow_debit_requires_exact_amount() {
+        assert!(verify_escrow_debit(1_000, 900, 100).is_ok());
+        // e.g. a transfer fee withheld from the escrow on top of the amount
+        assert!(verify_escrow_debit(1_000, 899, 100).is_err());
+        assert!(verify_escrow_debit(1_000, 950, 100).is_err());
+        assert!(verify_escrow_debit(100, 200, 100).is_err());
+    }
+
+    #[test]
+    fn compute_vested_handles_u64_max_allocations() {
+        let period = SECONDS_PER_MONTH;
+        for curve in [VestingCurve::Linear, VestingCurve::FrontLoaded, VestingCurve::BackLoaded] {
+            let beneficiary = Beneficiary {
+                allocated_tokens: u64::MAX,
+                total_months: MAX_TOTAL_MONTHS,
+                curve,
+                ..Default::default()
+            };
+            for months in [1, 60, MAX_TOTAL_MONTHS as i64 - 1] {
+                let vested = compute_vested(&beneficiary, months * period, period).unwrap();
+                assert!(vested < u64::MAX);
+            }
+            let end = MAX_TOTAL_MONTHS as i64 * period;
+            assert_eq!(compute_vested(&beneficiary, end, period).unwrap(), u64::MAX);
+        }
+    }
+
+    #[test]
+    fn compute_vested_is_exact_at_u8_month_limits() {
+        let period = SECONDS_PER_MONTH;
+        let beneficiary = |total_months, curve| Beneficiary {
+            allocated_tokens: u64::MAX,
+            total_months,
+            curve,
+            ..Default::default()
+        };
+
+        // allocated * m * (2n - m) reaches ~2^80 here; a u64 intermediate would overflow
+        // and return MathOverflow instead of these values.
+        let cases = [
+            (255, 254, VestingCurve::Linear, 254 * 72_340_172_838_076_673),
+            (255, 254, VestingCurve::FrontLoaded, 18_446_460_386_757_245_431),
+            (255, 254, VestingCurve::BackLoaded, 18_302_347_414_985_704_452),
+            (255, 1, VestingCurve::BackLoaded, 283_686_952_306_183),
+            // Truncation towards zero when the split is not exact
+            (254, 1, VestingCurve::Linear, 72_624_976_668_147_841),
+            (254, 253, VestingCurve::Linear, 18_374_119_097_041_403_773),
+        ];
+        for (total_months, months, curve, expected) in cases {
+            let vested = compute_vested(&beneficiary(total_months, curve), months * period, period);
+            assert_eq!(vested.unwrap(), expected);
+        }
+
+        // The result never exceeds the allocation, so the final u64 conversion cannot wrap
+        for curve in [VestingCurve::Linear, VestingCurve::FrontLoaded, VestingCurve::BackLoaded] {
+            let mut previous = 0;
+            for months in 0..=255 {
+                let vested = compute_vested(&beneficiary(255, curve), months * period, period).unwrap();
+                assert!(vested >= previous);
+                previous = vested;
+            }
+            assert_eq!(previous, u64::MAX);
+            assert_eq!(compute_vested(&beneficiary(255, curve), i64::MAX, period).unwrap(), u64::MAX);
+        }
+    }
+
+    #[test]
+    fn vesting_curves_at_quarter_points() {
+        let period = SECONDS_PER_MONTH;
+        let beneficiary = |curve| Beneficiary {
+            allocated_tokens: 1_600,
+            total_months: 4,
+            curve,
+            ..Default::default()
+        };
+
+        // (months, linear, front-loaded, back-loaded) for t = 0.25, 0.5, 0.75
+        for (months, linear, front, back) in [(1, 400, 700, 100), (2, 800, 1_200, 400), (3, 1_200, 1_500, 900)] {
+            let now = months * period;
+            assert_eq!(compute_vested(&beneficiary(VestingCurve::Linear), now, period).unwrap(), linear);
+            assert_eq!(compute_vested(&beneficiary(VestingCurve::FrontLoaded), now, period).unwrap(), front);
+            assert_eq!(compute_vested(&beneficiary(VestingCurve::BackLoaded), now, period).unwrap(), back);
+        }
+
+        for curve in [VestingCurve::Linear, VestingCurve::FrontLoaded, VestingCurve::BackLoaded] {
+            assert_eq!(compute_vested(&beneficiary(curve), 0, period).unwrap(), 0);
+            assert_eq!(compute_vested(&beneficiary(curve), 4 * period, period).unwrap(), 1_600);
+        }
+    }
+
+    #[test]
+    fn absolute_cliff_time_overrides_cliff_months() {
+        let period = SECONDS_PER_MONTH;
+        // 3-month offset cliff, then 9 monthly steps of 100
+        let offset = Beneficiary {
+            allocated_tokens: 900,
+            cliff_months: 3,
+            total_months: 12,
+            ..Default::default()
+        };
+        let earlier = Beneficiary { cliff_time: Some(period), ..offset };
+        let later = Beneficiary { cliff_time: Some(5 * period), ..offset };
+
+        assert_eq!(compute_vested(&offset, 2 * period, period).unwrap(), 0);
+        assert_eq!(compute_vested(&earlier, 2 * period, period).unwrap(), 100);
+        assert_eq!(compute_vested(&offset, 4 * period, period).unwrap(), 100);
+        assert_eq!(compute_vested(&later, 4 * period, period).unwrap(), 0);
+        assert_eq!(compute_vested(&later, 6 * period, period).unwrap(), 100);
+
+        assert_eq!(vesting_end_time(&earlier, period).unwrap(), 10 * period);
+        assert_eq!(vesting_end_time(&later, period).unwrap(), 14 * period);
+        assert_eq!(compute_vested(&later, 14 * period, period).unwrap(), 900);
+    }
+
+    #[test]
+    fn first_period_at_exact_cliff_boundary() {
+        let period = SECONDS_PER_MONTH;
+        // 3-month cliff, then 9 monthly steps of 100
+        let beneficiary = Beneficiary {
+            allocated_tokens: 900,
+            start_time: period,
+            cliff_months: 3,
+            total_months: 12,
+            ..Default::default()
+        };
+        let cliff_end = 4 * period;
+
+        // Default: the cliff ending unlocks nothing until a full period has passed
+        assert_eq!(compute_vested_with(&beneficiary, cliff_end, period, false).unwrap(), 0);
+        assert_eq!(compute_vested_with(&beneficiary, cliff_end + period, period, false).unwrap(), 100);
+
+        // unlock_first_period_at_cliff: the first step is available at the boundary itself
+        assert_eq!(compute_vested_with(&beneficiary, cliff_end - 1, period, true).unwrap(), 0);
+        assert_eq!(compute_vested_with(&beneficiary, cliff_end, period, true).unwrap(), 100);
+        assert_eq!(compute_vested_with(&beneficiary, cliff_end + period, period, true).unwrap(), 100);
+        assert_eq!(compute_vested_with(&beneficiary, cliff_end + 2 * period, period, true).unwrap(), 200);
+        assert_eq!(compute_vested_with(&beneficiary, 13 * period, period, true).unwrap(), 900);
+
+        // Without a cliff, the first step unlocks at start, never before it
+        let no_cliff = Beneficiary { cliff_months: 0, ..beneficiary };
+        assert_eq!(compute_vested_with(&no_cliff, period - 1, period, true).unwrap(), 0);
+        assert_eq!(compute_vested_with(&no_cliff, period, period, true).unwrap(), 75);
+    }
+
+    #[test]
+    fn continuous_linear_matches_exact_ratio() {
+        let period = SECONDS_PER_MONTH;
+        let beneficiary = Beneficiary {
+            allocated_tokens: 1_000_000_007,
+            cliff_months: 3,
+            total_months: 15,
+            ..Default::default()
+        };
+        let cliff_end = 3 * period;
+        let duration = 12 * period;
+        assert_eq!(compute_vested_continuous(&beneficiary, cliff_end, period).unwrap(), 0);
+        for elapsed in [1, 59, period - 1, period, period + period / 2, 7 * period + 12_345, duration - 1] {
+            let exact = 1_000_000_007u128 * elapsed as u128 / duration as u128;
+            let vested = compute_vested_continuous(&beneficiary, cliff_end + elapsed, period).unwrap();
+            assert_eq!(vested as u128, exact);
+        }
+
+        // Largest allocation over the longest timestamp schedule
+        let max = Beneficiary {
+            allocated_tokens: u64::MAX,
+            total_months: MAX_TOTAL_MONTHS,
+            ..Default::default()
+        };
+        let duration = MAX_TOTAL_MONTHS as i64 * period;
+        for elapsed in [1, period, duration / 3, duration - 1] {
+            let exact = u64::MAX as u128 * elapsed as u128 / duration as u128;
+            assert_eq!(compute_vested_continuous(&max, elapsed, period).unwrap() as u128, exact);
+        }
+        assert_eq!(compute_vested_continuous(&max, duration, period).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn continuous_linear_loses_less_than_stepped_or_integer_rate() {
+        let period = SECONDS_PER_MONTH;
+        let allocated: u64 = 1_000_000_000_000;
+        let beneficiary = Beneficiary { allocated_tokens: allocated, total_months: 12, ..Default::default() };
+        let duration = 12 * period;
+
+        // Halfway through the second month: exactly 1.5 / 12 of the allocation has accrued
+        let now = period + period / 2;
+        let exact = 125_000_000_000;
+
+        // Whole periods only: one month's share
+        let stepped = compute_vested(&beneficiary, now, period).unwrap();
+        // Per-second rate truncated to an integer before accumulating
+        let integer_rate = allocated / duration as u64 * now as u64;
+        let continuous = compute_vested_continuous(&beneficiary, now, period).unwrap();
+
+        assert_eq!(exact - stepped, 41_666_666_667);
+        assert_eq!(exact - integer_rate, 1_487_168);
+        assert_eq!(continuous, exact);
+    }
+
+    #[test]
+    fn continuous_linear_sums_to_allocation() {
+        // Short slot periods so every step of the accrual is visited
+        let period = 7;
+        let beneficiary = Beneficiary {
+            allocated_tokens: 1_000,
+            cliff_months: 1,
+            total_months: 4,
+            ..Default::default()
+        };
+        let duration = 3 * period;
+
+        let mut claimed = 0;
+        for now in 0..=(4 * period + 3) {
+            let vested = compute_vested_continuous(&beneficiary, now, period).unwrap();
+            assert!(vested >= claimed);
+            claimed = vested;
+        }
+        assert_eq!(claimed, 1_000);
+        assert_eq!(compute_vested_continuous(&beneficiary, period + duration - 1, period).unwrap(), 952);
+        assert_eq!(compute_vested_continuous(&beneficiary, period + duration, period).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn continuous_mode_never_vests_less_than_monthly() {
+        let data = DataAccount { claim_ceiling_time: i64::MAX, ..Default::default() };
+        let period = data.period_length();
+        let monthly = Beneficiary {
+            allocated_tokens: 1_000,
+            cliff_months: 1,
+            total_months: 4,
+            ..Default::default()
+        };
+        let continuous = Beneficiary { mode: VestingMode::Continuous, ..monthly };
+
+        for now in (0..=5 * period).step_by(period as usize / 4) {
+            let stepped = data.compute_vested_for(&monthly, now).unwrap();
+            let smooth = data.compute_vested_for(&continuous, now).unwrap();
+            assert!(smooth >= stepped);
+            if now % period == 0 {
+                assert_eq!(smooth, stepped);
+            }
+        }
+    }
+
+    #[test]
+    fn collateral_status_at_above_and_below_the_threshold() {
+        let entry = Beneficiary { allocated_tokens: 1_000, claimed_tokens: 200, ..Default::default() };
+        let beneficiaries = [entry, Beneficiary { allocated_tokens: 200, ..Default::default() }];
+
+        // Obligations are 1_000, so 105% requires 1_050
+        let at = collateral_status(&beneficiaries, 1_050, 10_500).unwrap();
+        assert!(at.collateralized);
+        assert_eq!((at.obligations, at.required, at.shortfall), (1_000, 1_050, 0));
+
+        assert!(collateral_status(&beneficiaries, 1_051, 10_500).unwrap().collateralized);
+
+        let below = collateral_status(&beneficiaries, 1_049, 10_500).unwrap();
+        assert!(!below.collateralized);
+        assert_eq!(below.shortfall, 1);
+
+        // The required amount is rounded up
+        let odd = collateral_status(&[Beneficiary { allocated_tokens: 3, ..Default::default() }], 3, 10_001).unwrap();
+        assert_eq!((odd.required, odd.shortfall), (4, 1));
+    }
+
+    #[test]
+    fn audit_log_overwrites_the_oldest_entry_when_full() {
+        let mut log = AuditLog { schedule: Pubkey::default(), total_ops: 0, entries: vec![] };
+        let entry = |amount| AuditEntry { op: AuditOp::Claim, actor: Pubkey::default(), amount, timestamp: 0 };
+
+        let ops = AUDIT_LOG_CAPACITY as u64 + 3;
+        for amount in 0..ops {
+            log.push(entry(amount)).unwrap();
+        }
+
+        assert_eq!(log.total_ops, ops);
+        assert_eq!(log.entries.len(), AUDIT_LOG_CAPACITY);
+        // The three newest overwrote the three oldest; the oldest survivor comes next
+        let amounts: Vec<u64> = log.entries.iter().map(|e| e.amount).collect();
+        assert_eq!(&amounts[..3], &[16, 17, 18]);
+        assert_eq!(amounts[3], 3);
+        assert_eq!(amounts[AUDIT_LOG_CAPACITY - 1], 15);
+    }
+
+    #[test]
+    fn percentage_milestones_convert_and_interpolate() {
+        let period = SECONDS_PER_MONTH;
+        let beneficiary = Beneficiary { allocated_tokens: 1_001, total_months: 12, ..Default::default() };
+        let row = |month, cumulative_bps| PercentageMilestone { month, cumulative_bps };
+        let table = [row(3, 2_500), row(6, 5_000), row(12, BPS_DENOMINATOR)];
+
+        let milestones = percentage_to_milestones(&beneficiary, &table, period).unwrap();
+        let amounts: Vec<u64> = milestones.iter().map(|m| m.amount).collect();
+        assert_eq!(amounts, vec![250, 250, 501]);
+        assert_eq!(milestones[2].unlock_time, 12 * period);
+
+        let schedule = MilestoneSchedule { beneficiary: Pubkey::default(), milestones };
+        let step = |now| schedule.vested_at(0, now, MilestoneInterpolation::Step).unwrap();
+        let linear = |now| schedule.vested_at(0, now, MilestoneInterpolation::Linear).unwrap();
+        assert_eq!(step(3 * period - 1), 0);
+        assert_eq!(step(3 * period), 250);
+        assert_eq!(linear(period + period / 2), 125);
+        assert_eq!(linear(3 * period), 250);
+        assert_eq!(linear(9 * period), 250 + 250 + 250);
+        assert_eq!(linear(12 * period), 1_001);
+
+        // Must strictly increase and end at 100% on total_months
+        for bad in [
+            vec![row(6, 5_000), row(3, 7_500), row(12, BPS_DENOMINATOR)],
+            vec![row(3, 5_000), row(6, 5_000), row(12, BPS_DENOMINATOR)],
+            vec![row(3, 5_000), row(12, 9_999)],
+            vec![row(3, 5_000), row(11, BPS_DENOMINATOR)],
+        ] {
+            assert!(percentage_to_milestones(&beneficiary, &bad, period).is_err());
+        }
+    }
+
+    #[cfg(feature = "debug-claim")]
+    #[test]
+    fn claim_debug_line_formats_the_intermediate_values() {
+        let entry = Beneficiary {
+            allocated_tokens: 1_200,
+            claimed_tokens: 300,
+            start_time: 0,
+            cliff_months: 3,
+            total_months: 12,
+            ..Default::default()
+        };
+        let now = 7 * SECONDS_PER_MONTH + 5;
+        let unlocked = compute_vested(&entry, now, SECONDS_PER_MONTH).unwrap();
+        let line = claim_debug_line(&entry, now, SECONDS_PER_MONTH, unlocked, unlocked - 300).unwrap();
+        assert_eq!(
+            line,
+            "claim debug: months_elapsed=7 months_vested=4 unlocked=533 claimed=300 claimable=233"
+        );
+    }
+
+    #[test]
+    fn schedule_hash_preimage_ignores_order_and_covers_every_entry() {
+        let entry = |allocated_tokens| Beneficiary {
+            key: Pubkey::new_unique(),
+            allocated_tokens,
+            start_time: 1_700_000_000,
+            cliff_months: 3,
+            total_months: 12,
+            assigned: true,
+            ..Default::default()
+        };
+        let (a, b) = (entry(1_000), entry(2_000));
+        let schedule = |beneficiaries| DataAccount { beneficiaries, ..Default::default() };
+
+        let preimage = schedule_hash_preimage(&schedule(vec![a, b]));
+        assert_eq!(preimage, schedule_hash_preimage(&schedule(vec![b, a])));
+        assert_eq!(preimage.len(), 32 + 4 + 2 * (32 + 8 + 8 + 1 + 1));
+
+        // Adding a beneficiary or changing a promised term changes the preimage
+        assert_ne!(preimage, schedule_hash_preimage(&schedule(vec![a, b, entry(1)])));
+        assert_ne!(preimage, schedule_hash_preimage(&schedule(vec![a, Beneficiary { total_months: 24, ..b }])));
+
+        // Unassigned slots are left out
+        let slot = Beneficiary { assigned: false, ..entry(500) };
+        assert_eq!(preimage, schedule_hash_preimage(&schedule(vec![a, slot, b])));
+    }
+
+    #[test]
+    fn validate_beneficiary_rejects_claim_history() {
+        let period = SECONDS_PER_MONTH;
+        let fresh = Beneficiary { allocated_tokens: 1_000, total_months: 12, ..Default::default() };
+        assert!(validate_beneficiary(&fresh, 0, period, period, 1).is_ok());
+
+        // A future last claim would hold every claim in the cooldown
+        let claimed = Beneficiary { last_claim_time: i64::MAX, ..fresh };
+        assert!(validate_beneficiary(&claimed, 0, period, period, 1).is_err());
+        let counted = Beneficiary { claim_count: 3, ..fresh };
+        assert!(validate_beneficiary(&counted, 0, period, period, 1).is_err());
+    }
+
+    #[test]
+    fn settlement_caps_the_original_curve() {
+        let data = DataAccount::default();
+        let period = SECONDS_PER_MONTH;
+        let original = Beneficiary { allocated_tokens: 1_200, total_months: 12, ..Default::default() };
+
+        // Settled at 300 when 600 had vested: the whole settlement is claimable at once
+        let below = Beneficiary { allocated_tokens: 300, settled_from: 1_200, ..original };
+        assert_eq!(data.compute_vested_for(&below, 6 * period).unwrap(), 300);
+
+        // Settled at 900 when 400 had vested: the original curve runs on until the cap
+        let above = Beneficiary { allocated_tokens: 900, settled_from: 1_200, ..original };
+        assert_eq!(data.compute_vested_for(&above, 4 * period).unwrap(), 400);
+        assert_eq!(data.compute_vested_for(&above, 8 * period).unwrap(), 800);
+        assert_eq!(data.compute_vested_for(&above, 10 * period).unwrap(), 900);
+    }
+
+    #[test]
+    fn surplus_shares_skip_exhausted_entries() {
+        let entry = |allocated_tokens, claimed_tokens| Beneficiary {
+            allocated_tokens,
+            claimed_tokens,
+            ..Default::default()
+        };
+        let open = [entry(1_000, 0), entry(3_000, 500)];
+        assert_eq!(surplus_shares(&open, 400).unwrap(), vec![100, 300]);
+
+        // A forfeited entry (claimed = allocated) neither shares nor dilutes the others
+        let forfeited = [entry(1_000, 0), entry(2_000, 2_000), entry(3_000, 500)];
+        assert_eq!(surplus_shares(&forfeited, 400).unwrap(), vec![100, 0, 300]);
+
+        assert!(surplus_shares(&[entry(1_000, 1_000)], 400).is_err());
+    }
+
+    #[test]
+    fn split_amounts_sum_to_the_total() {
+        let treasury = |bps| TreasurySplit { wallet: Pubkey::new_unique(), bps };
+        let split = [treasury(7_000), treasury(3_000)];
+        assert_eq!(split_amounts(1_000, &split).unwrap(), vec![700, 300]);
+
+        // The last treasury takes the rounding remainder
+        let thirds = [treasury(3_333), treasury(3_333), treasury(3_334)];
+        assert_eq!(split_amounts(100, &thirds).unwrap(), vec![33, 33, 34]);
+        assert_eq!(split_amounts(1, &thirds).unwrap(), vec![0, 0, 1]);
+
+        assert!(split_amounts(1_000, &[treasury(7_000), treasury(2_999)]).is_err());
+        assert!(split_amounts(1_000, &[treasury(10_000), treasury(0)]).is_err());
+        assert!(split_amounts(1_000, &[]).is_err());
+    }
+
+    #[test]
+    fn net_claimable_is_zero_unless_the_escrow_covers_an_eligible_claim() {
+        let eligible = ClaimEligibility::Eligible { amount: 500 };
+        assert_eq!(net_claimable(eligible, 500), NetClaimable { amount: 500, status: eligible });
+        assert_eq!(
+            net_claimable(eligible, 499),
+            NetClaimable { amount: 0, status: ClaimEligibility::EscrowUnderfunded { escrow_balance: 499 } }
+        );
+
+        let blocked = ClaimEligibility::BelowMinClaim { claimable: 100 };
+        assert_eq!(net_claimable(blocked, 1_000), NetClaimable { amount: 0, status: blocked });
+    }
+
+    #[test]
+    fn claim_eligibility_reports_the_first_failing_guard() {
+        let period = SECONDS_PER_MONTH;
+        let key = Pubkey::new_unique();
+        let entry = Beneficiary {
+            key,
+            allocated_tokens: 1_200,
+            start_time: 0,
+            cliff_months: 3,
+            total_months: 12,
+            assigned: true,
+            ..Default::default()
+        };
+        let mut data = DataAccount {
+            beneficiaries: vec![entry],
+            activated: true,
+            claim_ceiling_time: i64::MAX,
+            ..Default::default()
+        };
+        let check = |data: &DataAccount, now| claim_eligibility(data, key, now, now).unwrap();
+
+        assert_eq!(claim_eligibility(&data, Pubkey::new_unique(), 0, 0).unwrap(), ClaimEligibility::NotAMember);
+        assert_eq!(check(&data, period), ClaimEligibility::CliffNotReached { remaining: 2 * period });
+        assert_eq!(check(&data, 3 * period), ClaimEligibility::NothingVestedYet);
+        assert_eq!(check(&data, 4 * period), ClaimEligibility::Eligible { amount: 133 });
+
+        data.max_per_claim = 100;
+        assert_eq!(check(&data, 4 * period), ClaimEligibility::Eligible { amount: 100 });
+        data.min_claim_amount = 500;
+        assert_eq!(check(&data, 4 * period), ClaimEligibility::BelowMinClaim { claimable: 133 });
+        data.min_claim_amount = 0;
+
+        data.blackout_windows.push(BlackoutWindow { start: 4 * period, end: 4 * period + 60 });
+        assert_eq!(check(&data, 4 * period), ClaimEligibility::BlackoutActive { seconds_remaining: 60 });
+        data.blackout_windows.clear();
+
+        data.beneficiaries[0].last_claim_time = 4 * period - 10;
+        data.claim_cooldown_seconds = 30;
+        assert_eq!(check(&data, 4 * period), ClaimEligibility::CooldownActive { seconds_remaining: 20 });
+
+        data.paused = true;
+        assert_eq!(check(&data, 4 * period), ClaimEligibility::Paused);
+        data.drained = true;
+        assert_eq!(check(&data, 4 * period), ClaimEligibility::Drained);
+        data.activated = false;
+        assert_eq!(check(&data, 4 * period), ClaimEligibility::NotActivated);
+
+        let data = DataAccount {
+            beneficiaries: vec![Beneficiary { claimed_tokens: 1_200, ..entry }],
+            activated: true,
+            ..Default::default()
+        };
+        assert_eq!(check(&data, 12 * period), ClaimEligibility::FullyClaimed);
+    }
+
+    #[test]
+    fn recoverable_unclaimed_excludes_unvested_tokens() {
+        let period = SECONDS_PER_MONTH;
+        let grace_period = GRACE_PERIOD_MONTHS * period;
+        let beneficiary = Beneficiary {
+            allocated_tokens: 1_200,
+            claimed_tokens: 300,
+            total_months: 12,
+            ..Default::default()
+        };
+        let withdrawable_at = earliest_withdraw_time(&beneficiary, period, grace_period).unwrap();
+
+        // Vested-but-unclaimed tokens are not recoverable before the grace period ends
+        assert_eq!(recoverable_unclaimed(&beneficiary, 6 * period, period, grace_period).unwrap(), 0);
+        assert_eq!(recoverable_unclaimed(&beneficiary, withdrawable_at, period, grace_period).unwrap(), 0);
+        assert_eq!(recoverable_unclaimed(&beneficiary, withdrawable_at + 1, period, grace_period).unwrap(), 900);
+
+        let milestones = Beneficiary { curve: VestingCurve::Milestones, ..beneficiary };
+        assert_eq!(recoverable_unclaimed(&milestones, withdrawable_at + 1, period, grace_period).unwrap(), 900);
+
+        // A start time at the edge of i64 fails cleanly instead of overflowing
+        let far = Beneficiary { start_time: i64::MAX - period, ..beneficiary };
+        assert!(earliest_withdraw_time(&far, period, grace_period).is_err());
+        assert!(recoverable_unclaimed(&far, i64::MAX, period, grace_period).is_err());
+
+        // Claims above the allocation are surfaced, not recovered as 0
+        let corrupted = Beneficiary { claimed_tokens: 1_201, ..beneficiary };
+        assert!(recoverable_unclaimed(&corrupted, withdrawable_at, period, grace_period).is_ok());
+        assert!(recoverable_unclaimed(&corrupted, withdrawable_at + 1, period, grace_period).is_err());
+    }
+}
+
+// ================================================================================================
+// ACCOUNT STRUCTURES
+// ================================================================================================
+
+/// Account validation for initialize instruction
+/// - data_account: Stores vesting state.
+/// - escrow_wallet: Holds tokens for vesting.
+/// - wallet_to_withdraw_from: Funder's wallet to fund escrow (the admin's without a `funder`).
+/// - sender: The admin.
+/// - funder: Optional treasury signer funding the escrow instead of the admin.
+/// - token_mint: The SPL token mint.
+/// - audit_log: Audit log PDA, created when `options.audit_log` is set.
+/// - admin_registry: Sender's `AdminRegistry` (optional).
+/// - clock_source: `ClockSource` to use instead of the Clock sysvar (optional).
+/// - system_program, token_program: System and token programs.
+#[derive(Accounts)]
+#[instruction(beneficiaries: Vec<Beneficiary>, amount: u64, decimals: u8, options: ScheduleOptions)]
+pub struct Initialize<'info> {
+    #[account(init_if_needed,
+        payer = sender,
+        space = calculate_vesting_space!(beneficiaries.len()),
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(init_if_needed, 
+        payer = sender, 
+        seeds = [b"escrow_wallet".as_ref(), token_mint.key().as_ref()],
+        bump,
+        token::mint=token_mint,
+        token::authority=data_account,
+    )]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint=wallet_to_withdraw_from.owner == funder.as_ref().map_or(sender.key(), |f| f.key()) @VestingError::InvalidSender,
+        constraint=wallet_to_withdraw_from.mint == token_mint.key()
+    )]
+    pub wallet_to_withdraw_from: Account<'info, TokenAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub funder: Option<Signer<'info>>,
+
+    /// Required exactly when `options.audit_log` is set
+    #[account(
+        init,
+        payer = sender,
+        space = AUDIT_LOG_SPACE,
+        seeds = [b"audit_log", data_account.key().as_ref()],
+        bump
+    )]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+
+    /// Counts the schedule against the sender's schedule limit when passed
+    #[account(mut, seeds = [b"admin_registry", sender.key().as_ref()], bump)]
+    pub admin_registry: Option<Account<'info, AdminRegistry>>,
+
+    /// Makes the schedule read its time from this clock when passed
+    pub clock_source: Option<Account<'info, ClockSource>>,
+
+    pub system_program: Program<'info, System>,
+
+    pub token_program: Program<'info, Token>
+}
+
+/// Account validation for initialize_multi instruction; the per-asset accounts are passed in
+/// `remaining_accounts` (see `initialize_multi`).
+/// - sender: The admin, funding every escrow and paying rent (must sign).
+/// - system_program, token_program: System and token programs.
+#[derive(Accounts)]
+pub struct InitializeMulti<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for initialize_empty instruction
+/// - data_account: Stores vesting state, sized for `total_beneficiaries`.
+/// - escrow_wallet: Holds tokens for vesting.
+/// - wallet_to_withdraw_from: Admin's wallet to fund escrow.
+/// - sender: The admin.
+#[derive(Accounts)]
+#[instruction(total_beneficiaries: u32)]
+pub struct InitializeEmpty<'info> {
+    #[account(init,
+        payer = sender,
+        space = calculate_vesting_space!(total_beneficiaries as usize),
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(init,
+        payer = sender,
+        seeds = [b"escrow_wallet".as_ref(), token_mint.key().as_ref()],
+        bump,
+        token::mint=token_mint,
+        token::authority=data_account,
+    )]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint=wallet_to_withdraw_from.owner == sender.key() @VestingError::InvalidSender,
+        constraint=wallet_to_withdraw_from.mint == token_mint.key()
+    )]
+    pub wallet_to_withdraw_from: Account<'info, TokenAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    pub token_program: Program<'info, Token>
+}
+
+/// Account validation for create_escrow instruction
+/// - data_account: Future data account PDA, used only as the escrow authority.
+/// - escrow_wallet: Escrow token account to create (PDA).
+/// - payer: Pays rent for the escrow.
+#[derive(Accounts)]
+pub struct CreateEscrow<'info> {
+    /// CHECK: Address-only; the data account is created later by `initialize_prefunded`
+    #[account(
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump
+    )]
+    pub data_account: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"escrow_wallet", token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = data_account,
+    )]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for initialize_prefunded instruction
+/// - data_account: Stores vesting state.
+/// - escrow_wallet: Escrow created by `create_escrow` and already funded.
+/// - sender: The admin.
+/// - token_mint: The SPL token mint.
+/// - audit_log: Audit log PDA, created when `options.audit_log` is set.
+#[derive(Accounts)]
+#[instruction(beneficiaries: Vec<Beneficiary>, decimals: u8, options: ScheduleOptions)]
+pub struct InitializePrefunded<'info> {
+    #[account(init_if_needed,
+        payer = sender,
+        space = calculate_vesting_space!(beneficiaries.len()),
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        seeds = [b"escrow_wallet", token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = data_account,
+    )]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    /// Required exactly when `options.audit_log` is set
+    #[account(
+        init,
+        payer = sender,
+        space = AUDIT_LOG_SPACE,
+        seeds = [b"audit_log", data_account.key().as_ref()],
+        bump
+    )]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for migrate_escrow instruction
+/// - data_account: storing vesting configuration (PDA)
+/// - old_escrow: Current escrow, closed after the move
+/// - new_escrow: Fresh escrow PDA for the next nonce
+/// - admin: Current admin, pays rent for the new escrow and receives the old one's
+#[derive(Accounts)]
+#[instruction(old_escrow_bump: u8, data_bump: u8)]
+pub struct MigrateEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump = data_bump,
+        constraint = data_account.authority == admin.key() @VestingError::UnauthorizedAdmin,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        address = data_account.escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub old_escrow: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [
+            b"escrow_wallet",
+            token_mint.key().as_ref(),
+            data_account.escrow_nonce.saturating_add(1).to_le_bytes().as_ref()
+        ],
+        bump,
+        token::mint = token_mint,
+        token::authority = data_account,
+    )]
+    pub new_escrow: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for migrate_v1_to_v2 instruction
+/// - data_account: Data account PDA still in the v1 layout (parsed in the handler)
+/// - admin: Current admin, pays for the extra space
+/// - token_mint: Token mint for the vesting program
+#[derive(Accounts)]
+#[instruction(data_bump: u8)]
+pub struct MigrateDataAccount<'info> {
+    /// CHECK: Seeds and owner are checked here; the v1 layout is parsed in `migrate_v1_to_v2`
+    #[account(
+        mut,
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump = data_bump,
+        owner = crate::ID,
+    )]
+    pub data_account: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for initialize instruction
+/// - data_account: storing vesting configuration (PDA)
+/// - escrow_wallet: holding vested tokens (PDA)
+/// - sender: Beneficiary claiming tokens
+/// - token_mint: Token mint for the vesting program
+/// - wallet_to_deposit_to: Beneficiary's ATA (created in the handler when missing, if `associated_token_program`
+///   is passed and the schedule allows it), or the custodian vault for `claim_to_vault`
+/// - memo_program: SPL Memo program (only when `claim` is given a memo)
+/// - audit_log: Schedule's audit log PDA (only for schedules with `audit_log`)
+/// - forward_to: Destination token account (only for `claim_and_forward`)
+#[derive(Accounts)]
+#[instruction(data_bump: u8, wallet_bump: u8)]
+pub struct Claim<'info> {
+    #[account(
+        mut, 
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump= data_bump
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        address = data_account.escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    /// CHECK: Must be the sender's ATA for `token_mint`, or a vault verified by `claim_to_vault`;
+    /// verified and created if missing in `claim`
+    #[account(mut)]
+    pub wallet_to_deposit_to: UncheckedAccount<'info>,
+
+    /// Required only for beneficiaries converted with `convert_to_milestones`
+    #[account(
+        seeds = [b"milestones", data_account.key().as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub milestone_schedule: Option<Account<'info, MilestoneSchedule>>,
+
+    /// Required exactly when the schedule was initialized with `store_receipts`.
+    /// The nonce is the schedule's `event_seq` before this claim.
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + 32 + 32 + 8 + 8 + 8 + 8,
+        seeds = [
+            b"claim_receipt",
+            data_account.key().as_ref(),
+            sender.key().as_ref(),
+            &data_account.event_seq.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub claim_receipt: Option<Account<'info, ClaimReceipt>>,
+
+    /// CHECK: The SPL Memo program; required only when `claim` is given a memo
+    #[account(address = MEMO_PROGRAM_ID @ VestingError::InvalidMemoProgram)]
+    pub memo_program: Option<UncheckedAccount<'info>>,
+
+    /// Required exactly when the schedule was initialized with `audit_log`
+    #[account(mut, seeds = [b"audit_log", data_account.key().as_ref()], bump)]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+
+    /// Required only by `claim_and_forward`; checked against its `forward_to` argument
+    #[account(mut)]
+    pub forward_to: Option<Account<'info, TokenAccount>>,
+
+    /// Required only to create a missing `wallet_to_deposit_to`; omit when the ATA exists
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+
+    pub token_program: Program<'info, Token>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for close_empty_claim_ata
+/// - data_account: Stores vesting state (PDA)
+/// - sender: Beneficiary closing their ATA (receives the rent)
+/// - token_mint: Token mint for the vesting program
+/// - claim_ata: Sender's ATA for `token_mint` (must be empty)
+#[derive(Accounts)]
+pub struct CloseClaimAta<'info> {
+    #[account(
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        address = get_associated_token_address(&sender.key(), &token_mint.key())
+            @ VestingError::InvalidDepositAccount,
+    )]
+    pub claim_ata: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for withdraw instruction
+/// - data_account: storing vesting configuration (PDA)
+/// - escrow_wallet: holding vested tokens (PDA)
+/// - admin_wallet: Admin's token account to receive unclaimed tokens
+/// - co_admin: Co-admin (must sign when set on the schedule)
+/// - audit_log: Schedule's audit log PDA (only for schedules with `audit_log`)
+#[derive(Accounts)]
+#[instruction(data_bump: u8, escrow_bump: u8)]
+pub struct WithdrawUnclaimed<'info> {
+    #[account(
+        mut,
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump = data_bump
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        address = data_account.escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = admin_wallet.owner == admin.key(), 
+        constraint = admin_wallet.mint == token_mint.key(),
+    )]
+    pub admin_wallet: Account<'info, TokenAccount>,
+
+    pub admin: Signer<'info>,
+    /// Must sign when the schedule has a `co_admin`
+    pub co_admin: Option<Signer<'info>>,
+    /// Required exactly when the schedule was initialized with `audit_log`
+    #[account(mut, seeds = [b"audit_log", data_account.key().as_ref()], bump)]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+    pub token_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+
+/// Account validation for withdraw_amount instruction
+/// - data_account: storing vesting configuration (PDA)
+/// - escrow_wallet: holding vested tokens (PDA)
+/// - admin_wallet: Admin's token account to receive the recovered tokens
+/// - co_admin: Co-admin (must sign when set on the schedule)
+/// - audit_log: Schedule's audit log PDA (only for schedules with `audit_log`)
+#[derive(Accounts)]
+#[instruction(beneficiary: Pubkey, amount: u64, data_bump: u8, escrow_bump: u8)]
+pub struct WithdrawAmount<'info> {
+    #[account(
+        mut,
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump = data_bump,
+        constraint = data_account.authority == admin.key() @VestingError::UnauthorizedAdmin,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        address = data_account.escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = admin_wallet.owner == admin.key(),
+        constraint = admin_wallet.mint == token_mint.key(),
+    )]
+    pub admin_wallet: Account<'info, TokenAccount>,
+
+    pub admin: Signer<'info>,
+    /// Must sign when the schedule has a `co_admin`
+    pub co_admin: Option<Signer<'info>>,
+    /// Required exactly when the schedule was initialized with `audit_log`
+    #[account(mut, seeds = [b"audit_log", data_account.key().as_ref()], bump)]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+    pub token_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for withdraw_inactive instruction
+/// - data_account: storing vesting configuration (PDA)
+/// - escrow_wallet: holding vested tokens (PDA)
+/// - admin_wallet: Admin's token account to receive the recovered tokens
+/// - co_admin: Co-admin (must sign when set on the schedule)
+/// - audit_log: Schedule's audit log PDA (only for schedules with `audit_log`)
+#[derive(Accounts)]
+#[instruction(beneficiary: Pubkey, data_bump: u8, escrow_bump: u8)]
+pub struct WithdrawInactive<'info> {
+    #[account(
+        mut,
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump = data_bump,
+        constraint = data_account.authority == admin.key() @VestingError::UnauthorizedAdmin,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        address = data_account.escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = admin_wallet.owner == admin.key(),
+        constraint = admin_wallet.mint == token_mint.key(),
+    )]
+    pub admin_wallet: Account<'info, TokenAccount>,
+
+    pub admin: Signer<'info>,
+    /// Must sign when the schedule has a `co_admin`
+    pub co_admin: Option<Signer<'info>>,
+    /// Required exactly when the schedule was initialized with `audit_log`
+    #[account(mut, seeds = [b"audit_log", data_account.key().as_ref()], bump)]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+    pub token_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for change_admin instruction
+/// - data_account: Stores vesting state (PDA)
+/// - current_admin: Current admin (must sign)
+/// - new_admin: New admin address (must be system-owned so it can sign later)
+/// - audit_log: Schedule's audit log PDA (only for schedules with `audit_log`)
+#[derive(Accounts)]
+#[instruction(data_bump: u8)]
+pub struct ChangeAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump = data_bump,
+        constraint = data_account.authority == current_admin.key() @VestingError::UnauthorizedAdmin,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut)]
+    pub current_admin: Signer<'info>,
+    
+    #[account(
+        mut,
+        constraint = new_admin.key() != current_admin.key() @VestingError::SameAdmin,
+        constraint = new_admin.key() != Pubkey::default()   @VestingError::InvalidAddress,
+        constraint = new_admin.owner == &system_program::ID @VestingError::AdminMustBeSystemAccount
+    )]
+    pub new_admin: UncheckedAccount<'info>,
+
+    /// Required exactly when the schedule was initialized with `audit_log`
+    #[account(mut, seeds = [b"audit_log", data_account.key().as_ref()], bump)]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+
+    pub token_mint: Account<'info, Mint>
+}
+
+/// Account validation for add_beneficiaries instruction
+/// - data_account: Stores vesting state (PDA), reallocated to fit the combined list
+/// - escrow_wallet: Holds vested tokens (PDA)
+/// - wallet_to_withdraw_from: Admin's wallet funding the top-up
+/// - admin: Current admin (must sign, pays for realloc)
+/// - audit_log: Schedule's audit log PDA (only for schedules with `audit_log`)
+#[derive(Accounts)]
+#[instruction(beneficiaries: Vec<Beneficiary>, amount: u64, data_bump: u8)]
+pub struct AddBeneficiaries<'info> {
+    #[account(
+        mut,
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump = data_bump,
+        realloc = calculate_vesting_space!(data_account.beneficiaries.len() + beneficiaries.len()),
+        realloc::payer = admin,
+        realloc::zero = false,
+        constraint = data_account.authority == admin.key() @VestingError::UnauthorizedAdmin,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        address = data_account.escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = wallet_to_withdraw_from.owner == admin.key() @VestingError::InvalidSender,
+        constraint = wallet_to_withdraw_from.mint == token_mint.key()
+    )]
+    pub wallet_to_withdraw_from: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// Required exactly when the schedule was initialized with `audit_log`
+    #[account(mut, seeds = [b"audit_log", data_account.key().as_ref()], bump)]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for convert_to_milestones instruction
+/// - data_account: Stores vesting state (PDA)
+/// - milestone_schedule: Side account holding the beneficiary's milestones (PDA)
+/// - admin: Current admin (must sign, pays rent for the side account)
+#[derive(Accounts)]
+#[instruction(beneficiary: Pubkey, milestones: Vec<Milestone>, data_bump: u8)]
+pub struct ConvertToMilestones<'info> {
+    #[account(
+        mut,
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump = data_bump,
+        constraint = data_account.authority == admin.key() @VestingError::UnauthorizedAdmin,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + 32 + (4 + milestones.len() * (8 + 8)),
+        seeds = [b"milestones", data_account.key().as_ref(), beneficiary.as_ref()],
+        bump
+    )]
+    pub milestone_schedule: Account<'info, MilestoneSchedule>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for set_vesting_mode instruction
+/// - data_account: Stores vesting state (PDA)
+/// - signer: Admin or the beneficiary being switched (must sign)
+#[derive(Accounts)]
+#[instruction(beneficiary: Pubkey, mode: VestingMode, data_bump: u8)]
+pub struct SetVestingMode<'info> {
+    #[account(
+        mut,
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump = data_bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub signer: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+}
+
+/// Account validation for reserve_slots instruction
+/// - data_account: Stores vesting state (PDA), reallocated to fit the reserved slots
+/// - admin: Current admin (must sign, pays for realloc)
+#[derive(Accounts)]
+#[instruction(slots: Vec<SlotReservation>, data_bump: u8)]
+pub struct ReserveSlots<'info> {
+    #[account(
+        mut,
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump = data_bump,
+        realloc = calculate_vesting_space!(data_account.beneficiaries.len() + slots.len()),
+        realloc::payer = admin,
+        realloc::zero = false,
+        constraint = data_account.authority == admin.key() @VestingError::UnauthorizedAdmin,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for forfeit instruction
+/// - data_account: Stores vesting state (PDA)
+/// - escrow_wallet: Holds vested tokens (PDA)
+/// - admin_wallet: Admin's token account receiving the forfeited tokens
+/// - beneficiary: Beneficiary declining their grant (must sign)
+#[derive(Accounts)]
+#[instruction(data_bump: u8, escrow_bump: u8)]
+pub struct Forfeit<'info> {
+    #[account(
+        mut,
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump = data_bump
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(
+        mut,
+        address = data_account.escrow_wallet @ VestingError::InvalidEscrowWallet,
+    )]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = admin_wallet.owner == data_account.authority,
+        constraint = admin_wallet.mint == token_mint.key(),
+    )]
+    pub admin_wallet: Account<'info, TokenAccount>,
+
+    pub beneficiary: Signer<'info>,
+    pub token_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Account validation for admin configuration updates
+/// - data_account: Stores vesting state (PDA)
+/// - admin: Current admin (must sign)
+#[derive(Accounts)]
+#[instruction(data_bump: u8)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump = data_bump,
+        constraint = data_account.authority == admin.key() @VestingError::UnauthorizedAdmin,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub admin: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+}
+
+/// Account validation for instructions the admin can delegate to an `AdminRole`
+/// - data_account: Stores vesting state (PDA)
+/// - operator: Admin or the instruction's role holder (must sign)
+#[derive(Accounts)]
+#[instruction(data_bump: u8)]
+pub struct RoleOperation<'info> {
+    #[account(
+        mut,
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump = data_bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub operator: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+}
+
+/// Account validation for distribute_surplus instruction
+/// - data_account: Stores vesting state (PDA)
+/// - operator: Admin or the fund manager (must sign)
+/// - escrow_wallet: Holds vested tokens (PDA), read for `ScheduleStateChanged`
+#[derive(Accounts)]
+#[instruction(data_bump: u8)]
+pub struct DistributeSurplus<'info> {
+    #[account(
+        mut,
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump = data_bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub operator: Signer<'info>,
+
+    #[account(address = data_account.escrow_wallet @ VestingError::InvalidEscrowWallet)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+}
+
+/// Account validation for set_co_admin instruction
+/// - data_account: Stores vesting state (PDA)
+/// - admin: Current admin (must sign)
+/// - co_admin: Current co-admin (must sign when one is set)
+#[derive(Accounts)]
+#[instruction(data_bump: u8)]
+pub struct SetCoAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump = data_bump,
+        constraint = data_account.authority == admin.key() @VestingError::UnauthorizedAdmin,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub admin: Signer<'info>,
+
+    pub co_admin: Option<Signer<'info>>,
+
+    pub token_mint: Account<'info, Mint>,
+}
+
+/// Account validation for set_schedule_limit instruction
+/// - admin_registry: Admin's registry PDA, created on first use
+/// - admin: Registry owner (must sign, pays for creation)
+#[derive(Accounts)]
+pub struct SetScheduleLimit<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + 32 + 4 + 4,
+        seeds = [b"admin_registry", admin.key().as_ref()],
+        bump
+    )]
+    pub admin_registry: Account<'info, AdminRegistry>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for release_schedule_slot instruction
+/// - data_account: Stores vesting state (PDA)
+/// - admin: Current admin (must sign)
+/// - admin_registry: Registry the schedule was counted against
+#[derive(Accounts)]
+#[instruction(data_bump: u8)]
+pub struct ReleaseScheduleSlot<'info> {
+    #[account(
+        mut,
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump = data_bump,
+        constraint = data_account.authority == admin.key() @VestingError::UnauthorizedAdmin,
+        constraint = data_account.admin_registry == Some(admin_registry.key()) @VestingError::InvalidAdminRegistry,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub admin_registry: Account<'info, AdminRegistry>,
+
+    pub token_mint: Account<'info, Mint>,
+}
+
+/// Account validation for create_clock_source instruction
+/// - clock_source: Clock PDA of the authority, created here
+/// - authority: Clock authority (must sign, pays for creation)
+#[derive(Accounts)]
+pub struct CreateClockSource<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8,
+        seeds = [b"clock_source", authority.key().as_ref()],
+        bump
+    )]
+    pub clock_source: Account<'info, ClockSource>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Account validation for advance_clock_source instruction
+/// - clock_source: Clock to move forward
+/// - authority: Clock authority (must sign)
+#[derive(Accounts)]
+pub struct AdvanceClockSource<'info> {
+    #[account(mut, has_one = authority @VestingError::UnauthorizedAdmin)]
+    pub clock_source: Account<'info, ClockSource>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Account validation for sync_clock instruction
+/// - data_account: Stores vesting state (PDA)
+/// - clock_source: The schedule's `clock_override`
+#[derive(Accounts)]
+pub struct SyncClock<'info> {
+    #[account(
+        mut,
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump,
+        constraint = data_account.clock_override == Some(clock_source.key()) @VestingError::InvalidClockSource,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub clock_source: Account<'info, ClockSource>,
+
+    pub token_mint: Account<'info, Mint>,
+}
+
+/// Account validation for check_collateral instruction
+/// - data_account: Stores vesting state (PDA)
+/// - escrow_wallet: Holds vested tokens (PDA)
+#[derive(Accounts)]
+pub struct CheckCollateral<'info> {
+    #[account(
+        mut,
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump,
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(address = data_account.escrow_wallet @ VestingError::InvalidEscrowWallet)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+}
+
+/// Account validation for read-only schedule queries
+/// - data_account: Stores vesting state (PDA)
+/// - token_mint: Token mint for the vesting program
+#[derive(Accounts)]
+pub struct ReadSchedule<'info> {
+    #[account(
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+}
+
+/// Account validation for read-only queries that also need the escrow
+/// - data_account: Stores vesting state (PDA)
+/// - escrow_wallet: Holds vested tokens (PDA)
+/// - token_mint: Token mint for the vesting program
+#[derive(Accounts)]
+pub struct ReadScheduleWithEscrow<'info> {
+    #[account(
+        seeds = [b"data_account", token_mint.key().as_ref()],
+        bump
+    )]
+    pub data_account: Account<'info, DataAccount>,
+
+    #[account(address = data_account.escrow_wallet @ VestingError::InvalidEscrowWallet)]
+    pub escrow_wallet: Account<'info, TokenAccount>,
+
+    pub token_mint: Account<'info, Mint>,
+}
+
+/// Account validation for get_constants (no accounts needed)
+#[derive(Accounts)]
+pub struct ReadConstants {}
+
+/// Account validation for verify_pdas (deliberately unchecked so mismatches get specific errors)
+/// - data_account: Data account PDA to check
+/// - escrow_wallet: Escrow PDA to check
+/// - token_mint: Token mint the PDAs are derived from
+#[derive(Accounts)]
+pub struct VerifyPdas<'info> {
+    /// CHECK: Compared against the derived data account PDA in `verify_pdas`
+    pub data_account: UncheckedAccount<'info>,
+
+    /// CHECK: Compared against the derived escrow PDA in `verify_pdas`
+    pub escrow_wallet: UncheckedAccount<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+}
+
+// ================================================================================================
+// DATA STRUCTURES
+// ================================================================================================
+
+/// Configuration for a single beneficiary in the vesting schedule
+/// - key: Beneficiary's address.
+/// - allocated_tokens: Total tokens allocated.
+/// - claimed_tokens: Tokens already claimed.
+/// - start_time: Vesting start timestamp.
+/// - cliff_months: Number of cliff months.
+/// - total_months: Total vesting duration in months.
+/// - last_claim_time: Unix timestamp of the last claim (0 = never claimed).
+/// - curve: Release shape over the vesting period (see `VestingCurve`).
+/// - assigned: False for slots reserved by `reserve_slots` until `assign_slot` sets the key.
+///   Always stored as true for beneficiaries added with a known key.
+/// - notify_tag: Opaque tag copied into `TokensClaimed` for off-chain relays (all zero = none).
+/// - cliff_time: Absolute cliff date overriding the `cliff_months` offset (None = use the offset).
+/// - claim_count: Number of successful claims.
+/// - original_index: Insertion order within the schedule, assigned by the program and never
+///   changed, so it identifies the entry even after compaction moves it.
+/// - reference: Opaque external id (e.g. an HR grant id) copied into `TokensClaimed` (all zero = none).
+/// - mode: Per-period or continuous accrual for a linear curve (see `VestingMode`).
+/// - settled_from: Allocation before a `forfeit` settlement capped it (0 = never settled).
+#[derive(Default, Copy, Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct Beneficiary {
+    pub key: Pubkey,
+    pub allocated_tokens: u64, // RAW UNITS
+    pub claimed_tokens: u64,   // RAW UNITS
+    pub start_time: i64, 
+    pub cliff_months: u8,
+    pub total_months: u8,
+    pub last_claim_time: i64,
+    pub curve: VestingCurve,
+    pub assigned: bool,
+    pub notify_tag: [u8; 16],
+    pub cliff_time: Option<i64>,
+    pub claim_count: u32,
+    pub original_index: u32,
+    pub reference: [u8; 24],
+    pub mode: VestingMode,
+    pub settled_from: u64,     // RAW UNITS
+}
+
+/// Allocation reserved by `reserve_slots` for a wallet assigned later
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize)]
+pub struct SlotReservation {
+    pub amount: u64, // RAW UNITS
+    pub cliff_months: u8,
+    pub total_months: u8,
+    pub start_time: i64,
 }
 
-// Macro to calculate the space required for the DataAccount based on the number of beneficiaries.
-macro_rules! calculate_vesting_space {
-    ($beneficiaries_count: expr) => {
-        8 + 8 + 32 + 32 + 32 + 1 + (4 + $beneficiaries_count * (32 + 8 + 8 + 8 + 1 + 1) + 1)
-    };
+/// Release shape of a beneficiary's allocation, with t = months_vested / vesting_months
+#[derive(Default, Copy, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum VestingCurve {
+    /// unlocked = allocated * t
+    #[default]
+    Linear,
+    /// unlocked = allocated * (1 - (1 - t)^2): more early, less later
+    FrontLoaded,
+    /// unlocked = allocated * t^2: less early, more later
+    BackLoaded,
+    /// Set by `convert_to_milestones`: unlocks follow the beneficiary's `MilestoneSchedule`
+    Milestones,
+}
+
+/// Accrual of a `Linear` beneficiary, switched with `set_vesting_mode`. Schedules initialized
+/// with `continuous_linear` accrue continuously whatever the mode.
+#[derive(Default, Copy, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum VestingMode {
+    /// Whole periods after the cliff (`compute_vested`)
+    #[default]
+    Monthly,
+    /// Every second (every slot in slot mode) after the cliff (`compute_vested_continuous`)
+    Continuous,
+}
+
+/// Cause of an `EscrowBalanceChanged` event
+#[derive(Copy, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum EscrowChangeReason {
+    /// Admin or funder deposit (`initialize`, `initialize_empty`, `add_beneficiaries`)
+    Fund,
+    /// Beneficiary claim
+    Claim,
+    /// `withdraw` or `withdraw_amount`
+    Withdraw,
+    EmergencyDrain,
+    /// `force_unlock_dust`
+    DustSweep,
+    Forfeit,
+    /// `migrate_escrow`: the balance moved into a new escrow account
+    Migration,
+}
+
+/// Operation recorded in an `AuditLog`
+#[derive(Copy, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum AuditOp {
+    Initialize,
+    Claim,
+    Withdraw,
+    WithdrawAmount,
+    EmergencyDrain,
+    DustSweep,
+    AddBeneficiaries,
+    ChangeAdmin,
+    WithdrawInactive,
+}
+
+/// One `AuditLog` entry: who did what, for how many raw units, when (unix timestamp)
+#[derive(Copy, Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct AuditEntry {
+    pub op: AuditOp,
+    pub actor: Pubkey,
+    pub amount: u64, // RAW UNITS (0 for `ChangeAdmin`)
+    pub timestamp: i64,
+}
+
+/// Ring buffer of a schedule's most recent operations (PDA: ["audit_log", data_account]),
+/// kept when the schedule was initialized with `audit_log`.
+/// - schedule: Data account the log belongs to.
+/// - total_ops: Operations recorded so far; the next one goes to `total_ops % AUDIT_LOG_CAPACITY`.
+/// - entries: At most `AUDIT_LOG_CAPACITY` entries; once full, the oldest is overwritten.
+#[account]
+pub struct AuditLog {
+    pub schedule: Pubkey,
+    pub total_ops: u64,
+    pub entries: Vec<AuditEntry>,
+}
+
+impl AuditLog {
+    /// Records `entry`, overwriting the oldest one once the buffer is full.
+    pub fn push(&mut self, entry: AuditEntry) -> Result<()> {
+        let slot = (self.total_ops % AUDIT_LOG_CAPACITY as u64) as usize;
+        if self.entries.len() < AUDIT_LOG_CAPACITY {
+            self.entries.push(entry);
+        } else {
+            self.entries[slot] = entry;
+        }
+        self.total_ops = self.total_ops.checked_add(1).ok_or(VestingError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+/// Use of funded-but-unallocated escrow tokens
+#[derive(Default, Copy, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum SurplusPolicy {
+    /// Admin may allocate the surplus to new beneficiaries or slots
+    #[default]
+    AdminSweep,
+    /// `distribute_surplus` shares it among beneficiaries once vesting completes
+    ProRataBeneficiaries,
+    /// Nobody can allocate it
+    Locked,
+}
+
+/// Operational role the admin can delegate with `set_role`
+#[derive(Copy, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum AdminRole {
+    /// Can pause and resume claims (`set_paused`)
+    ClaimManager,
+    /// Can distribute the surplus (`distribute_surplus`)
+    FundManager,
+}
+
+/// A single milestone unlock: `amount` RAW UNITS become claimable at `unlock_time`
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize)]
+pub struct Milestone {
+    pub unlock_time: i64,
+    pub amount: u64, // RAW UNITS
+}
+
+/// One row of an `initialize_percentage_milestones` table: `cumulative_bps` (out of
+/// `BPS_DENOMINATOR`) of the allocation has unlocked `month` periods after `start_time`
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize)]
+pub struct PercentageMilestone {
+    pub month: u8,
+    pub cumulative_bps: u16,
+}
+
+/// How milestone beneficiaries release between unlock times (schedule-wide)
+#[derive(Default, Copy, Clone, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum MilestoneInterpolation {
+    /// Each milestone's amount unlocks in full at its `unlock_time`
+    #[default]
+    Step,
+    /// Each milestone's amount unlocks linearly from the previous unlock time
+    Linear,
+}
+
+/// Side account holding a beneficiary's milestone list (PDA: ["milestones", data_account, beneficiary])
+/// - beneficiary: Beneficiary the milestones belong to.
+/// - milestones: Unlocks in ascending `unlock_time` order, summing to the allocation.
+#[account]
+pub struct MilestoneSchedule {
+    pub beneficiary: Pubkey,         // 32
+    pub milestones: Vec<Milestone>,  // 4 + n * (8 + 8)
+}
+
+/// Immutable record of one claim (PDA: ["claim_receipt", data_account, beneficiary, nonce])
+/// - schedule: Data account the claim was made against.
+/// - beneficiary: Claiming beneficiary.
+/// - amount: Tokens transferred - RAW UNITS.
+/// - timestamp: Unix timestamp of the claim.
+/// - total_claimed: Beneficiary's running claimed total after this claim - RAW UNITS.
+/// - event_seq: Sequence number of the matching `TokensClaimed` event.
+#[account]
+pub struct ClaimReceipt {
+    pub schedule: Pubkey,    // 32
+    pub beneficiary: Pubkey, // 32
+    pub amount: u64,         // 8
+    pub timestamp: i64,      // 8
+    pub total_claimed: u64,  // 8
+    pub event_seq: u64,      // 8
+}
+
+/// Per-admin schedule limit (PDA: ["admin_registry", admin]), opted into by passing it to `initialize`
+/// - admin: Admin the registry belongs to.
+/// - schedule_count: Schedules currently counted (incremented on init, decremented on release).
+/// - max_schedules: Limit on `schedule_count` (see `set_schedule_limit`).
+#[account]
+pub struct AdminRegistry {
+    pub admin: Pubkey,        // 32
+    pub schedule_count: u32,  // 4
+    pub max_schedules: u32,   // 4
+}
+
+/// Controllable time source (PDA: ["clock_source", authority]) a schedule can follow instead of the Clock sysvar
+/// - authority: Signer allowed to advance it.
+/// - unix_timestamp: Current time; only moves forward.
+#[account]
+pub struct ClockSource {
+    pub authority: Pubkey,    // 32
+    pub unix_timestamp: i64,  // 8
+}
+
+impl MilestoneSchedule {
+    /// Sum of the milestones unlocked at `now` - RAW UNITS. With `Linear` interpolation, the
+    /// next milestone also accrues pro-rata from the previous unlock time (`start_time` for the first).
+    pub fn vested_at(
+        &self,
+        start_time: i64,
+        now: i64,
+        interpolation: MilestoneInterpolation,
+    ) -> Result<u64> {
+        let mut vested = 0u64;
+        let mut previous = start_time;
+        for m in self.milestones.iter() {
+            if m.unlock_time <= now {
+                vested = vested.checked_add(m.amount).ok_or(VestingError::MathOverflow)?;
+                previous = m.unlock_time;
+                continue;
+            }
+            if interpolation == MilestoneInterpolation::Linear && now > previous {
+                // previous < now < unlock_time, so the share is below `amount`
+                let share = (m.amount as u128) * ((now - previous) as u128)
+                    / ((m.unlock_time - previous) as u128);
+                vested = vested.checked_add(share as u64).ok_or(VestingError::MathOverflow)?;
+            }
+            break;
+        }
+        Ok(vested)
+    }
+}
+
+/// Original beneficiary layout (v1), only read by `migrate_v1_to_v2`
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize)]
+pub struct BeneficiaryV1 {
+    pub key: Pubkey,
+    pub allocated_tokens: u64, // RAW UNITS
+    pub claimed_tokens: u64,   // RAW UNITS
+    pub start_time: i64,
+    pub cliff_months: u8,
+    pub total_months: u8,
+}
+
+/// Original data account layout (v1), only read by `migrate_v1_to_v2`
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct DataAccountV1 {
+    pub token_amount: u64,
+    pub authority: Pubkey,
+    pub escrow_wallet: Pubkey,
+    pub token_mint: Pubkey,
+    pub beneficiaries: Vec<BeneficiaryV1>,
+    pub decimals: u8,
+}
+
+/// Schedule-wide settings fixed at initialization
+/// - use_slots: Measure vesting periods in slots instead of unix timestamps.
+/// - slots_per_period: Length of one vesting period in slots (slot mode only).
+/// - fixed_rate_numerator / fixed_rate_denominator: Informational fiat rate per raw unit (0 = disabled).
+/// - require_existing_ata: `claim` rejects instead of creating a missing beneficiary ATA.
+/// - no_withdraw: Admin can never withdraw from escrow; funding must match allocations exactly.
+/// - auto_stake_program: Staking program that receives claimed tokens (see `deposit_to_stake_program`).
+/// - emergency_enabled: Allow `emergency_drain` while the schedule is paused.
+/// - store_receipts: Every `claim` creates a `ClaimReceipt` account (paid by the beneficiary).
+/// - surplus_policy: What happens to funded-but-unallocated tokens (see `SurplusPolicy`).
+/// - compact_on_complete: A claim that exhausts a beneficiary removes their entry (ignored with `store_receipts`).
+/// - min_total_months: Reject beneficiaries vesting over fewer months (never below `MIN_TOTAL_MONTHS`).
+/// - unlock_first_period_at_cliff: Release the first period's share at cliff end instead of one period later.
+/// - continuous_linear: Linear beneficiaries vest per second (per slot) in Q64.64 instead of per period.
+/// - audit_log: Keep an on-chain `AuditLog` of recent operations (its PDA must be created at init).
+/// - require_no_freeze_authority: Reject mints with a freeze authority, which could freeze the escrow.
+/// - inactivity_window_seconds: Let admin recover vested tokens left unclaimed this long (0 = disabled, timestamp schedules only).
+#[derive(Default, Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ScheduleOptions {
+    pub use_slots: bool,
+    pub slots_per_period: u64,
+    pub fixed_rate_numerator: u64,
+    pub fixed_rate_denominator: u64,
+    pub require_existing_ata: bool,
+    pub no_withdraw: bool,
+    pub auto_stake_program: Option<Pubkey>,
+    pub emergency_enabled: bool,
+    pub store_receipts: bool,
+    pub surplus_policy: SurplusPolicy,
+    pub compact_on_complete: bool,
+    pub min_total_months: u8,
+    pub unlock_first_period_at_cliff: bool,
+    pub continuous_linear: bool,
+    pub audit_log: bool,
+    pub require_no_freeze_authority: bool,
+    pub inactivity_window_seconds: u64,
+}
+
+/// Share of the tokens recovered by `withdraw` sent to one treasury
+/// - wallet: Owner of the treasury's token account.
+/// - bps: Share in basis points; the shares of a split sum to 10_000.
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize)]
+pub struct TreasurySplit {
+    pub wallet: Pubkey,
+    pub bps: u16,
+}
+
+/// One schedule of `initialize_multi`
+/// - mint: Token mint of the schedule.
+/// - amount: Tokens to move into its escrow - RAW UNITS.
+/// - decimals: Token decimals for reference.
+/// - allocations: `allocated_tokens` of each shared beneficiary, in order - RAW UNITS.
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct AssetConfig {
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub decimals: u8,
+    pub allocations: Vec<u64>,
+}
+
+/// Main account storing all vesting program state.
+/// - token_amount: Total tokens for vesting - RAW UNITS.
+/// - authority: Admin address.
+/// - escrow_wallet: Escrow wallet address.
+/// - token_mint: SPL token mint.
+/// - beneficiaries: List of all beneficiaries.
+/// - decimals: Token decimals.
+/// - event_seq: Sequence number of the last emitted event.
+/// - max_per_claim: Maximum tokens per claim - RAW UNITS (0 = unlimited).
+/// - use_slots: Vesting periods are measured in slots (start_time is a slot).
+/// - slots_per_period: Length of one vesting period in slots.
+/// - fixed_rate_numerator / fixed_rate_denominator: Fixed fiat rate used only for event reporting.
+/// - min_claim_amount: Minimum tokens per claim - RAW UNITS (0 = disabled).
+/// - require_existing_ata: Beneficiaries must create their ATA before claiming.
+/// - no_withdraw: Admin withdrawals are permanently disabled.
+/// - claim_cooldown_seconds: Minimum seconds between claims of a beneficiary (0 = disabled).
+/// - version: Layout version (`DATA_ACCOUNT_VERSION`).
+/// - auto_stake_program: Staking program claims are deposited into (None = plain transfer).
+/// - escrow_nonce: Number of escrow rotations (0 = original escrow seeds).
+/// - escrow_migration_requested_at: Unix timestamp of the pending escrow rotation request (0 = none).
+/// - expected_beneficiaries: Beneficiary count `append_beneficiaries` fills up to before activation.
+/// - activated: Claims are allowed (false only while a two-phase init is being populated).
+/// - paused: Claims are rejected until admin resumes them.
+/// - emergency_enabled: `emergency_drain` is available (fixed at init).
+/// - drained: Escrow was emptied by `emergency_drain`; claims and withdraws are rejected.
+/// - store_receipts: Claims create an on-chain `ClaimReceipt`.
+/// - surplus_policy: Use of funded-but-unallocated tokens (fixed at init).
+/// - claim_ceiling_time: `claim` only pays out what vested up to this time (i64::MAX = no cap).
+/// - beneficiary_count: Expected length of `beneficiaries` (see `check_beneficiary_count`).
+/// - active_beneficiaries: Entries with unclaimed tokens left (0 = schedule can be closed).
+/// - compact_on_complete: Fully claimed entries are swap-removed from `beneficiaries`.
+/// - min_total_months: Schedule's floor on `total_months` (see `min_vesting_months`).
+/// - co_admin: Second signer required for withdrawals (None = admin alone).
+/// - unlock_first_period_at_cliff: The first vesting step unlocks when the cliff ends (see `compute_vested_with`).
+/// - blackout_windows: Sorted, non-overlapping windows during which `claim` is rejected.
+/// - continuous_linear: Linear beneficiaries accrue continuously (see `compute_vested_continuous`).
+/// - next_original_index: `original_index` given to the next inserted entry.
+/// - audit_log: Operations are recorded in the schedule's `AuditLog` (fixed at init).
+/// - claim_manager: May pause and resume claims besides the admin (None = admin only).
+/// - fund_manager: May distribute the surplus besides the admin (None = admin only).
+/// - require_no_freeze_authority: The mint was checked to have no freeze authority (fixed at init).
+/// - milestone_interpolation: How milestone beneficiaries release between unlock times (fixed at init).
+/// - admin_registry: `AdminRegistry` the schedule is counted against (None = not counted or released).
+/// - inactivity_window_seconds: Inactivity after which `withdraw_inactive` may recover vested tokens (0 = disabled, fixed at init).
+/// - clock_override: `ClockSource` the schedule reads its time from instead of the Clock sysvar (fixed at init).
+/// - override_time: Last time synced from `clock_override` with `sync_clock`.
+/// - min_collateral_bps: Escrow-to-obligations ratio `check_collateral` alarms below (see `set_min_collateral_bps`).
+#[account]
+#[derive(Default)]
+pub struct DataAccount {
+    // Space in bytes: 8 + 8 + 32 + 32 + 32 + 1 + 8 + 8 + 1 + 8 + 8 + 8 + 8 + 1 + 1 + 8 + 1 + 33 + 1 + 8 + 4 + 1 + 1 + 1 + 1 + 1 + 1 + 8 + 4 + 4 + 1 + 1 + 33 + 1 + (4 + 8 * 16) + 1 + 4 + 1 + 33 + 33 + 1 + 1 + 33 + 8 + 33 + 8 + 2 + (4 + (50 * (32 + 8 + 8 + 36)))
+    pub token_amount: u64,     // 8 
+    pub authority: Pubkey,   // 32
+    pub escrow_wallet: Pubkey, // 32
+    pub token_mint: Pubkey,    // 32
+    pub beneficiaries: Vec<Beneficiary>, // (4 + (n * (32 + 8 + 8 + 8 + 1 + 1 + 8)))
+    pub decimals: u8,          // 1
+    pub event_seq: u64,        // 8
+    pub max_per_claim: u64,    // 8
+    pub use_slots: bool,       // 1
+    pub slots_per_period: u64, // 8
+    pub fixed_rate_numerator: u64,   // 8
+    pub fixed_rate_denominator: u64, // 8
+    pub min_claim_amount: u64,       // 8
+    pub require_existing_ata: bool,  // 1
+    pub no_withdraw: bool,           // 1
+    pub claim_cooldown_seconds: u64, // 8
+    pub version: u8,                 // 1
+    pub auto_stake_program: Option<Pubkey>, // 33
+    pub escrow_nonce: u8,                   // 1
+    pub escrow_migration_requested_at: i64, // 8
+    pub expected_beneficiaries: u32,        // 4
+    pub activated: bool,                    // 1
+    pub paused: bool,                       // 1
+    pub emergency_enabled: bool,            // 1
+    pub drained: bool,                      // 1
+    pub store_receipts: bool,               // 1
+    pub surplus_policy: SurplusPolicy,      // 1
+    pub claim_ceiling_time: i64,            // 8
+    pub beneficiary_count: u32,             // 4
+    pub active_beneficiaries: u32,          // 4
+    pub compact_on_complete: bool,          // 1
+    pub min_total_months: u8,               // 1
+    pub co_admin: Option<Pubkey>,           // 33
+    pub unlock_first_period_at_cliff: bool, // 1
+    pub blackout_windows: Vec<BlackoutWindow>, // (4 + MAX_BLACKOUT_WINDOWS * 16)
+    pub continuous_linear: bool,            // 1
+    pub next_original_index: u32,           // 4
+    pub audit_log: bool,                    // 1
+    pub claim_manager: Option<Pubkey>,      // 33
+    pub fund_manager: Option<Pubkey>,       // 33
+    pub require_no_freeze_authority: bool,  // 1
+    pub milestone_interpolation: MilestoneInterpolation, // 1
+    pub admin_registry: Option<Pubkey>,     // 33
+    pub inactivity_window_seconds: u64,     // 8
+    pub clock_override: Option<Pubkey>,     // 33
+    pub override_time: i64,                 // 8
+    pub min_collateral_bps: u16,            // 2
+}
+
+impl DataAccount {
+    /// Validates and stores a new schedule. Shared by `initialize` and `initialize_prefunded`.
+    /// `mint_freezable` is whether `token_mint` has a freeze authority.
+    /// Returns the total allocation in RAW UNITS.
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure(
+        &mut self,
+        authority: Pubkey,
+        escrow_wallet: Pubkey,
+        token_mint: Pubkey,
+        mint_freezable: bool,
+        beneficiaries: Vec<Beneficiary>,
+        amount: u64,
+        decimals: u8,
+        options: &ScheduleOptions,
+    ) -> Result<u64> {
+        self.authority = authority;
+        self.version = DATA_ACCOUNT_VERSION;
+        self.claim_ceiling_time = i64::MAX;
+
+        if options.use_slots {
+            require!(options.slots_per_period > 0, VestingError::InvalidSlotsPerPeriod);
+        }
+        self.use_slots = options.use_slots;
+        self.slots_per_period = options.slots_per_period;
+
+        if options.fixed_rate_numerator > 0 {
+            require!(options.fixed_rate_denominator > 0, VestingError::InvalidFixedRate);
+        }
+        self.fixed_rate_numerator = options.fixed_rate_numerator;
+        self.fixed_rate_denominator = options.fixed_rate_denominator;
+        self.require_existing_ata = options.require_existing_ata;
+        self.no_withdraw = options.no_withdraw;
+        self.auto_stake_program = options.auto_stake_program;
+        self.emergency_enabled = options.emergency_enabled;
+        self.store_receipts = options.store_receipts;
+        self.surplus_policy = options.surplus_policy;
+        self.compact_on_complete = options.compact_on_complete;
+        self.unlock_first_period_at_cliff = options.unlock_first_period_at_cliff;
+        self.continuous_linear = options.continuous_linear;
+        self.audit_log = options.audit_log;
+
+        if options.require_no_freeze_authority {
+            require!(!mint_freezable, VestingError::MintHasFreezeAuthority);
+        }
+        self.require_no_freeze_authority = options.require_no_freeze_authority;
+
+        // last_claim_time is a unix timestamp, so inactivity is only measured on timestamp schedules
+        if options.inactivity_window_seconds > 0 {
+            require!(
+                !options.use_slots && options.inactivity_window_seconds < GRACE_PERIOD as u64,
+                VestingError::InvalidInactivityWindow
+            );
+        }
+        self.inactivity_window_seconds = options.inactivity_window_seconds;
+
+        require!(options.min_total_months <= MAX_TOTAL_MONTHS, VestingError::TotalMonthsTooLong);
+        self.min_total_months = options.min_total_months;
+        self.min_collateral_bps = BPS_DENOMINATOR;
+
+        let now = self.current_time()?;
+        require!(now >= 0, VestingError::InvalidClock);
+        let max_start_delay = self.max_start_delay();
+        let period = self.period_length();
+        let min_months = self.min_vesting_months();
+
+        require!(!beneficiaries.is_empty(), VestingError::NoBeneficiaries);
+        require!(beneficiaries.len() <= MAX_BENEFICIARIES, VestingError::TooManyBeneficiaries);
+        require!(amount > 0, VestingError::InvalidAmount);
+        require!(decimals <= MAX_DECIMALS, VestingError::InvalidDecimals);
+
+        let mut seen = std::collections::HashSet::new();
+
+        let mut beneficiaries = beneficiaries;
+        for b in beneficiaries.iter_mut() {
+            validate_beneficiary(b, now, max_start_delay, period, min_months)?;
+            b.assigned = true;
+
+            // Prevent duplicate beneficiaries
+            require!(seen.insert(b.key), VestingError::DuplicateBeneficiary);
+        }
+
+        // Validate total allocation against available amount (all in raw units)
+        let total_allocated = total_allocation(&beneficiaries)?;
+        require!(total_allocated <= amount, VestingError::OverAllocation);
+
+        // Surplus could never be recovered from a no-withdraw schedule
+        if self.no_withdraw {
+            require!(total_allocated == amount, VestingError::UnallocatedSurplus);
+        }
+
+        // Store vesting configuration
+        self.assign_original_indices(&mut beneficiaries)?;
+        self.beneficiaries = beneficiaries;
+        self.token_amount = amount;
+        self.decimals = decimals;
+        self.escrow_wallet = escrow_wallet;
+        self.token_mint = token_mint;
+        self.expected_beneficiaries = self.beneficiaries.len() as u32;
+        self.sync_beneficiary_count();
+        self.active_beneficiaries = active_count(&self.beneficiaries);
+        self.activated = true;
+
+        Ok(total_allocated)
+    }
+
+    /// Guards against `beneficiaries` and `beneficiary_count` drifting apart.
+    /// Called at the start of every instruction that mutates the beneficiary list.
+    pub fn check_beneficiary_count(&self) -> Result<()> {
+        require!(
+            self.beneficiaries.len() as u32 == self.beneficiary_count,
+            VestingError::CountMismatch
+        );
+        Ok(())
+    }
+
+    /// `compute_vested` under this schedule's period and `unlock_first_period_at_cliff`, or
+    /// `compute_vested_continuous` for linear beneficiaries of `continuous_linear` schedules
+    /// and those switched to `VestingMode::Continuous`.
+    /// A `forfeit` settlement caps the original curve at the settled allocation.
+    pub fn compute_vested_for(&self, beneficiary: &Beneficiary, now: i64) -> Result<u64> {
+        if beneficiary.settled_from > 0 {
+            let original = Beneficiary {
+                allocated_tokens: beneficiary.settled_from,
+                settled_from: 0,
+                ..*beneficiary
+            };
+            let vested = self.compute_vested_for(&original, now)?;
+            return Ok(std::cmp::min(vested, beneficiary.allocated_tokens));
+        }
+        let continuous = self.continuous_linear || beneficiary.mode == VestingMode::Continuous;
+        if continuous && beneficiary.curve == VestingCurve::Linear {
+            return compute_vested_continuous(beneficiary, now, self.period_length());
+        }
+        compute_vested_with(beneficiary, now, self.period_length(), self.unlock_first_period_at_cliff)
+    }
+
+    /// Whether unix timestamp `now` falls inside a claim blackout window.
+    pub fn in_blackout(&self, now: i64) -> bool {
+        self.blackout_windows.iter().any(|w| w.start <= now && now < w.end)
+    }
+
+    /// Requires `signer` to be the `co_admin`, when one is set. Guards every instruction
+    /// that moves tokens out of escrow to the admin, and `set_co_admin` itself.
+    pub fn check_co_admin(&self, signer: Option<Pubkey>) -> Result<()> {
+        if let Some(co_admin) = self.co_admin {
+            require!(signer == Some(co_admin), VestingError::CoAdminSignatureRequired);
+        }
+        Ok(())
+    }
+
+    /// Requires `signer` to be the admin or the current holder of `role`.
+    pub fn check_role(&self, role: AdminRole, signer: Pubkey) -> Result<()> {
+        let holder = match role {
+            AdminRole::ClaimManager => self.claim_manager,
+            AdminRole::FundManager => self.fund_manager,
+        };
+        require!(
+            signer == self.authority || holder == Some(signer),
+            VestingError::UnauthorizedAdmin
+        );
+        Ok(())
+    }
+
+    /// Records the current length of `beneficiaries` after entries were added.
+    pub fn sync_beneficiary_count(&mut self) {
+        self.beneficiary_count = self.beneficiaries.len() as u32;
+    }
+
+    /// Counts not-yet-exhausted entries about to be appended to `beneficiaries`.
+    /// Gives each new entry the next `original_index`, in order. Inputs' values are ignored.
+    pub fn assign_original_indices(&mut self, added: &mut [Beneficiary]) -> Result<()> {
+        for b in added.iter_mut() {
+            b.original_index = self.next_original_index;
+            self.next_original_index = self
+                .next_original_index
+                .checked_add(1)
+                .ok_or(VestingError::MathOverflow)?;
+        }
+        Ok(())
+    }
+
+    pub fn add_active_beneficiaries(&mut self, added: &[Beneficiary]) -> Result<()> {
+        self.active_beneficiaries = self
+            .active_beneficiaries
+            .checked_add(active_count(added))
+            .ok_or(VestingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Sum of `claimed_tokens` over all beneficiaries - RAW UNITS.
+    pub fn total_claimed(&self) -> Result<u64> {
+        let mut total_claimed = 0u64;
+        for b in self.beneficiaries.iter() {
+            total_claimed = total_claimed.checked_add(b.claimed_tokens).ok_or(VestingError::MathOverflow)?;
+        }
+        Ok(total_claimed)
+    }
+
+    /// Increments and returns the per-schedule event sequence number.
+    /// Gives indexers a total order over all events of a schedule.
+    pub fn next_event_seq(&mut self) -> Result<u64> {
+        self.event_seq = self.event_seq.checked_add(1).ok_or(VestingError::MathOverflow)?;
+        Ok(self.event_seq)
+    }
+
+    /// Current time in the schedule's unit: slot in slot mode, unix timestamp otherwise.
+    /// Schedules with a `clock_override` use the time last synced from it instead.
+    pub fn current_time(&self) -> Result<i64> {
+        if self.clock_override.is_some() {
+            return Ok(self.override_time);
+        }
+        let clock = Clock::get()?;
+        if self.use_slots {
+            Ok(i64::try_from(clock.slot).map_err(|_| VestingError::MathOverflow)?)
+        } else {
+            Ok(clock.unix_timestamp)
+        }
+    }
+
+    /// Length of one vesting period in the schedule's unit.
+    pub fn period_length(&self) -> i64 {
+        if self.use_slots {
+            self.slots_per_period as i64
+        } else {
+            SECONDS_PER_MONTH
+        }
+    }
+
+    /// Informational fiat value of `amount` at the fixed rate set at initialization.
+    /// Returns 0 when no rate is configured. Never used for token math.
+    pub fn usd_value(&self, amount: u64) -> Result<u64> {
+        if self.fixed_rate_denominator == 0 {
+            return Ok(0);
+        }
+        let value = (amount as u128)
+            .checked_mul(self.fixed_rate_numerator as u128)
+            .ok_or(VestingError::MathOverflow)?
+            / self.fixed_rate_denominator as u128;
+        Ok(u64::try_from(value).map_err(|_| VestingError::MathOverflow)?)
+    }
+
+    /// `amount` in whole tokens (RAW UNITS / 10^decimals, rounded down).
+    /// Informational only; 0 when `decimals` is too large for a u64 unit.
+    pub fn whole_tokens(&self, amount: u64) -> u64 {
+        10u64
+            .checked_pow(self.decimals as u32)
+            .map_or(0, |unit| amount / unit)
+    }
+
+    /// `MAX_START_DELAY` converted to the schedule's unit.
+    pub fn max_start_delay(&self) -> i64 {
+        if self.use_slots {
+            ((MAX_START_DELAY as i128 * self.slots_per_period as i128) / SECONDS_PER_MONTH as i128) as i64
+        } else {
+            MAX_START_DELAY
+        }
+    }
+
+    /// Minimum `total_months` for new beneficiaries: the schedule's floor, but never below `MIN_TOTAL_MONTHS`.
+    pub fn min_vesting_months(&self) -> u8 {
+        std::cmp::max(MIN_TOTAL_MONTHS, self.min_total_months)
+    }
+
+    /// `min_collateral_bps`, or `BPS_DENOMINATOR` for schedules created before it was stored.
+    pub fn collateral_threshold_bps(&self) -> u16 {
+        std::cmp::max(BPS_DENOMINATOR, self.min_collateral_bps)
+    }
+}
+
+/// Result of `get_claim_eligibility`: `Eligible` or the first reason `claim` would fail
+#[derive(Clone, Copy, PartialEq, Eq, Debug, AnchorSerialize, AnchorDeserialize)]
+pub enum ClaimEligibility {
+    /// `claim` would transfer `amount` RAW UNITS (after `max_per_claim`)
+    Eligible { amount: u64 },
+    /// No assigned entry for the address (never added, or compacted away)
+    NotAMember,
+    /// Two-phase initialization has not been completed
+    NotActivated,
+    /// The escrow was emptied by `emergency_drain`
+    Drained,
+    Paused,
+    /// `remaining` until the cliff ends, in the schedule's unit (slots when `use_slots`)
+    CliffNotReached { remaining: i64 },
+    FullyClaimed,
+    /// Nothing new has vested since the last claim
+    NothingVestedYet,
+    /// `claimable` is below the schedule's `min_claim_amount`
+    BelowMinClaim { claimable: u64 },
+    BlackoutActive { seconds_remaining: i64 },
+    CooldownActive { seconds_remaining: i64 },
+    /// The escrow holds less than the claim (only reported by `get_net_claimable`)
+    EscrowUnderfunded { escrow_balance: u64 },
+}
+
+/// Result of `get_net_claimable`
+/// - amount: RAW UNITS the beneficiary would receive by claiming now (0 unless `Eligible`).
+/// - status: `Eligible` or the first reason `claim` would fail.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct NetClaimable {
+    pub amount: u64,
+    pub status: ClaimEligibility,
+}
+
+/// Overall schedule status returned by `get_schedule_summary` (see `schedule_health`)
+#[derive(Clone, Copy, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum ScheduleHealth {
+    NotStarted,
+    InCliff,
+    Vesting,
+    FullyVested,
+    InGracePeriod,
+    Recoverable,
+    Closed,
+}
+
+/// Aggregate view of a schedule returned by `get_schedule_summary`
+/// - health: Overall status.
+/// - beneficiaries_count: Number of beneficiaries.
+/// - token_amount: Total tokens funded - RAW UNITS.
+/// - total_allocated: Sum of all allocations - RAW UNITS.
+/// - total_claimed: Sum of claimed (or recovered) tokens - RAW UNITS.
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ScheduleSummary {
+    pub health: ScheduleHealth,
+    pub beneficiaries_count: u32,
+    pub token_amount: u64,
+    pub total_allocated: u64,
+    pub total_claimed: u64,
+}
+
+/// Control keys of a schedule returned by `get_roles`
+/// - authority: Admin.
+/// - co_admin: Second signer required for withdrawals (None = admin alone).
+/// - claim_manager: Can pause and resume claims (`AdminRole::ClaimManager`).
+/// - fund_manager: Can distribute the surplus (`AdminRole::FundManager`).
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ScheduleRoles {
+    pub authority: Pubkey,
+    pub co_admin: Option<Pubkey>,
+    pub claim_manager: Option<Pubkey>,
+    pub fund_manager: Option<Pubkey>,
+}
+
+/// Result of `check_collateral`
+/// - collateralized: Escrow holds at least `required`.
+/// - obligations: Allocated but not yet claimed, over every beneficiary - RAW UNITS.
+/// - required: `obligations` scaled by `min_collateral_bps`, rounded up - RAW UNITS.
+/// - shortfall: `required` minus the escrow balance (0 when collateralized) - RAW UNITS.
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct CollateralStatus {
+    pub collateralized: bool,
+    pub obligations: u64,
+    pub required: u64,
+    pub shortfall: u64,
+}
+
+/// Claim blackout window in unix timestamps: `start` inclusive, `end` exclusive.
+#[derive(Clone, Copy, Default, AnchorSerialize, AnchorDeserialize)]
+pub struct BlackoutWindow {
+    pub start: i64,
+    pub end: i64,
+}
+
+/// Program constants returned by `get_constants` (see CONSTANTS for each meaning).
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ProgramConstants {
+    pub seconds_per_month: i64,
+    pub grace_period_months: i64,
+    pub grace_period: i64,
+    pub max_start_delay: i64,
+    pub max_beneficiaries: u32,
+    pub max_total_months: u8,
+    pub min_total_months: u8,
+    pub max_cliff_months: u8,
+    pub max_decimals: u8,
+    pub max_milestones: u32,
+    pub max_memo_len: u32,
+    pub max_beneficiary_page_entries: u32,
+    pub max_curve_samples: u8,
+    pub export_format_version: u8,
+    pub export_entries_per_page: u32,
+    pub escrow_migration_timelock: i64,
+    pub data_account_version: u8,
+    pub max_blackout_windows: u32,
+    pub dust_threshold: u64,
+    pub max_claimable_batch: u32,
+    pub max_batch_size: u32,
+}
+
+/// One point of `sample_curve`.
+/// - timestamp: Sample time, in the schedule's unit (slot when `use_slots`).
+/// - cumulative_vested: Amount vested at `timestamp` - RAW UNITS.
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct CurveSample {
+    pub timestamp: i64,
+    pub cumulative_vested: u64,
+}
+
+/// Result of `get_next_period_claimable`.
+/// - next_boundary: Next period boundary, in the schedule's unit (slot when `use_slots`).
+/// - additional: Amount that vests between now and `next_boundary` - RAW UNITS.
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct NextPeriodClaimable {
+    pub next_boundary: i64,
+    pub additional: u64,
 }
 
 // ================================================================================================
-// ACCOUNT STRUCTURES
+// EVENTS
 // ================================================================================================
 
-/// Account validation for initialize instruction
-/// - data_account: Stores vesting state.
-/// - escrow_wallet: Holds tokens for vesting.
-/// - wallet_to_withdraw_from: Admin's wallet to fund escrow.
-/// - sender: The admin.
-/// - token_mint: The SPL token mint.
-/// - system_program, token_program: System and token programs.
-#[derive(Accounts)]
-#[instruction(beneficiaries: Vec<Beneficiary>, amount: u64, decimals: u8)]
-pub struct Initialize<'info> {
-    #[account(init,
-        payer = sender,
-        space = calculate_vesting_space!(beneficiaries.len()),
-        seeds = [b"data_account", token_mint.key().as_ref()],
-        bump
-    )]
-    pub data_account: Account<'info, DataAccount>,
+/// Emitted when vesting program is successfully initialized
+#[event]
+pub struct VestingInitialized {
+    pub admin: Pubkey,
+    /// Owner of the wallet that funded the escrow (default for `initialize_prefunded`)
+    pub funder: Pubkey,
+    pub token_mint: Pubkey,
+    pub total_amount: u64,
+    pub beneficiaries_count: u32,
+    pub usd_value: u64,
+    /// `total_amount` in whole tokens, for spotting decimals mistakes
+    pub effective_token_amount: u64,
+    pub event_seq: u64,
+}
 
-    #[account(init, 
-        payer = sender, 
-        seeds = [b"escrow_wallet".as_ref(), token_mint.key().as_ref()],
-        bump,
-        token::mint=token_mint,
-        token::authority=data_account,
-    )]
-    pub escrow_wallet: Account<'info, TokenAccount>,
+/// Emitted when a two-phase schedule is fully populated and claims open
+#[event]
+pub struct ScheduleActivated {
+    pub admin: Pubkey,
+    pub beneficiaries_count: u32,
+    pub total_allocated: u64,
+    pub event_seq: u64,
+}
+
+/// Emitted whenever an instruction changes the escrow balance, so monitoring can watch a
+/// single event type for every escrow movement
+#[event]
+pub struct EscrowBalanceChanged {
+    pub schedule: Pubkey,
+    pub old_balance: u64,
+    pub new_balance: u64,
+    pub reason: EscrowChangeReason,
+    pub timestamp: i64,
+    pub event_seq: u64,
+}
+
+/// Emitted at the end of every operation that moves escrow tokens or changes allocations,
+/// giving indexers a uniform time series of schedule state
+#[event]
+pub struct ScheduleStateChanged {
+    pub schedule: Pubkey,
+    pub total_allocated: u64,
+    pub total_claimed: u64,
+    pub escrow_balance: u64,
+    pub timestamp: i64,
+    pub event_seq: u64,
+}
+
+/// Emitted by `claim_and_forward` after `TokensClaimed`
+#[event]
+pub struct ClaimForwarded {
+    pub beneficiary: Pubkey,
+    /// Token account the claimed tokens were moved to
+    pub forward_to: Pubkey,
+    /// Tokens moved on from the beneficiary's ATA (what the claim delivered)
+    pub amount: u64,
+    pub event_seq: u64,
+}
+
+/// Emitted when a beneficiary claims vested tokens
+#[event]
+pub struct TokensClaimed {
+    pub beneficiary: Pubkey,
+    /// Gross amount debited from escrow
+    pub amount: u64,
+    /// Amount credited to the beneficiary's ATA (equals `amount` for standard mints)
+    pub net_received: u64,
+    pub timestamp: i64,
+    pub usd_value: u64,
+    pub cumulative_claimed: u64,
+    pub remaining_allocation: u64,
+    /// Beneficiary's `notify_tag` (all zero = none)
+    pub notify_tag: [u8; 16],
+    /// Beneficiary's claims so far, including this one
+    pub claim_count: u32,
+    /// Beneficiary's stable `original_index`
+    pub original_index: u32,
+    /// Beneficiary's external `reference` (all zero = none)
+    pub reference: [u8; 24],
+    pub event_seq: u64,
+}
 
-    #[account(
-        mut,
-        constraint=wallet_to_withdraw_from.owner == sender.key(),
-        constraint=wallet_to_withdraw_from.mint == token_mint.key()
-    )]
-    pub wallet_to_withdraw_from: Account<'info, TokenAccount>,
+/// Emitted when admin withdraws unclaimed tokens after grace period
+#[event]
+pub struct AllUnclaimedWithdrawn {
+    pub admin: Pubkey,
+    pub total_amount: u64,
+    pub beneficiaries_processed: u32,
+    pub timestamp: i64,
+    pub event_seq: u64,
+}
 
-    pub token_mint: Account<'info, Mint>,
+/// Emitted by `withdraw` for each beneficiary whose unclaimed tokens were recovered,
+/// in ascending `index` order
+#[event]
+pub struct BeneficiaryUnclaimedWithdrawn {
+    pub admin: Pubkey,
+    pub beneficiary: Pubkey,
+    pub index: u32,
+    pub original_index: u32,
+    pub amount: u64,
+    pub event_seq: u64,
+}
 
-    #[account(mut)]
-    pub sender: Signer<'info>,
+/// Emitted by `withdraw` with `skip_on_error` for a beneficiary whose recovery math
+/// failed; their entry is left unchanged for investigation
+#[event]
+pub struct BeneficiarySkipped {
+    pub admin: Pubkey,
+    pub beneficiary: Pubkey,
+    pub index: u32,
+    pub event_seq: u64,
+}
 
-    pub system_program: Program<'info, System>,
+/// Emitted when admin appends beneficiaries to an existing schedule
+#[event]
+pub struct BeneficiariesAdded {
+    pub admin: Pubkey,
+    pub added_count: u32,
+    pub beneficiaries_count: u32,
+    pub funded_amount: u64,
+    pub event_seq: u64,
+}
 
-    pub token_program: Program<'info, Token>
+/// Emitted when the surplus is distributed pro-rata to beneficiaries
+#[event]
+pub struct SurplusDistributed {
+    pub admin: Pubkey,
+    pub surplus: u64,
+    pub distributed: u64,
+    pub event_seq: u64,
 }
 
-/// Account validation for initialize instruction
-/// - data_account: storing vesting configuration (PDA)
-/// - escrow_wallet: holding vested tokens (PDA)
-/// - sender: Beneficiary claiming tokens
-/// - token_mint: Token mint for the vesting program
-/// - wallet_to_deposit_to: Beneficiary's token account (created if needed)
-#[derive(Accounts)]
-#[instruction(data_bump: u8, wallet_bump: u8)]
-pub struct Claim<'info> {
-    #[account(
-        mut, 
-        seeds = [b"data_account", token_mint.key().as_ref()],
-        bump= data_bump
-    )]
-    pub data_account: Account<'info, DataAccount>,
+/// Emitted when admin reserves unassigned beneficiary slots
+#[event]
+pub struct SlotsReserved {
+    pub admin: Pubkey,
+    pub reserved_count: u32,
+    pub beneficiaries_count: u32,
+    pub event_seq: u64,
+}
 
-    #[account(
-        mut,
-        seeds= [b"escrow_wallet".as_ref(), token_mint.key().as_ref()],
-        bump=wallet_bump,
-    )]
-    pub escrow_wallet: Account<'info, TokenAccount>,
+/// Emitted when admin assigns a wallet to a reserved slot
+#[event]
+pub struct SlotAssigned {
+    pub admin: Pubkey,
+    pub slot_index: u32,
+    pub beneficiary: Pubkey,
+    pub event_seq: u64,
+}
 
-    #[account(mut)]
-    pub sender: Signer<'info>,
+/// Emitted when a beneficiary forfeits their remaining allocation
+#[event]
+pub struct BeneficiaryForfeited {
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    /// Allocation kept under a negotiated settlement (None = full forfeit)
+    pub settlement_amount: Option<u64>,
+    pub timestamp: i64,
+    pub event_seq: u64,
+}
 
-    pub token_mint: Account<'info, Mint>,
+/// Emitted when admin updates the per-claim transfer limit
+#[event]
+pub struct MaxPerClaimUpdated {
+    pub admin: Pubkey,
+    pub max_per_claim: u64,
+    pub event_seq: u64,
+}
 
-    #[account(
-        init_if_needed,
-        payer = sender,
-        associated_token::mint = token_mint,
-        associated_token::authority = sender
-    )]
-    pub wallet_to_deposit_to: Account<'info, TokenAccount>,
+/// Emitted when admin recovers part of a single beneficiary's unclaimed tokens
+#[event]
+pub struct PartialUnclaimedWithdrawn {
+    pub admin: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub remaining_unclaimed: u64,
+    pub timestamp: i64,
+    pub event_seq: u64,
+}
 
-    pub associated_token_program: Program<'info, AssociatedToken>,
+/// Emitted when admin recovers an inactive beneficiary's vested-but-unclaimed tokens
+#[event]
+pub struct InactiveUnclaimedWithdrawn {
+    pub admin: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub last_activity: i64,
+    pub timestamp: i64,
+    pub event_seq: u64,
+}
 
-    pub token_program: Program<'info, Token>,
+/// Emitted when admin updates the minimum claim threshold
+#[event]
+pub struct MinClaimAmountUpdated {
+    pub admin: Pubkey,
+    pub min_claim_amount: u64,
+    pub event_seq: u64,
+}
 
-    pub system_program: Program<'info, System>,
+/// Emitted when admin updates the claim cooldown
+#[event]
+pub struct ClaimCooldownUpdated {
+    pub admin: Pubkey,
+    pub claim_cooldown_seconds: u64,
+    pub event_seq: u64,
 }
 
-/// Account validation for withdraw instruction
-/// - data_account: storing vesting configuration (PDA)
-/// - escrow_wallet: holding vested tokens (PDA)
-/// - admin_wallet: Admin's token account to receive unclaimed tokens
-#[derive(Accounts)]
-#[instruction(data_bump: u8, escrow_bump: u8)]
-pub struct WithdrawUnclaimed<'info> {
-    #[account(
-        mut,
-        seeds = [b"data_account", token_mint.key().as_ref()],
-        bump = data_bump
-    )]
-    pub data_account: Account<'info, DataAccount>,
+/// Emitted by `withdraw` for each treasury of a split
+#[event]
+pub struct TreasuryPaid {
+    pub admin: Pubkey,
+    /// Treasury token account credited
+    pub treasury: Pubkey,
+    pub amount: u64,
+    pub event_seq: u64,
+}
 
-    #[account(
-        mut,
-        seeds = [b"escrow_wallet", token_mint.key().as_ref()],
-        bump = escrow_bump,
-    )]
-    pub escrow_wallet: Account<'info, TokenAccount>,
+/// Emitted when admin updates the collateral alarm threshold
+#[event]
+pub struct MinCollateralUpdated {
+    pub admin: Pubkey,
+    pub min_collateral_bps: u16,
+    pub event_seq: u64,
+}
 
-    #[account(
-        mut,
-        constraint = admin_wallet.owner == admin.key(), 
-        constraint = admin_wallet.mint == token_mint.key(),
-    )]
-    pub admin_wallet: Account<'info, TokenAccount>,
+/// Emitted by `check_collateral` when the escrow is below the collateral threshold
+#[event]
+pub struct CollateralBelowThreshold {
+    pub escrow_balance: u64,
+    pub obligations: u64,
+    pub required: u64,
+    pub shortfall: u64,
+    pub min_collateral_bps: u16,
+    pub event_seq: u64,
+}
 
-    pub admin: Signer<'info>,
-    pub token_mint: Account<'info, Mint>,
-    pub token_program: Program<'info, Token>,
+/// Emitted when admin moves the claim ceiling
+#[event]
+pub struct ClaimCeilingUpdated {
+    pub admin: Pubkey,
+    pub claim_ceiling_time: i64,
+    pub event_seq: u64,
 }
 
+/// Emitted when admin or the claim manager pauses or resumes claims
+#[event]
+pub struct PausedUpdated {
+    pub admin: Pubkey,
+    pub paused: bool,
+    pub event_seq: u64,
+}
 
-/// Account validation for change_admin instruction
-/// - data_account: Stores vesting state (PDA)
-/// - current_admin: Current admin (must sign)
-/// - new_admin: New admin address
-#[derive(Accounts)]
-#[instruction(data_bump: u8)]
-pub struct ChangeAdmin<'info> {
-    #[account(
-        mut,
-        seeds = [b"data_account", token_mint.key().as_ref()],
-        bump = data_bump,
-        constraint = data_account.authority == current_admin.key() @VestingError::UnauthorizedAdmin,
-    )]
-    pub data_account: Account<'info, DataAccount>,
+/// Emitted when admin grants or revokes an operational role
+#[event]
+pub struct RoleUpdated {
+    pub admin: Pubkey,
+    pub role: AdminRole,
+    pub holder: Option<Pubkey>,
+    pub event_seq: u64,
+}
 
-    #[account(mut)]
-    pub current_admin: Signer<'info>,
-    
-    #[account(
-        mut,
-        constraint = new_admin.key() != current_admin.key() @VestingError::SameAdmin,
-        constraint = new_admin.key() != Pubkey::default()   @VestingError::InvalidAddress
-    )]
-    pub new_admin: UncheckedAccount<'info>,
+/// Emitted when a schedule syncs its time from its clock source
+#[event]
+pub struct ClockSynced {
+    pub clock_source: Pubkey,
+    pub timestamp: i64,
+    pub event_seq: u64,
+}
 
-    pub token_mint: Account<'info, Mint>
+/// Emitted when a closed schedule releases its slot in the admin's registry
+#[event]
+pub struct ScheduleSlotReleased {
+    pub admin: Pubkey,
+    pub registry: Pubkey,
+    pub schedule_count: u32,
+    pub event_seq: u64,
 }
 
-// ================================================================================================
-// DATA STRUCTURES
-// ================================================================================================
+/// Emitted when admin sets or clears the co-admin
+#[event]
+pub struct CoAdminUpdated {
+    pub admin: Pubkey,
+    pub co_admin: Option<Pubkey>,
+    pub event_seq: u64,
+}
 
-/// Configuration for a single beneficiary in the vesting schedule
-/// - key: Beneficiary's address.
-/// - allocated_tokens: Total tokens allocated.
-/// - claimed_tokens: Tokens already claimed.
-/// - start_time: Vesting start timestamp.
-/// - cliff_months: Number of cliff months.
-/// - total_months: Total vesting duration in months.
-#[derive(Default, Copy, Clone, AnchorSerialize, AnchorDeserialize)]
-pub struct Beneficiary {
-    pub key: Pubkey,
-    pub allocated_tokens: u64, // RAW UNITS
-    pub claimed_tokens: u64,   // RAW UNITS
-    pub start_time: i64, 
-    pub cliff_months: u8,
-    pub total_months: u8,
+/// Emitted when admin adds a claim blackout window
+#[event]
+pub struct BlackoutAdded {
+    pub admin: Pubkey,
+    pub start: i64,
+    pub end: i64,
+    pub event_seq: u64,
 }
 
-/// Main account storing all vesting program state.
-/// - token_amount: Total tokens for vesting - RAW UNITS.
-/// - authority: Admin address.
-/// - escrow_wallet: Escrow wallet address.
-/// - token_mint: SPL token mint.
-/// - beneficiaries: List of all beneficiaries.
-/// - decimals: Token decimals.
-#[account]
-#[derive(Default)]
-pub struct DataAccount {
-    // Space in bytes: 8 + 8 + 32 + 32 + 32 + 1 + (4 + (50 * (32 + 8 + 8 + 10)))
-    pub token_amount: u64,     // 8 
-    pub authority: Pubkey,   // 32
-    pub escrow_wallet: Pubkey, // 32
-    pub token_mint: Pubkey,    // 32
-    pub beneficiaries: Vec<Beneficiary>, // (4 + (n * (32 + 8 + 8 + 8 + 1 +1)))
-    pub decimals: u8           // 1
+/// Emitted when admin removes all claim blackout windows
+#[event]
+pub struct BlackoutsCleared {
+    pub admin: Pubkey,
+    pub event_seq: u64,
 }
 
-// ================================================================================================
-// EVENTS
-// ================================================================================================
+/// Emitted when all start times are shifted before vesting begins
+#[event]
+pub struct ScheduleRescheduled {
+    pub admin: Pubkey,
+    pub old_anchor_time: i64,
+    pub new_anchor_time: i64,
+    pub beneficiaries_count: u32,
+    pub event_seq: u64,
+}
 
-/// Emitted when vesting program is successfully initialized
+/// Emitted when a v1 data account is migrated to the current layout
 #[event]
-pub struct VestingInitialized {
+pub struct DataAccountMigrated {
     pub admin: Pubkey,
-    pub token_mint: Pubkey,
-    pub total_amount: u64,
+    pub version: u8,
     pub beneficiaries_count: u32,
+    pub event_seq: u64,
 }
 
-/// Emitted when a beneficiary claims vested tokens
+/// Emitted when admin resets `token_amount` to the sum of allocations
 #[event]
-pub struct TokensClaimed {
+pub struct TokenAmountRecomputed {
+    pub admin: Pubkey,
+    pub old_token_amount: u64,
+    pub new_token_amount: u64,
+    pub event_seq: u64,
+}
+
+/// Emitted when a linear beneficiary switches between monthly and continuous accrual
+#[event]
+pub struct VestingModeChanged {
+    /// Admin or the beneficiary
+    pub signer: Pubkey,
+    pub beneficiary: Pubkey,
+    pub mode: VestingMode,
+    /// Vested at the switch under the old mode - RAW UNITS
+    pub vested_before: u64,
+    /// Vested at the switch under the new mode - RAW UNITS
+    pub vested_after: u64,
+    pub event_seq: u64,
+}
+
+/// Emitted when admin converts a beneficiary to milestone-based release
+#[event]
+pub struct ConvertedToMilestones {
+    pub admin: Pubkey,
     pub beneficiary: Pubkey,
+    pub milestones_count: u32,
+    pub event_seq: u64,
+}
+
+/// Emitted when admin requests an escrow rotation
+#[event]
+pub struct EscrowMigrationRequested {
+    pub admin: Pubkey,
+    pub unlock_time: i64,
+    pub event_seq: u64,
+}
+
+/// Emitted when the escrow is rotated to a new account
+#[event]
+pub struct EscrowMigrated {
+    pub admin: Pubkey,
+    pub old_escrow: Pubkey,
+    pub new_escrow: Pubkey,
+    pub amount: u64,
+    pub escrow_nonce: u8,
+    pub event_seq: u64,
+}
+
+/// Emitted when admin empties the escrow with `emergency_drain`
+#[event]
+pub struct EmergencyDrain {
+    pub admin: Pubkey,
+    pub recipient: Pubkey,
     pub amount: u64,
     pub timestamp: i64,
+    pub event_seq: u64,
 }
 
-/// Emitted when admin withdraws unclaimed tokens after grace period
+/// Emitted when admin sweeps sub-threshold escrow dust with `force_unlock_dust`
 #[event]
-pub struct AllUnclaimedWithdrawn {
+pub struct DustSwept {
     pub admin: Pubkey,
-    pub total_amount: u64,
-    pub beneficiaries_processed: u32,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub event_seq: u64,
+}
+
+/// Emitted when a fully claimed entry is removed; the entry at `moved_from` now sits at `index`.
+/// `original_index` is that of the removed entry.
+#[event]
+pub struct BeneficiaryCompacted {
+    pub beneficiary: Pubkey,
+    pub index: u32,
+    pub original_index: u32,
+    pub moved_from: u32,
+    pub event_seq: u64,
+}
+
+/// Emitted when the last beneficiary with unclaimed tokens is fully claimed or recovered
+#[event]
+pub struct ScheduleExhausted {
+    pub schedule: Pubkey,
     pub timestamp: i64,
+    pub event_seq: u64,
 }
 
 /// Emitted when admin changes
@@ -637,7 +7078,8 @@ pub struct AllUnclaimedWithdrawn {
 pub struct AdminChanged {
     pub old_admin: Pubkey,
     pub new_admin: Pubkey,
-    pub timestamp: i64
+    pub timestamp: i64,
+    pub event_seq: u64,
 }
 
 // ================================================================================================
@@ -650,7 +7092,7 @@ pub struct AdminChanged {
 /// and help with debugging and user experience.
 #[error_code]
 pub enum VestingError {
-    #[msg("Unauthorized: sender is not the program admin")]
+    #[msg("Funding wallet is not owned by the signer")]
     InvalidSender,
     #[msg("No tokens available to claim at this time")]
     ClaimNotAllowed,
@@ -680,7 +7122,7 @@ pub enum VestingError {
     InvalidStartTime,
     #[msg("Insufficient token balance for requested operation")]
     InsufficientBalance,
-    #[msg("Token decimals must be 9 or less")]
+    #[msg("Token decimals must be 18 or less")]
     InvalidDecimals,
     #[msg("Total beneficiary allocations exceed available tokens")]
     OverAllocation,
@@ -700,4 +7142,156 @@ pub enum VestingError {
     SameAdmin,
     #[msg("Invalid admin address")]
     InvalidAddress,
+    #[msg("Claimable amount does not match the expected value")]
+    ClaimableAssertionFailed,
+    #[msg("No remaining allocation to forfeit")]
+    NothingToForfeit,
+    #[msg("Slots per period must be greater than zero in slot mode")]
+    InvalidSlotsPerPeriod,
+    #[msg("Requested page is out of range")]
+    InvalidPage,
+    #[msg("Requested amount exceeds the beneficiary's unclaimed balance")]
+    AmountExceedsUnclaimed,
+    #[msg("Vesting schedule is already initialized for this mint")]
+    AlreadyInitialized,
+    #[msg("Fixed rate denominator must be greater than zero when a rate is set")]
+    InvalidFixedRate,
+    #[msg("Vesting has already started for at least one beneficiary")]
+    VestingAlreadyStarted,
+    #[msg("Claimable amount is below the minimum claim threshold")]
+    BelowMinClaim,
+    #[msg("Beneficiary token account does not exist")]
+    AtaDoesNotExist,
+    #[msg("Deposit account is not the beneficiary's associated token account")]
+    InvalidDepositAccount,
+    #[msg("Admin withdrawals are disabled for this schedule")]
+    WithdrawDisabled,
+    #[msg("Funded amount must equal total allocations when withdrawals are disabled")]
+    UnallocatedSurplus,
+    #[msg("Escrow balance does not cover the total allocation")]
+    UnderCollateralized,
+    #[msg("Claim cooldown is still active for this beneficiary")]
+    ClaimCooldownActive,
+    #[msg("Data account is not in the v1 layout")]
+    AlreadyMigrated,
+    #[msg("Vesting period too long - maximum 120 months allowed")]
+    TotalMonthsTooLong,
+    #[msg("Address is not a member of this vesting schedule")]
+    NotAMember,
+    #[msg("Staking program account missing or does not match auto_stake_program")]
+    InvalidStakeProgram,
+    #[msg("Escrow migration was not requested or its timelock has not passed")]
+    EscrowMigrationLocked,
+    #[msg("New admin must be a system-owned account, not a program account")]
+    AdminMustBeSystemAccount,
+    #[msg("Slot index is out of range")]
+    InvalidSlotIndex,
+    #[msg("Slot has already been assigned a beneficiary")]
+    SlotAlreadyAssigned,
+    #[msg("Timestamp is in the future")]
+    TimestampInFuture,
+    #[msg("Escrow balance changed by a different amount than transferred")]
+    InvariantViolation,
+    #[msg("Schedule is not activated until all beneficiaries are appended")]
+    ScheduleNotActivated,
+    #[msg("Schedule is already activated")]
+    AlreadyActivated,
+    #[msg("Recomputing would increase token_amount")]
+    TokenAmountWouldIncrease,
+    #[msg("Claimable amount differs from the expected amount")]
+    ClaimableChanged,
+    #[msg("Milestones must be ascending, within the vesting window and sum to the allocation")]
+    InvalidMilestones,
+    #[msg("Milestone schedule account is required for this beneficiary")]
+    MilestonesRequired,
+    #[msg("Escrow token account is not owned by the data account")]
+    InvalidEscrowAuthority,
+    #[msg("Claims are paused")]
+    SchedulePaused,
+    #[msg("Schedule must be paused first")]
+    ScheduleNotPaused,
+    #[msg("Emergency drain was not enabled at initialization")]
+    EmergencyDisabled,
+    #[msg("Schedule was drained by an emergency drain")]
+    ScheduleDrained,
+    #[msg("Claim receipt must be passed exactly when the schedule stores receipts")]
+    InvalidClaimReceipt,
+    #[msg("Not allowed by the schedule's surplus policy")]
+    SurplusPolicyViolation,
+    #[msg("Vesting has not completed for every beneficiary")]
+    VestingNotComplete,
+    #[msg("No unallocated surplus to distribute")]
+    NoSurplus,
+    #[msg("Data account or bump does not match the derived data account PDA")]
+    DataPdaMismatch,
+    #[msg("Escrow wallet or bump does not match the derived escrow PDA")]
+    EscrowPdaMismatch,
+    #[msg("Beneficiary list length does not match the stored beneficiary count")]
+    CountMismatch,
+    #[msg("Cliff time must be after the start time")]
+    InvalidCliffTime,
+    #[msg("Vault address is not derived from the given owner program and seeds")]
+    InvalidVaultDerivation,
+    #[msg("Vesting duration is below the schedule's minimum")]
+    VestingTooShort,
+    #[msg("Memo must be valid UTF-8 of at most 128 bytes")]
+    InvalidMemo,
+    #[msg("Memo program account missing or not the SPL Memo program")]
+    InvalidMemoProgram,
+    #[msg("At least two curve samples are required")]
+    InvalidSampleCount,
+    #[msg("Co-admin must also sign")]
+    CoAdminSignatureRequired,
+    #[msg("Claims are suspended during a blackout window")]
+    BlackoutActive,
+    #[msg("Blackout window must be non-empty, end in the future and start after the last window")]
+    InvalidBlackoutWindow,
+    #[msg("Too many blackout windows - maximum 8 allowed")]
+    TooManyBlackoutWindows,
+    #[msg("Token account still holds tokens - only empty ATAs can be closed")]
+    ClaimAtaNotEmpty,
+    #[msg("Escrow balance is not dust - it must be non-zero and below 1000 raw units")]
+    NotDust,
+    #[msg("Settlement amount cannot be below the tokens already claimed")]
+    SettlementBelowClaimed,
+    #[msg("Settlement amount cannot exceed the original allocation")]
+    SettlementAboveAllocation,
+    #[msg("Audit log account must be passed exactly when the schedule keeps an audit log")]
+    InvalidAuditLog,
+    #[msg("Token mint has a freeze authority")]
+    MintHasFreezeAuthority,
+    #[msg("Admin has reached the maximum number of schedules")]
+    MaxSchedulesReached,
+    #[msg("Schedule still has unclaimed allocations")]
+    ScheduleNotClosed,
+    #[msg("Admin registry does not match the one the schedule is counted against")]
+    InvalidAdminRegistry,
+    #[msg("Batch is too large - split it across transactions")]
+    BatchTooLarge,
+    #[msg("Inactivity window must be shorter than the grace period and is not supported in slot mode")]
+    InvalidInactivityWindow,
+    #[msg("Schedule has no inactivity window")]
+    InactivityRecoveryDisabled,
+    #[msg("Beneficiary has not been inactive for the inactivity window")]
+    BeneficiaryNotInactive,
+    #[msg("Escrow holds less than the claim - it was emptied outside the program")]
+    EscrowUnderfunded,
+    #[msg("Clock source is not the schedule's clock override, or the schedule uses slots")]
+    InvalidClockSource,
+    #[msg("Clock source cannot move backwards")]
+    ClockSourceRewound,
+    #[msg("Switching vesting mode would reduce the amount already vested")]
+    ModeSwitchWouldReduce,
+    #[msg("Collateral threshold must be at least 10_000 basis points")]
+    InvalidCollateralThreshold,
+    #[msg("Expected a mint, data account, escrow and funding account per asset")]
+    InvalidAssetAccounts,
+    #[msg("Treasury split must sum to 10_000 bps, with one token account of the mint per treasury")]
+    InvalidTreasurySplit,
+    #[msg("Clock reports a negative time")]
+    InvalidClock,
+    #[msg("Forward destination must be the given token account of the schedule's mint")]
+    InvalidForwardAccount,
+    #[msg("New beneficiaries must start with no claim history")]
+    InvalidClaimHistory,
 }
@@ -0,0 +1,1197 @@
+import * as anchor from "@coral-xyz/anchor";
+import { BN } from "@coral-xyz/anchor";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { assert } from "chai";
+import { BUFFER_SECONDS, DECIMALS, SECOND_PER_MONTH } from "./constant";
+import {
+  BeneficiaryInput,
+  claimBuilder,
+  claimFor,
+  createMint,
+  createPDA,
+  createUserAndATA,
+  currentTime,
+  defaultOptions,
+  expectError,
+  getTokenBalance,
+  randomBeneficiary,
+  Schedule,
+  sendWithEvents,
+  sendWithLogs,
+  setUpSchedule,
+  startVesting,
+  toRawUnitFromBN,
+  vestingSpace,
+  VestingEnv,
+  warp,
+} from "./utils";
+
+describe("admin operations", () => {
+  let env: VestingEnv;
+
+  before(async () => {
+    env = await startVesting();
+  });
+
+  function addBeneficiaries(
+    schedule: Schedule,
+    beneficiaries: BeneficiaryInput[],
+    amount: BN = new BN(0)
+  ) {
+    return env.program.methods
+      .addBeneficiaries(beneficiaries, amount, schedule.dataBump)
+      .accounts({
+        dataAccount: schedule.dataAccount,
+        escrowWallet: schedule.escrowWallet,
+        walletToWithdrawFrom: schedule.senderATA,
+        admin: schedule.sender,
+        tokenMint: schedule.mint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+  }
+
+  describe("add_beneficiaries", () => {
+    const CHUNK_SIZE = 10;
+    let schedule: Schedule;
+
+    before(async () => {
+      // Fund enough surplus for 49 extra beneficiaries of 1 token each
+      schedule = await setUpSchedule(
+        env,
+        [{ amount: new BN(1), cliff: 0, duration: 12 }],
+        { surplus: new BN(50_000_000) }
+      );
+    });
+
+    it("adds beneficiaries up to exactly MAX_BENEFICIARIES", async () => {
+      const startTime = (await currentTime(env)) + BUFFER_SECONDS;
+      let count = 1;
+
+      while (count < 50) {
+        const size = Math.min(CHUNK_SIZE, 50 - count);
+        const chunk = Array.from({ length: size }, () =>
+          randomBeneficiary(startTime)
+        );
+        await addBeneficiaries(schedule, chunk);
+        count += size;
+      }
+
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      const info = await env.provider.connection.getAccountInfo(
+        schedule.dataAccount
+      );
+      assert.equal(account.beneficiaries.length, 50);
+      assert.equal(info.data.length, vestingSpace(50));
+    });
+
+    it("rejects the 51st beneficiary (TooManyBeneficiaries)", async () => {
+      const startTime = (await currentTime(env)) + BUFFER_SECONDS;
+
+      await expectError(
+        addBeneficiaries(schedule, [randomBeneficiary(startTime)]),
+        "TooManyBeneficiaries"
+      );
+    });
+
+    it("rejects allocations beyond the funded amount (OverAllocation)", async () => {
+      const fresh = await setUpSchedule(env, [
+        { amount: new BN(1), cliff: 0, duration: 12 },
+      ]);
+      const startTime = (await currentTime(env)) + BUFFER_SECONDS;
+
+      await expectError(
+        addBeneficiaries(fresh, [randomBeneficiary(startTime)]),
+        "OverAllocation"
+      );
+    });
+  });
+
+  describe("reserve_slots / assign_slot", () => {
+    let schedule: Schedule;
+    let slotUser: [Keypair, PublicKey];
+
+    function assignSlot(slotIndex: number, beneficiary: PublicKey) {
+      return env.program.methods
+        .assignSlot(schedule.dataBump, slotIndex, beneficiary)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(
+        env,
+        [{ amount: new BN(1_000), cliff: 0, duration: 12 }],
+        { surplus: toRawUnitFromBN(new BN(1_200)) }
+      );
+      slotUser = await createUserAndATA(env.ctx, env.provider, schedule.mint);
+    });
+
+    it("reserves an unassigned slot funded from the surplus", async () => {
+      const startTime = (await currentTime(env)) + BUFFER_SECONDS;
+
+      await env.program.methods
+        .reserveSlots(
+          [
+            {
+              amount: toRawUnitFromBN(new BN(1_200)),
+              cliffMonths: 0,
+              totalMonths: 12,
+              startTime: new BN(startTime),
+            },
+          ],
+          schedule.dataBump
+        )
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(account.beneficiaries.length, 2);
+      assert.isFalse(account.beneficiaries[1].assigned);
+      assert.isTrue(account.beneficiaries[1].key.equals(PublicKey.default));
+      assert.isTrue(account.beneficiaries[0].assigned);
+    });
+
+    it("an unassigned slot cannot be claimed", async () => {
+      await warp(env, SECOND_PER_MONTH + BigInt(BUFFER_SECONDS * 2));
+      const withSlotUser = {
+        ...schedule,
+        users: [...schedule.users, slotUser],
+      };
+
+      await expectError(
+        claimBuilder(env, withSlotUser, 1).rpc(),
+        "BeneficiaryNotFound"
+      );
+    });
+
+    it("does not match the unassigned slot by the default key", async () => {
+      const lookups = env.program.methods;
+      const query = {
+        dataAccount: schedule.dataAccount,
+        tokenMint: schedule.mint,
+      };
+
+      await expectError(
+        lookups.findMySchedule(PublicKey.default).accounts(query).view(),
+        "NotAMember"
+      );
+      await expectError(
+        lookups
+          .assertClaimable(PublicKey.default, new BN(0))
+          .accounts(query)
+          .rpc(),
+        "BeneficiaryNotFound"
+      );
+      await expectError(
+        lookups
+          .getVestedAt(PublicKey.default, new BN(0))
+          .accounts(query)
+          .view(),
+        "BeneficiaryNotFound"
+      );
+      await expectError(
+        lookups
+          .getNextPeriodClaimable(PublicKey.default)
+          .accounts(query)
+          .view(),
+        "BeneficiaryNotFound"
+      );
+      await expectError(
+        lookups.sampleCurve(PublicKey.default, 4).accounts(query).view(),
+        "BeneficiaryNotFound"
+      );
+      await expectError(
+        lookups
+          .withdrawAmount(
+            PublicKey.default,
+            new BN(1),
+            schedule.dataBump,
+            schedule.escrowBump
+          )
+          .accounts({
+            dataAccount: schedule.dataAccount,
+            escrowWallet: schedule.escrowWallet,
+            adminWallet: schedule.senderATA,
+            admin: schedule.sender,
+            tokenMint: schedule.mint,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc(),
+        "BeneficiaryNotFound"
+      );
+    });
+
+    it("assigns the slot and lets the wallet claim", async () => {
+      await assignSlot(1, slotUser[0].publicKey);
+
+      const withSlotUser = {
+        ...schedule,
+        users: [...schedule.users, slotUser],
+      };
+      await claimBuilder(env, withSlotUser, 1).rpc();
+
+      const balance = await getTokenBalance(slotUser[1], env.provider);
+      assert.equal(
+        balance.toString(),
+        toRawUnitFromBN(new BN(100)).toString()
+      );
+    });
+
+    it("rejects assigning a slot twice (SlotAlreadyAssigned)", async () => {
+      await expectError(
+        assignSlot(1, Keypair.generate().publicKey),
+        "SlotAlreadyAssigned"
+      );
+    });
+
+    it("rejects an out-of-range slot (InvalidSlotIndex)", async () => {
+      await expectError(
+        assignSlot(5, Keypair.generate().publicKey),
+        "InvalidSlotIndex"
+      );
+    });
+  });
+
+  describe("distribute_surplus", () => {
+    function distributeSurplus(schedule: Schedule) {
+      return env.program.methods
+        .distributeSurplus(schedule.dataBump)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          operator: schedule.sender,
+          escrowWallet: schedule.escrowWallet,
+          tokenMint: schedule.mint,
+        });
+    }
+
+    it("is rejected under the default AdminSweep policy", async () => {
+      const schedule = await setUpSchedule(
+        env,
+        [{ amount: new BN(1_000), cliff: 0, duration: 1 }],
+        { surplus: toRawUnitFromBN(new BN(400)) }
+      );
+
+      await expectError(
+        distributeSurplus(schedule).rpc(),
+        "SurplusPolicyViolation"
+      );
+    });
+
+    it("shares the surplus pro-rata once vesting completes", async () => {
+      const schedule = await setUpSchedule(
+        env,
+        [
+          { amount: new BN(1_000), cliff: 0, duration: 1 },
+          { amount: new BN(3_000), cliff: 0, duration: 1 },
+        ],
+        {
+          surplus: toRawUnitFromBN(new BN(400)),
+          options: {
+            ...defaultOptions(),
+            surplusPolicy: { proRataBeneficiaries: {} },
+          },
+        }
+      );
+
+      await expectError(
+        distributeSurplus(schedule).rpc(),
+        "VestingNotComplete"
+      );
+
+      await warp(env, SECOND_PER_MONTH + BigInt(BUFFER_SECONDS));
+      await distributeSurplus(schedule).rpc();
+
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(
+        account.beneficiaries[0].allocatedTokens.toString(),
+        toRawUnitFromBN(new BN(1_100)).toString()
+      );
+      assert.equal(
+        account.beneficiaries[1].allocatedTokens.toString(),
+        toRawUnitFromBN(new BN(3_300)).toString()
+      );
+
+      await claimFor(env, schedule, 0);
+      assert.equal(
+        (await getTokenBalance(schedule.users[0][1], env.provider)).toString(),
+        toRawUnitFromBN(new BN(1_100)).toString()
+      );
+      await expectError(distributeSurplus(schedule).rpc(), "NoSurplus");
+    });
+
+    it("leaves out a beneficiary who forfeited", async () => {
+      const schedule = await setUpSchedule(
+        env,
+        [
+          { amount: new BN(1_000), cliff: 0, duration: 1 },
+          { amount: new BN(3_000), cliff: 0, duration: 1 },
+        ],
+        {
+          surplus: toRawUnitFromBN(new BN(400)),
+          options: {
+            ...defaultOptions(),
+            surplusPolicy: { proRataBeneficiaries: {} },
+          },
+        }
+      );
+      const [user] = schedule.users[0];
+      await env.program.methods
+        .forfeit(schedule.dataBump, schedule.escrowBump, null)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          adminWallet: schedule.senderATA,
+          beneficiary: user.publicKey,
+          tokenMint: schedule.mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+      await warp(env, SECOND_PER_MONTH + BigInt(BUFFER_SECONDS));
+      const events = await sendWithEvents(env, distributeSurplus(schedule));
+
+      // The whole surplus goes to the remaining beneficiary
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      const state = events.find((e) => e.name === "scheduleStateChanged");
+      assert.equal(
+        state.data.totalAllocated.toString(),
+        toRawUnitFromBN(new BN(4_400)).toString()
+      );
+      assert.equal(
+        account.beneficiaries[0].allocatedTokens.toString(),
+        toRawUnitFromBN(new BN(1_000)).toString()
+      );
+      assert.equal(
+        account.beneficiaries[1].allocatedTokens.toString(),
+        toRawUnitFromBN(new BN(3_400)).toString()
+      );
+      await expectError(claimFor(env, schedule, 0), "ClaimNotAllowed");
+    });
+  });
+
+  describe("change_admin", () => {
+    let schedule: Schedule;
+
+    function changeAdmin(newAdmin: PublicKey) {
+      return env.program.methods
+        .changeAdmin(schedule.dataBump)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          currentAdmin: schedule.sender,
+          newAdmin,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 12 },
+      ]);
+    });
+
+    it("rejects a PDA as the new admin (AdminMustBeSystemAccount)", async () => {
+      await expectError(
+        changeAdmin(schedule.dataAccount),
+        "AdminMustBeSystemAccount"
+      );
+    });
+
+    it("rejects a token account as the new admin", async () => {
+      await expectError(
+        changeAdmin(schedule.escrowWallet),
+        "AdminMustBeSystemAccount"
+      );
+    });
+
+    it("accepts a wallet address", async () => {
+      const newAdmin = Keypair.generate().publicKey;
+
+      await changeAdmin(newAdmin);
+
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.isTrue(account.authority.equals(newAdmin));
+    });
+  });
+
+  describe("reschedule_all", () => {
+    let schedule: Schedule;
+
+    function rescheduleAll(newAnchorTime: BN) {
+      return env.program.methods
+        .rescheduleAll(schedule.dataBump, newAnchorTime)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 12 },
+        { amount: new BN(2_000), cliff: 3, duration: 12 },
+        { amount: new BN(3_000), cliff: 6, duration: 24 },
+      ]);
+    });
+
+    it("shifts every beneficiary's start time by the same delta", async () => {
+      const before = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      const delta = new BN(SECOND_PER_MONTH.toString());
+      const newAnchor = before.beneficiaries[0].startTime.add(delta);
+
+      await rescheduleAll(newAnchor);
+
+      const after = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      after.beneficiaries.forEach((b, i) => {
+        assert.equal(
+          b.startTime.toString(),
+          before.beneficiaries[i].startTime.add(delta).toString()
+        );
+      });
+    });
+
+    it("rejects a new anchor in the past", async () => {
+      const now = await currentTime(env);
+
+      await expectError(rescheduleAll(new BN(now - 1)), "InvalidStartTime");
+    });
+
+    it("rejects once vesting has begun", async () => {
+      await warp(env, SECOND_PER_MONTH + BigInt(BUFFER_SECONDS * 2));
+      const now = await currentTime(env);
+
+      await expectError(
+        rescheduleAll(new BN(now + BUFFER_SECONDS)),
+        "VestingAlreadyStarted"
+      );
+    });
+  });
+
+  describe("recompute_token_amount", () => {
+    let schedule: Schedule;
+
+    function recompute() {
+      return env.program.methods
+        .recomputeTokenAmount(schedule.dataBump)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(
+        env,
+        [
+          { amount: new BN(1_000), cliff: 0, duration: 12 },
+          { amount: new BN(2_000), cliff: 0, duration: 12 },
+        ],
+        { surplus: toRawUnitFromBN(new BN(500)) }
+      );
+    });
+
+    it("lowers token_amount to the sum of allocations", async () => {
+      await recompute();
+
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(
+        account.tokenAmount.toString(),
+        toRawUnitFromBN(new BN(3_000)).toString()
+      );
+    });
+
+    it("never inflates token_amount (TokenAmountWouldIncrease)", async () => {
+      // Corrupt the account so allocations exceed token_amount
+      const info = await env.provider.connection.getAccountInfo(
+        schedule.dataAccount
+      );
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      const corrupted = await env.program.coder.accounts.encode("DataAccount", {
+        ...account,
+        tokenAmount: toRawUnitFromBN(new BN(1_000)),
+      });
+      const data = Buffer.alloc(info.data.length);
+      corrupted.copy(data);
+      env.ctx.setAccount(schedule.dataAccount, { ...info, data });
+
+      await expectError(recompute(), "TokenAmountWouldIncrease");
+    });
+  });
+
+  describe("beneficiary_count", () => {
+    let schedule: Schedule;
+
+    before(async () => {
+      schedule = await setUpSchedule(
+        env,
+        [
+          { amount: new BN(1_000), cliff: 0, duration: 1 },
+          { amount: new BN(2_000), cliff: 0, duration: 1 },
+        ],
+        { surplus: new BN(1_000_000) }
+      );
+    });
+
+    it("tracks the beneficiary list length", async () => {
+      const startTime = (await currentTime(env)) + BUFFER_SECONDS;
+      await addBeneficiaries(schedule, [randomBeneficiary(startTime)]);
+
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(account.beneficiaryCount, 3);
+      assert.equal(account.beneficiaries.length, 3);
+    });
+
+    it("rejects mutations once the count is desynced (CountMismatch)", async () => {
+      const info = await env.provider.connection.getAccountInfo(
+        schedule.dataAccount
+      );
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      const corrupted = await env.program.coder.accounts.encode("DataAccount", {
+        ...account,
+        beneficiaryCount: 2,
+      });
+      const data = Buffer.alloc(info.data.length);
+      corrupted.copy(data);
+      env.ctx.setAccount(schedule.dataAccount, { ...info, data });
+
+      await warp(env, SECOND_PER_MONTH + BigInt(BUFFER_SECONDS));
+      await expectError(claimBuilder(env, schedule, 0).rpc(), "CountMismatch");
+    });
+  });
+
+  describe("migrate_escrow", () => {
+    const ESCROW_MIGRATION_TIMELOCK = BigInt(2 * 24 * 60 * 60);
+    let schedule: Schedule;
+    let newEscrow: PublicKey;
+    let newEscrowBump: number;
+
+    function migrateEscrow() {
+      return env.program.methods
+        .migrateEscrow(schedule.escrowBump, schedule.dataBump)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          oldEscrow: schedule.escrowWallet,
+          newEscrow,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 12 },
+      ]);
+      [newEscrow, newEscrowBump] = await createPDA(
+        [
+          Buffer.from("escrow_wallet"),
+          schedule.mint.toBuffer(),
+          Buffer.from([1]),
+        ],
+        env.program.programId
+      );
+    });
+
+    it("rejects a migration that was never requested", async () => {
+      await expectError(migrateEscrow(), "EscrowMigrationLocked");
+    });
+
+    it("rejects a migration before the timelock passes", async () => {
+      await env.program.methods
+        .requestEscrowMigration(schedule.dataBump)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+
+      await expectError(migrateEscrow(), "EscrowMigrationLocked");
+    });
+
+    it("moves the full balance to the new escrow and closes the old one", async () => {
+      const balance = await getTokenBalance(schedule.escrowWallet, env.provider);
+      await warp(env, ESCROW_MIGRATION_TIMELOCK);
+
+      await migrateEscrow();
+
+      assert.equal(
+        (await getTokenBalance(newEscrow, env.provider)).toString(),
+        balance.toString()
+      );
+      assert.isNull(
+        await env.provider.connection.getAccountInfo(schedule.escrowWallet)
+      );
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.isTrue(account.escrowWallet.equals(newEscrow));
+      assert.equal(account.escrowNonce, 1);
+    });
+
+    it("pays claims from the new escrow", async () => {
+      const rotated = {
+        ...schedule,
+        escrowWallet: newEscrow,
+        escrowBump: newEscrowBump,
+      };
+      await warp(env, SECOND_PER_MONTH + BigInt(BUFFER_SECONDS));
+
+      await claimFor(env, rotated, 0);
+
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.isTrue(account.beneficiaries[0].claimedTokens.gtn(0));
+    });
+  });
+
+  describe("emergency_drain", () => {
+    let guarded: Schedule;
+    let schedule: Schedule;
+
+    function setPaused(target: Schedule, paused: boolean) {
+      return env.program.methods
+        .setPaused(target.dataBump, paused)
+        .accounts({
+          dataAccount: target.dataAccount,
+          operator: target.sender,
+          tokenMint: target.mint,
+        })
+        .rpc();
+    }
+
+    function drainBuilder(
+      target: Schedule,
+      admin: PublicKey = target.sender,
+      adminWallet: PublicKey = target.senderATA
+    ) {
+      return env.program.methods
+        .emergencyDrain(target.dataBump, target.escrowBump)
+        .accounts({
+          dataAccount: target.dataAccount,
+          escrowWallet: target.escrowWallet,
+          adminWallet,
+          admin,
+          tokenMint: target.mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        });
+    }
+
+    before(async () => {
+      guarded = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 12 },
+      ]);
+      schedule = await setUpSchedule(
+        env,
+        [{ amount: new BN(1_000), cliff: 0, duration: 12 }],
+        { options: { ...defaultOptions(), emergencyEnabled: true } }
+      );
+    });
+
+    it("rejects when emergency_enabled was not set at init", async () => {
+      await setPaused(guarded, true);
+      await expectError(drainBuilder(guarded).rpc(), "EmergencyDisabled");
+    });
+
+    it("rejects while the schedule is not paused", async () => {
+      await expectError(drainBuilder(schedule).rpc(), "ScheduleNotPaused");
+    });
+
+    it("rejects a signer other than the admin", async () => {
+      await setPaused(schedule, true);
+      const [user, userATA] = schedule.users[0];
+
+      await expectError(
+        drainBuilder(schedule, user.publicKey, userATA).signers([user]).rpc(),
+        "UnauthorizedAdmin"
+      );
+    });
+
+    it("rejects claims while paused", async () => {
+      await warp(env, SECOND_PER_MONTH + BigInt(BUFFER_SECONDS));
+      await expectError(
+        claimBuilder(env, schedule, 0).rpc(),
+        "SchedulePaused"
+      );
+    });
+
+    it("moves the whole escrow to the admin and flags the schedule", async () => {
+      const escrowBefore = await getTokenBalance(
+        schedule.escrowWallet,
+        env.provider
+      );
+      const adminBefore = await getTokenBalance(
+        schedule.senderATA,
+        env.provider
+      );
+
+      const events = await sendWithEvents(env, drainBuilder(schedule));
+
+      assert.equal(events[0].name, "emergencyDrain");
+      assert.equal(events[0].data.amount.toString(), escrowBefore.toString());
+      assert.equal(
+        (await getTokenBalance(schedule.escrowWallet, env.provider)).toNumber(),
+        0
+      );
+      assert.equal(
+        (await getTokenBalance(schedule.senderATA, env.provider)).toString(),
+        adminBefore.add(escrowBefore).toString()
+      );
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.isTrue(account.drained);
+    });
+
+    it("rejects claims and a second drain once drained", async () => {
+      await setPaused(schedule, false);
+      await expectError(
+        claimBuilder(env, schedule, 0).rpc(),
+        "ScheduleDrained"
+      );
+
+      await setPaused(schedule, true);
+      await expectError(drainBuilder(schedule).rpc(), "ScheduleDrained");
+    });
+  });
+
+  describe("set_role", () => {
+    let schedule: Schedule;
+    const claimManager = Keypair.generate();
+
+    function setRole(
+      role: { claimManager: {} } | { fundManager: {} },
+      holder: PublicKey | null,
+      admin?: Keypair
+    ) {
+      return env.program.methods
+        .setRole(schedule.dataBump, role, holder)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          admin: admin ? admin.publicKey : schedule.sender,
+          tokenMint: schedule.mint,
+        })
+        .signers(admin ? [admin] : [])
+        .rpc();
+    }
+
+    function setPausedBy(operator: Keypair, paused: boolean) {
+      return env.program.methods
+        .setPaused(schedule.dataBump, paused)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          operator: operator.publicKey,
+          tokenMint: schedule.mint,
+        })
+        .signers([operator])
+        .rpc();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 12 },
+      ]);
+    });
+
+    it("only lets the admin grant roles", async () => {
+      await expectError(
+        setRole({ claimManager: {} }, claimManager.publicKey, claimManager),
+        "UnauthorizedAdmin"
+      );
+      await expectError(setPausedBy(claimManager, true), "UnauthorizedAdmin");
+    });
+
+    it("lets the claim manager pause and resume claims", async () => {
+      await setRole({ claimManager: {} }, claimManager.publicKey);
+      let account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.isTrue(account.claimManager.equals(claimManager.publicKey));
+      assert.isNull(account.fundManager);
+
+      await setPausedBy(claimManager, true);
+      account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.isTrue(account.paused);
+
+      await setPausedBy(claimManager, false);
+      account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.isFalse(account.paused);
+    });
+
+    it("does not give the claim manager withdrawal or surplus power", async () => {
+      await expectError(
+        env.program.methods
+          .withdraw(schedule.dataBump, schedule.escrowBump, false, null)
+          .accounts({
+            dataAccount: schedule.dataAccount,
+            escrowWallet: schedule.escrowWallet,
+            adminWallet: schedule.senderATA,
+            admin: claimManager.publicKey,
+            tokenMint: schedule.mint,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([claimManager])
+          .rpc(),
+        "UnauthorizedAdmin"
+      );
+      await expectError(
+        env.program.methods
+          .distributeSurplus(schedule.dataBump)
+          .accounts({
+            dataAccount: schedule.dataAccount,
+            operator: claimManager.publicKey,
+            escrowWallet: schedule.escrowWallet,
+            tokenMint: schedule.mint,
+          })
+          .signers([claimManager])
+          .rpc(),
+        "UnauthorizedAdmin"
+      );
+    });
+
+    it("emits RoleUpdated and revokes the role", async () => {
+      const events = await sendWithEvents(
+        env,
+        env.program.methods
+          .setRole(schedule.dataBump, { claimManager: {} }, null)
+          .accounts({
+            dataAccount: schedule.dataAccount,
+            admin: schedule.sender,
+            tokenMint: schedule.mint,
+          })
+      );
+      assert.equal(events[0].name, "roleUpdated");
+      assert.deepEqual(events[0].data.role, { claimManager: {} });
+      assert.isNull(events[0].data.holder);
+
+      await expectError(setPausedBy(claimManager, true), "UnauthorizedAdmin");
+    });
+  });
+
+  describe("audit_log", () => {
+    let schedule: Schedule;
+
+    async function fetchLog() {
+      return env.program.account.auditLog.fetch(schedule.auditLog);
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(
+        env,
+        [{ amount: new BN(1_200), cliff: 0, duration: 12 }],
+        {
+          // Per-second accrual so a claim is possible every second
+          options: { ...defaultOptions(), auditLog: true, continuousLinear: true },
+        }
+      );
+      const startTime = schedule.beneficiaries[0].startTime.toNumber();
+      await warp(env, BigInt(startTime - (await currentTime(env)) + 10));
+    });
+
+    it("records initialize and claims", async () => {
+      let log = await fetchLog();
+      assert.isTrue(log.schedule.equals(schedule.dataAccount));
+      assert.equal(log.totalOps.toNumber(), 1);
+      assert.deepEqual(log.entries[0].op, { initialize: {} });
+      assert.isTrue(log.entries[0].actor.equals(schedule.sender));
+      assert.equal(
+        log.entries[0].amount.toString(),
+        schedule.totalAmount.toString()
+      );
+
+      const [user] = schedule.users[0];
+      const events = await sendWithEvents(
+        env,
+        claimBuilder(env, schedule, 0),
+        [user]
+      );
+
+      log = await fetchLog();
+      assert.equal(log.totalOps.toNumber(), 2);
+      assert.deepEqual(log.entries[1].op, { claim: {} });
+      assert.isTrue(log.entries[1].actor.equals(user.publicKey));
+      assert.equal(
+        log.entries[1].amount.toString(),
+        events[0].data.amount.toString()
+      );
+    });
+
+    it("rejects an operation without the log account", async () => {
+      await warp(env, BigInt(1));
+      const [user, userATA] = schedule.users[0];
+      await expectError(
+        env.program.methods
+          .claim(schedule.dataBump, schedule.escrowBump, null)
+          .accounts({
+            dataAccount: schedule.dataAccount,
+            escrowWallet: schedule.escrowWallet,
+            sender: user.publicKey,
+            tokenMint: schedule.mint,
+            walletToDepositTo: userATA,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([user])
+          .rpc(),
+        "InvalidAuditLog"
+      );
+    });
+
+    it("keeps only the latest AUDIT_LOG_CAPACITY (16) operations", async () => {
+      const capacity = 16;
+      // 2 recorded so far; 17 more claims overwrite the 3 oldest entries
+      for (let i = 0; i < capacity + 1; i++) {
+        await warp(env, BigInt(1));
+        await claimFor(env, schedule, 0);
+      }
+
+      const log = await fetchLog();
+      const total = log.totalOps.toNumber();
+      assert.equal(total, capacity + 3);
+      assert.equal(log.entries.length, capacity);
+      assert.isFalse(log.entries.some((e) => "initialize" in e.op));
+
+      // Read in recording order, oldest first, starting at the next write slot
+      const ordered = log.entries
+        .slice(total % capacity)
+        .concat(log.entries.slice(0, total % capacity));
+      for (let i = 1; i < capacity; i++) {
+        assert.isAbove(
+          ordered[i].timestamp.toNumber(),
+          ordered[i - 1].timestamp.toNumber()
+        );
+      }
+    });
+  });
+
+  describe("check_collateral", () => {
+    let schedule: Schedule;
+
+    // Returns the decoded `CollateralStatus` and the events of one check
+    async function checkCollateral() {
+      const logs = await sendWithLogs(
+        env,
+        env.program.methods.checkCollateral().accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          tokenMint: schedule.mint,
+        })
+      );
+      const prefix = `Program return: ${env.program.programId} `;
+      const returned = logs.find((log) => log.startsWith(prefix));
+      const status = env.program.coder.types.decode(
+        "CollateralStatus",
+        Buffer.from(returned.slice(prefix.length), "base64")
+      );
+      const parser = new anchor.EventParser(
+        env.program.programId,
+        env.program.coder
+      );
+      return { status, events: Array.from(parser.parseLogs(logs)) };
+    }
+
+    function setMinCollateral(bps: number) {
+      return env.program.methods
+        .setMinCollateralBps(schedule.dataBump, bps)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+    }
+
+    before(async () => {
+      // 1_000 allocated, 1_050 in escrow
+      schedule = await setUpSchedule(
+        env,
+        [{ amount: new BN(1_000), cliff: 0, duration: 12 }],
+        { surplus: new BN(50) }
+      );
+    });
+
+    it("passes above the default threshold of 10_000 bps", async () => {
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(account.minCollateralBps, 10_000);
+
+      const { status, events } = await checkCollateral();
+      assert.isTrue(status.collateralized);
+      assert.equal(status.obligations.toString(), "1000");
+      assert.equal(status.shortfall.toString(), "0");
+      assert.lengthOf(events, 0);
+    });
+
+    it("passes exactly at the threshold", async () => {
+      await setMinCollateral(10_500);
+      await warp(env, BigInt(1));
+
+      const { status, events } = await checkCollateral();
+      assert.isTrue(status.collateralized);
+      assert.equal(status.required.toString(), "1050");
+      assert.lengthOf(events, 0);
+    });
+
+    it("alarms below the threshold", async () => {
+      await setMinCollateral(10_600);
+      await warp(env, BigInt(1));
+
+      const { status, events } = await checkCollateral();
+      assert.isFalse(status.collateralized);
+      assert.equal(status.shortfall.toString(), "10");
+      assert.equal(events[0].name, "collateralBelowThreshold");
+      assert.equal(events[0].data.shortfall.toString(), "10");
+      assert.equal(events[0].data.minCollateralBps, 10_600);
+    });
+
+    it("rejects a threshold below 10_000 bps", async () => {
+      await expectError(setMinCollateral(9_999), "InvalidCollateralThreshold");
+    });
+  });
+
+  describe("migrate_v1_to_v2", () => {
+    // Original layout: token_amount, authority, escrow_wallet, token_mint, beneficiaries, decimals
+    function encodeV1(
+      tokenAmount: BN,
+      authority: PublicKey,
+      escrowWallet: PublicKey,
+      tokenMint: PublicKey,
+      beneficiaries: BeneficiaryInput[]
+    ): Buffer {
+      const discriminator =
+        env.program.coder.accounts.accountDiscriminator("DataAccount");
+      const entries = beneficiaries.map((b) =>
+        Buffer.concat([
+          b.key.toBuffer(),
+          b.allocatedTokens.toArrayLike(Buffer, "le", 8),
+          b.claimedTokens.toArrayLike(Buffer, "le", 8),
+          b.startTime.toArrayLike(Buffer, "le", 8),
+          Buffer.from([b.cliffMonths, b.totalMonths]),
+        ])
+      );
+      const count = Buffer.alloc(4);
+      count.writeUInt32LE(beneficiaries.length);
+
+      return Buffer.concat([
+        discriminator,
+        tokenAmount.toArrayLike(Buffer, "le", 8),
+        authority.toBuffer(),
+        escrowWallet.toBuffer(),
+        tokenMint.toBuffer(),
+        count,
+        ...entries,
+        Buffer.from([DECIMALS, 0]),
+      ]);
+    }
+
+    it("migrates a v1 account and keeps its schedule", async () => {
+      const mint = await createMint(env.provider, DECIMALS);
+      const [dataAccount, dataBump] = await createPDA(
+        [Buffer.from("data_account"), mint.toBuffer()],
+        env.program.programId
+      );
+      const admin = env.provider.wallet.publicKey;
+      const escrowWallet = Keypair.generate().publicKey;
+      const startTime = (await currentTime(env)) + BUFFER_SECONDS;
+      const legacy = randomBeneficiary(startTime);
+
+      const data = encodeV1(
+        legacy.allocatedTokens,
+        admin,
+        escrowWallet,
+        mint,
+        [legacy]
+      );
+      env.ctx.setAccount(dataAccount, {
+        lamports:
+          await env.provider.connection.getMinimumBalanceForRentExemption(
+            data.length
+          ),
+        owner: env.program.programId,
+        executable: false,
+        data,
+      });
+
+      const migrate = () =>
+        env.program.methods
+          .migrateV1ToV2(dataBump)
+          .accounts({
+            dataAccount,
+            admin,
+            tokenMint: mint,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+
+      await migrate();
+
+      const info = await env.provider.connection.getAccountInfo(dataAccount);
+      assert.equal(info.data.length, vestingSpace(1));
+
+      const account = await env.program.account.dataAccount.fetch(dataAccount);
+      assert.equal(account.version, 2);
+      assert.equal(
+        account.tokenAmount.toString(),
+        legacy.allocatedTokens.toString()
+      );
+      assert.isTrue(account.authority.equals(admin));
+      assert.isTrue(account.escrowWallet.equals(escrowWallet));
+      assert.equal(account.decimals, DECIMALS);
+      assert.equal(account.maxPerClaim.toNumber(), 0);
+
+      const [b] = account.beneficiaries;
+      assert.isTrue(b.key.equals(legacy.key));
+      assert.equal(b.startTime.toString(), legacy.startTime.toString());
+      assert.equal(b.totalMonths, legacy.totalMonths);
+      assert.equal(b.lastClaimTime.toNumber(), 0);
+
+      await expectError(migrate(), "AlreadyMigrated");
+    });
+  });
+});
@@ -0,0 +1,239 @@
+import { BN } from "@coral-xyz/anchor";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { Keypair } from "@solana/web3.js";
+import { assert } from "chai";
+import { SECOND_PER_MONTH } from "./constant";
+import {
+  claimFor,
+  expectError,
+  getTokenBalance,
+  Schedule,
+  sendWithEvents,
+  setUpSchedule,
+  startVesting,
+  VestingEnv,
+  VestingModeInput,
+  warp,
+  warpToMonth,
+} from "./utils";
+
+describe("beneficiary operations", () => {
+  let env: VestingEnv;
+
+  before(async () => {
+    env = await startVesting();
+  });
+
+  describe("forfeit", () => {
+    let schedule: Schedule;
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+    });
+
+    it("returns the unclaimed remainder to the admin mid-vesting", async () => {
+      await warp(env, SECOND_PER_MONTH * BigInt(4) + BigInt(30));
+      await claimFor(env, schedule, 0);
+
+      const [user, userATA] = schedule.users[0];
+      const claimed = await getTokenBalance(userATA, env.provider);
+      const adminBefore = await getTokenBalance(schedule.senderATA, env.provider);
+
+      await env.program.methods
+        .forfeit(schedule.dataBump, schedule.escrowBump, null)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          adminWallet: schedule.senderATA,
+          beneficiary: user.publicKey,
+          tokenMint: schedule.mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+      const allocated = schedule.beneficiaries[0].allocatedTokens;
+      const adminAfter = await getTokenBalance(schedule.senderATA, env.provider);
+      assert.equal(
+        adminAfter.sub(adminBefore).toString(),
+        allocated.sub(claimed).toString()
+      );
+
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(
+        account.beneficiaries[0].claimedTokens.toString(),
+        allocated.toString()
+      );
+    });
+
+    it("cannot claim after forfeiting", async () => {
+      await warp(env, SECOND_PER_MONTH * BigInt(2));
+      await expectError(claimFor(env, schedule, 0), "ClaimNotAllowed");
+    });
+  });
+
+  describe("forfeit with a settlement amount", () => {
+    let schedule: Schedule;
+    let allocated: BN;
+    let startTime: number;
+
+    function settle(settlement: BN) {
+      const [user] = schedule.users[0];
+      return env.program.methods
+        .forfeit(schedule.dataBump, schedule.escrowBump, settlement)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          adminWallet: schedule.senderATA,
+          beneficiary: user.publicKey,
+          tokenMint: schedule.mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+    }
+
+    async function fetchEntry() {
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      return account.beneficiaries[0];
+    }
+
+    beforeEach(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      allocated = schedule.beneficiaries[0].allocatedTokens;
+      startTime = schedule.beneficiaries[0].startTime.toNumber();
+    });
+
+    it("settles above the vested amount and keeps vesting to it", async () => {
+      // 4/12 vested; settle at 9/12
+      await warpToMonth(env, startTime, 4);
+      const settlement = allocated.muln(9).divn(12);
+      const adminBefore = await getTokenBalance(
+        schedule.senderATA,
+        env.provider
+      );
+
+      await settle(settlement);
+
+      const adminAfter = await getTokenBalance(schedule.senderATA, env.provider);
+      assert.equal(
+        adminAfter.sub(adminBefore).toString(),
+        allocated.sub(settlement).toString()
+      );
+      const entry = await fetchEntry();
+      assert.equal(entry.allocatedTokens.toString(), settlement.toString());
+      assert.equal(entry.settledFrom.toString(), allocated.toString());
+
+      // The original curve runs on: 6/12 at month 6, capped at 9/12 from month 9
+      await warpToMonth(env, startTime, 6);
+      await claimFor(env, schedule, 0);
+      const midway = await getTokenBalance(schedule.users[0][1], env.provider);
+      assert.equal(midway.toString(), allocated.divn(2).toString());
+
+      await warpToMonth(env, startTime, 9);
+      await claimFor(env, schedule, 0);
+      const received = await getTokenBalance(
+        schedule.users[0][1],
+        env.provider
+      );
+      assert.equal(received.toString(), settlement.toString());
+    });
+
+    it("settles below the vested amount and pays it out at once", async () => {
+      // 6/12 vested; settle at 3/12, already covered by the vested amount
+      await warpToMonth(env, startTime, 6);
+      const settlement = allocated.divn(4);
+
+      await settle(settlement);
+      await claimFor(env, schedule, 0);
+
+      const received = await getTokenBalance(
+        schedule.users[0][1],
+        env.provider
+      );
+      assert.equal(received.toString(), settlement.toString());
+      await expectError(claimFor(env, schedule, 0), "ClaimNotAllowed");
+    });
+
+    it("rejects a settlement below the claimed amount", async () => {
+      await warpToMonth(env, startTime, 4);
+      await claimFor(env, schedule, 0);
+
+      await expectError(settle(allocated.divn(4)), "SettlementBelowClaimed");
+    });
+
+    it("rejects a settlement above the original allocation", async () => {
+      await expectError(
+        settle(allocated.addn(1)),
+        "SettlementAboveAllocation"
+      );
+    });
+  });
+
+  describe("set_vesting_mode", () => {
+    let schedule: Schedule;
+    let user: Keypair;
+
+    const setMode = (mode: VestingModeInput, signer: Keypair) =>
+      env.program.methods
+        .setVestingMode(user.publicKey, mode, schedule.dataBump)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          signer: signer.publicKey,
+          tokenMint: schedule.mint,
+        });
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      user = schedule.users[0][0];
+      const startTime = schedule.beneficiaries[0].startTime.toNumber();
+      await warpToMonth(
+        env,
+        startTime,
+        4,
+        Number(SECOND_PER_MONTH / BigInt(2))
+      );
+    });
+
+    it("rejects signers other than the admin or beneficiary", async () => {
+      const stranger = Keypair.generate();
+      await expectError(
+        setMode({ continuous: {} }, stranger).signers([stranger]).rpc(),
+        "UnauthorizedAdmin"
+      );
+    });
+
+    it("switches monthly to continuous without reducing vested", async () => {
+      const events = await sendWithEvents(
+        env,
+        setMode({ continuous: {} }, user),
+        [user]
+      );
+      const { vestedBefore, vestedAfter } = events[0].data;
+      assert.equal(vestedBefore.toString(), "400");
+      assert.isTrue(vestedAfter.gt(vestedBefore));
+
+      await claimFor(env, schedule, 0);
+      const claimed = await getTokenBalance(schedule.users[0][1], env.provider);
+      assert.isTrue(claimed.gte(vestedAfter));
+    });
+
+    it("rejects switching back while it would reduce vested", async () => {
+      await warp(env, BigInt(30));
+      await expectError(
+        setMode({ monthly: {} }, user).signers([user]).rpc(),
+        "ModeSwitchWouldReduce"
+      );
+    });
+  });
+});
@@ -0,0 +1,1600 @@
+import { BN } from "@coral-xyz/anchor";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  createCloseAccountInstruction,
+  createTransferInstruction,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import {
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import { assert } from "chai";
+import { BUFFER_SECONDS, SECOND_PER_MONTH } from "./constant";
+import {
+  claimBuilder,
+  createUserAndATA,
+  currentTime,
+  defaultOptions,
+  expectError,
+  getTokenBalance,
+  MOCK_STAKING_PROGRAM_ID,
+  Schedule,
+  sendWithEvents,
+  sendWithLogs,
+  setUpSchedule,
+  startVesting,
+  toRawUnitFromBN,
+  milestoneSchedulePDA,
+  VestingEnv,
+  warp,
+  warpToMonth,
+} from "./utils";
+
+describe("claim variants", () => {
+  let env: VestingEnv;
+
+  before(async () => {
+    env = await startVesting();
+  });
+
+  describe("max_per_claim", () => {
+    let schedule: Schedule;
+    let limit: BN;
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      limit = schedule.beneficiaries[0].allocatedTokens.divn(10);
+
+      await env.program.methods
+        .setMaxPerClaim(schedule.dataBump, limit)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+    });
+
+    it("releases a large claimable across several capped claims", async () => {
+      // 6 months vested = 600 tokens, limit = 120 tokens per claim
+      await warp(env, SECOND_PER_MONTH * BigInt(6) + BigInt(30));
+      const [user, userATA] = schedule.users[0];
+      const vested = schedule.beneficiaries[0].allocatedTokens.divn(2);
+
+      for (let i = 0; i < 5; i++) {
+        const events = await sendWithEvents(
+          env,
+          claimBuilder(env, schedule, 0),
+          [user]
+        );
+        assert.equal(events[0].data.amount.toString(), limit.toString());
+        await warp(env, BigInt(1));
+      }
+
+      const balance = await getTokenBalance(userATA, env.provider);
+      assert.equal(balance.toString(), vested.toString());
+      await expectError(
+        claimBuilder(env, schedule, 0).rpc(),
+        "ClaimNotAllowed"
+      );
+    });
+
+    it("only the admin can set the limit", async () => {
+      const [user] = schedule.users[0];
+      await expectError(
+        env.program.methods
+          .setMaxPerClaim(schedule.dataBump, new BN(0))
+          .accounts({
+            dataAccount: schedule.dataAccount,
+            admin: user.publicKey,
+            tokenMint: schedule.mint,
+          })
+          .signers([user])
+          .rpc(),
+        "UnauthorizedAdmin"
+      );
+    });
+  });
+
+  describe("min_claim_amount", () => {
+    let schedule: Schedule;
+    let monthly: BN;
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      monthly = schedule.beneficiaries[0].allocatedTokens.divn(12);
+
+      await env.program.methods
+        .setMinClaimAmount(schedule.dataBump, monthly.muln(2))
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+    });
+
+    it("rejects a claim below the threshold", async () => {
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+
+      await expectError(claimBuilder(env, schedule, 0).rpc(), "BelowMinClaim");
+    });
+
+    it("allows the final dust claim that exhausts the allocation", async () => {
+      const [user, userATA] = schedule.users[0];
+
+      // Month 11: well above the threshold
+      await warp(env, SECOND_PER_MONTH * BigInt(10));
+      await claimBuilder(env, schedule, 0).rpc();
+
+      // Month 12: only one month (below the threshold) remains
+      await warp(env, SECOND_PER_MONTH);
+      const events = await sendWithEvents(
+        env,
+        claimBuilder(env, schedule, 0),
+        [user]
+      );
+      assert.equal(events[0].data.amount.toString(), monthly.toString());
+
+      const balance = await getTokenBalance(userATA, env.provider);
+      assert.equal(
+        balance.toString(),
+        schedule.beneficiaries[0].allocatedTokens.toString()
+      );
+    });
+  });
+
+  describe("claim_cooldown_seconds", () => {
+    let schedule: Schedule;
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+
+      await env.program.methods
+        .setClaimCooldown(
+          schedule.dataBump,
+          new BN((SECOND_PER_MONTH * BigInt(3)).toString())
+        )
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+    });
+
+    it("rejects a second claim inside the cooldown window", async () => {
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+      await claimBuilder(env, schedule, 0).rpc();
+
+      await warp(env, SECOND_PER_MONTH);
+      await expectError(
+        claimBuilder(env, schedule, 0).rpc(),
+        "ClaimCooldownActive"
+      );
+    });
+
+    it("lets the final exhausting claim bypass the cooldown", async () => {
+      const userATA = schedule.users[0][1];
+
+      // Month 11: cooldown has long passed
+      await warp(env, SECOND_PER_MONTH * BigInt(9));
+      await claimBuilder(env, schedule, 0).rpc();
+
+      // Month 12: still inside the cooldown, but this claim exhausts the allocation
+      await warp(env, SECOND_PER_MONTH);
+      await claimBuilder(env, schedule, 0).rpc();
+
+      const balance = await getTokenBalance(userATA, env.provider);
+      assert.equal(
+        balance.toString(),
+        schedule.beneficiaries[0].allocatedTokens.toString()
+      );
+    });
+  });
+
+  describe("escrow authority", () => {
+    it("rejects an escrow at the right address owned by someone else", async () => {
+      const schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+
+      // Substitute the token account's owner field (bytes 32..64)
+      const info = await env.provider.connection.getAccountInfo(
+        schedule.escrowWallet
+      );
+      const data = Buffer.from(info.data);
+      Keypair.generate().publicKey.toBuffer().copy(data, 32);
+      env.ctx.setAccount(schedule.escrowWallet, { ...info, data });
+
+      await expectError(
+        claimBuilder(env, schedule, 0).rpc(),
+        "InvalidEscrowAuthority"
+      );
+    });
+  });
+
+  describe("externally drained escrow", () => {
+    it("fails without persisting the claim accounting", async () => {
+      const schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+
+      // Zero the escrow's token amount field (bytes 64..72) as an out-of-band drain would
+      const info = await env.provider.connection.getAccountInfo(
+        schedule.escrowWallet
+      );
+      const data = Buffer.from(info.data);
+      data.writeBigUInt64LE(BigInt(0), 64);
+      env.ctx.setAccount(schedule.escrowWallet, { ...info, data });
+
+      await expectError(
+        claimBuilder(env, schedule, 0).rpc(),
+        "EscrowUnderfunded"
+      );
+
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(account.beneficiaries[0].claimedTokens.toNumber(), 0);
+      assert.equal(account.beneficiaries[0].claimCount, 0);
+      assert.equal(account.beneficiaries[0].lastClaimTime.toNumber(), 0);
+
+      // Once refilled, the same claim pays out the full vested amount
+      env.ctx.setAccount(schedule.escrowWallet, info);
+      await warp(env, BigInt(1));
+      await claimBuilder(env, schedule, 0).rpc();
+
+      const balance = await getTokenBalance(schedule.users[0][1], env.provider);
+      assert.equal(
+        balance.toString(),
+        schedule.beneficiaries[0].allocatedTokens.divn(12).toString()
+      );
+    });
+  });
+
+  describe("require_existing_ata", () => {
+    async function closeUserATA(schedule: Schedule) {
+      const [user, userATA] = schedule.users[0];
+      const tx = new Transaction().add(
+        createCloseAccountInstruction(userATA, user.publicKey, user.publicKey)
+      );
+      await env.provider.sendAndConfirm(tx, [user]);
+    }
+
+    it("rejects a claim to a missing ATA when set", async () => {
+      const schedule = await setUpSchedule(
+        env,
+        [{ amount: new BN(1_200), cliff: 0, duration: 12 }],
+        { options: { ...defaultOptions(), requireExistingAta: true } }
+      );
+      await closeUserATA(schedule);
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+
+      await expectError(claimBuilder(env, schedule, 0).rpc(), "AtaDoesNotExist");
+    });
+
+    it("creates a missing ATA at claim time when unset", async () => {
+      const schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      await closeUserATA(schedule);
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+
+      await claimBuilder(env, schedule, 0).rpc();
+
+      const balance = await getTokenBalance(schedule.users[0][1], env.provider);
+      assert.equal(
+        balance.toString(),
+        schedule.beneficiaries[0].allocatedTokens.divn(12).toString()
+      );
+    });
+
+    it("claims into an existing ATA without the ATA program", async () => {
+      const schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+
+      await claimBuilder(env, schedule, 0, false).rpc();
+
+      const balance = await getTokenBalance(schedule.users[0][1], env.provider);
+      assert.equal(
+        balance.toString(),
+        schedule.beneficiaries[0].allocatedTokens.divn(12).toString()
+      );
+    });
+
+    it("does not create a missing ATA without the ATA program", async () => {
+      const schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      await closeUserATA(schedule);
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+
+      await expectError(
+        claimBuilder(env, schedule, 0, false).rpc(),
+        "AtaDoesNotExist"
+      );
+    });
+  });
+
+  describe("close_empty_claim_ata", () => {
+    let schedule: Schedule;
+
+    function closeBuilder(owner: Keypair, claimAta: PublicKey) {
+      return env.program.methods
+        .closeEmptyClaimAta()
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          sender: owner.publicKey,
+          tokenMint: schedule.mint,
+          claimAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([owner]);
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      const [user, userATA] = schedule.users[0];
+      await env.provider.sendAndConfirm(
+        new Transaction().add(
+          createCloseAccountInstruction(userATA, user.publicKey, user.publicKey)
+        ),
+        [user]
+      );
+      // The claim re-creates the ATA at the beneficiary's expense
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+      await claimBuilder(env, schedule, 0).rpc();
+    });
+
+    it("rejects an ATA that still holds tokens", async () => {
+      const [user, userATA] = schedule.users[0];
+      await expectError(closeBuilder(user, userATA).rpc(), "ClaimAtaNotEmpty");
+    });
+
+    it("rejects a signer that is not a beneficiary", async () => {
+      const [stranger, strangerATA] = await createUserAndATA(
+        env.ctx,
+        env.provider,
+        schedule.mint
+      );
+      await expectError(
+        closeBuilder(stranger, strangerATA).rpc(),
+        "BeneficiaryNotFound"
+      );
+    });
+
+    it("closes the emptied ATA and refunds its rent", async () => {
+      const [user, userATA] = schedule.users[0];
+      const balance = await getTokenBalance(userATA, env.provider);
+      await env.provider.sendAndConfirm(
+        new Transaction().add(
+          createTransferInstruction(
+            userATA,
+            schedule.senderATA,
+            user.publicKey,
+            BigInt(balance.toString())
+          )
+        ),
+        [user]
+      );
+      const connection = env.provider.connection;
+      const rent = (await connection.getAccountInfo(userATA)).lamports;
+      const lamportsBefore = (await connection.getAccountInfo(user.publicKey))
+        .lamports;
+
+      await closeBuilder(user, userATA).rpc();
+
+      assert.isNull(await connection.getAccountInfo(userATA));
+      const lamportsAfter = (await connection.getAccountInfo(user.publicKey))
+        .lamports;
+      assert.equal(lamportsAfter, lamportsBefore + rent);
+    });
+  });
+
+  describe("auto_stake_program", () => {
+    let schedule: Schedule;
+    let vault: PublicKey;
+
+    before(async () => {
+      schedule = await setUpSchedule(
+        env,
+        [{ amount: new BN(1_200), cliff: 0, duration: 12 }],
+        {
+          options: {
+            ...defaultOptions(),
+            autoStakeProgram: MOCK_STAKING_PROGRAM_ID,
+          },
+        }
+      );
+      [, vault] = await createUserAndATA(env.ctx, env.provider, schedule.mint);
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+    });
+
+    it("rejects a claim without the staking accounts", async () => {
+      await expectError(
+        claimBuilder(env, schedule, 0).rpc(),
+        "InvalidStakeProgram"
+      );
+    });
+
+    it("deposits the claimed amount into the staking program", async () => {
+      const [user, userATA] = schedule.users[0];
+
+      await claimBuilder(env, schedule, 0)
+        .remainingAccounts([
+          { pubkey: MOCK_STAKING_PROGRAM_ID, isSigner: false, isWritable: false },
+          // mock_staking::deposit accounts: owner_token_account, vault, owner, token_program
+          { pubkey: userATA, isSigner: false, isWritable: true },
+          { pubkey: vault, isSigner: false, isWritable: true },
+          { pubkey: user.publicKey, isSigner: true, isWritable: true },
+          { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+        ])
+        .rpc();
+
+      const staked = await getTokenBalance(vault, env.provider);
+      const wallet = await getTokenBalance(userATA, env.provider);
+      assert.equal(
+        staked.toString(),
+        schedule.beneficiaries[0].allocatedTokens.divn(12).toString()
+      );
+      assert.equal(wallet.toNumber(), 0);
+    });
+  });
+
+  describe("claim_to_vault", () => {
+    let schedule: Schedule;
+    let custodian: Keypair;
+    let vault: PublicKey;
+    let vaultSeeds: Buffer[];
+
+    function claimToVault(ownerProgram: PublicKey, seeds: Buffer[]) {
+      const [user] = schedule.users[0];
+      return env.program.methods
+        .claimToVault(
+          schedule.dataBump,
+          schedule.escrowBump,
+          ownerProgram,
+          seeds
+        )
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          sender: user.publicKey,
+          tokenMint: schedule.mint,
+          walletToDepositTo: vault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      // The custodian's ATA is a PDA of the associated token program
+      [custodian, vault] = await createUserAndATA(
+        env.ctx,
+        env.provider,
+        schedule.mint
+      );
+      const seeds = [
+        custodian.publicKey.toBuffer(),
+        TOKEN_PROGRAM_ID.toBuffer(),
+        schedule.mint.toBuffer(),
+      ];
+      const [, bump] = PublicKey.findProgramAddressSync(
+        seeds,
+        ASSOCIATED_TOKEN_PROGRAM_ID
+      );
+      vaultSeeds = [...seeds, Buffer.from([bump])];
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+    });
+
+    it("rejects seeds that do not derive the vault", async () => {
+      await expectError(
+        claimToVault(TOKEN_PROGRAM_ID, vaultSeeds),
+        "InvalidVaultDerivation"
+      );
+    });
+
+    it("delivers the claim into the derived vault", async () => {
+      await claimToVault(ASSOCIATED_TOKEN_PROGRAM_ID, vaultSeeds);
+
+      const vaultBalance = await getTokenBalance(vault, env.provider);
+      const wallet = await getTokenBalance(schedule.users[0][1], env.provider);
+      assert.equal(
+        vaultBalance.toString(),
+        schedule.beneficiaries[0].allocatedTokens.divn(12).toString()
+      );
+      assert.equal(wallet.toNumber(), 0);
+    });
+  });
+
+  describe("memo", () => {
+    const MEMO_PROGRAM_ID = new PublicKey(
+      "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"
+    );
+    let schedule: Schedule;
+
+    function claimWithMemo(memo: Buffer, memoProgram: PublicKey | null) {
+      const [user, userATA] = schedule.users[0];
+      return env.program.methods
+        .claim(schedule.dataBump, schedule.escrowBump, memo)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          sender: user.publicKey,
+          tokenMint: schedule.mint,
+          walletToDepositTo: userATA,
+          memoProgram,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user]);
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+    });
+
+    it("rejects a memo longer than 128 bytes", async () => {
+      await expectError(
+        claimWithMemo(Buffer.alloc(129, "a"), MEMO_PROGRAM_ID).rpc(),
+        "InvalidMemo"
+      );
+    });
+
+    it("rejects a memo without the memo program", async () => {
+      await expectError(
+        claimWithMemo(Buffer.from("invoice-42"), null).rpc(),
+        "InvalidMemoProgram"
+      );
+    });
+
+    it("invokes the memo program before the transfer", async () => {
+      const [user] = schedule.users[0];
+      const logs = await sendWithLogs(
+        env,
+        claimWithMemo(Buffer.from("invoice-42"), MEMO_PROGRAM_ID),
+        [user]
+      );
+
+      const memoIndex = logs.indexOf(
+        `Program ${MEMO_PROGRAM_ID.toBase58()} invoke [2]`
+      );
+      const transferIndex = logs.indexOf(
+        `Program ${TOKEN_PROGRAM_ID.toBase58()} invoke [2]`
+      );
+      assert.isAtLeast(memoIndex, 0);
+      assert.isAbove(transferIndex, memoIndex);
+      assert.isTrue(logs.some((log) => log.includes('"invoice-42"')));
+    });
+  });
+
+  describe("claim_exact", () => {
+    let schedule: Schedule;
+
+    function claimExact(expected: BN) {
+      const [user, userATA] = schedule.users[0];
+      return env.program.methods
+        .claimExact(schedule.dataBump, schedule.escrowBump, expected)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          sender: user.publicKey,
+          tokenMint: schedule.mint,
+          walletToDepositTo: userATA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+    });
+
+    it("rejects a stale figure after the clock advances (ClaimableChanged)", async () => {
+      const monthly = schedule.beneficiaries[0].allocatedTokens.divn(12);
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+      // Client computes one month's worth, but another month vests before submission
+      const computed = monthly;
+      await warp(env, SECOND_PER_MONTH);
+
+      await expectError(claimExact(computed), "ClaimableChanged");
+
+      await claimExact(monthly.muln(2));
+      const balance = await getTokenBalance(
+        schedule.users[0][1],
+        env.provider
+      );
+      assert.equal(balance.toString(), monthly.muln(2).toString());
+    });
+  });
+
+  describe("claim_and_forward", () => {
+    let schedule: Schedule;
+    let lender: PublicKey;
+
+    function claimAndForward(forwardTo: PublicKey, account = forwardTo) {
+      const [user, userATA] = schedule.users[0];
+      return env.program.methods
+        .claimAndForward(schedule.dataBump, schedule.escrowBump, forwardTo)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          sender: user.publicKey,
+          tokenMint: schedule.mint,
+          walletToDepositTo: userATA,
+          forwardTo: account,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user]);
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 1 },
+      ]);
+      [, lender] = await createUserAndATA(env.ctx, env.provider, schedule.mint);
+      const startTime = schedule.beneficiaries[0].startTime.toNumber();
+      await warpToMonth(env, startTime, 1);
+    });
+
+    it("rejects an account other than the given destination", async () => {
+      await expectError(
+        claimAndForward(lender, schedule.users[0][1]).rpc(),
+        "InvalidForwardAccount"
+      );
+    });
+
+    it("lands the claimed tokens at the destination", async () => {
+      const events = await sendWithEvents(env, claimAndForward(lender), [
+        schedule.users[0][0],
+      ]);
+
+      const allocated = schedule.beneficiaries[0].allocatedTokens.toString();
+      const forwarded = await getTokenBalance(lender, env.provider);
+      assert.equal(forwarded.toString(), allocated);
+      const kept = await getTokenBalance(schedule.users[0][1], env.provider);
+      assert.equal(kept.toString(), "0");
+
+      const claimed = events.find((e) => e.name === "tokensClaimed");
+      const forward = events.find((e) => e.name === "claimForwarded");
+      assert.equal(claimed.data.amount.toString(), allocated);
+      assert.isTrue(forward.data.forwardTo.equals(lender));
+      assert.equal(forward.data.amount.toString(), allocated);
+      assert.equal(
+        forward.data.eventSeq.toNumber(),
+        claimed.data.eventSeq.toNumber() + 1
+      );
+    });
+  });
+
+  describe("store_receipts", () => {
+    let schedule: Schedule;
+
+    before(async () => {
+      schedule = await setUpSchedule(
+        env,
+        [{ amount: new BN(1_200), cliff: 0, duration: 12 }],
+        { options: { ...defaultOptions(), storeReceipts: true } }
+      );
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+    });
+
+    it("rejects a claim without a receipt account", async () => {
+      await expectError(
+        claimBuilder(env, schedule, 0).rpc(),
+        "InvalidClaimReceipt"
+      );
+    });
+
+    it("creates a receipt recording the claim", async () => {
+      const [user, userATA] = schedule.users[0];
+      const { eventSeq } = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      const [claimReceipt] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("claim_receipt"),
+          schedule.dataAccount.toBuffer(),
+          user.publicKey.toBuffer(),
+          eventSeq.toArrayLike(Buffer, "le", 8),
+        ],
+        env.program.programId
+      );
+
+      await env.program.methods
+        .claim(schedule.dataBump, schedule.escrowBump, null)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          sender: user.publicKey,
+          tokenMint: schedule.mint,
+          walletToDepositTo: userATA,
+          claimReceipt,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+      const receipt = await env.program.account.claimReceipt.fetch(
+        claimReceipt
+      );
+      const monthly = schedule.beneficiaries[0].allocatedTokens.divn(12);
+      assert.isTrue(receipt.schedule.equals(schedule.dataAccount));
+      assert.isTrue(receipt.beneficiary.equals(user.publicKey));
+      assert.equal(receipt.amount.toString(), monthly.toString());
+      assert.equal(receipt.totalClaimed.toString(), monthly.toString());
+      assert.isTrue(receipt.timestamp.gtn(0));
+      assert.equal(receipt.eventSeq.toNumber(), eventSeq.toNumber() + 1);
+    });
+  });
+
+  describe("claim_ceiling_time", () => {
+    let schedule: Schedule;
+
+    function setClaimCeiling(months: number) {
+      const ceiling = schedule.beneficiaries[0].startTime.add(
+        new BN((SECOND_PER_MONTH * BigInt(months)).toString())
+      );
+      return env.program.methods
+        .setClaimCeiling(schedule.dataBump, ceiling)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+    });
+
+    it("defaults to no cap", async () => {
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(
+        account.claimCeilingTime.toString(),
+        "9223372036854775807"
+      );
+    });
+
+    it("raising the ceiling opens more claimable amount", async () => {
+      const userATA = schedule.users[0][1];
+      const monthly = schedule.beneficiaries[0].allocatedTokens.divn(12);
+      await setClaimCeiling(2);
+      await warp(env, SECOND_PER_MONTH * BigInt(4) + BigInt(30));
+
+      await claimBuilder(env, schedule, 0).rpc();
+      assert.equal(
+        (await getTokenBalance(userATA, env.provider)).toString(),
+        monthly.muln(2).toString()
+      );
+      await expectError(
+        claimBuilder(env, schedule, 0).rpc(),
+        "ClaimNotAllowed"
+      );
+
+      await setClaimCeiling(4);
+      await claimBuilder(env, schedule, 0).rpc();
+      assert.equal(
+        (await getTokenBalance(userATA, env.provider)).toString(),
+        monthly.muln(4).toString()
+      );
+    });
+  });
+
+  describe("absolute cliff_time", () => {
+    const month = new BN(SECOND_PER_MONTH.toString());
+    let schedule: Schedule;
+
+    before(async () => {
+      const startTime = (await currentTime(env)) + BUFFER_SECONDS;
+      const start = new BN(startTime);
+      // Both have a 3-month offset cliff that the absolute date overrides
+      schedule = await setUpSchedule(
+        env,
+        [
+          {
+            amount: new BN(900),
+            cliff: 3,
+            duration: 12,
+            cliffTime: start.add(month),
+          },
+          {
+            amount: new BN(900),
+            cliff: 3,
+            duration: 12,
+            cliffTime: start.add(month.muln(5)),
+          },
+        ],
+        { startTime }
+      );
+    });
+
+    it("releases from an absolute cliff earlier than the month offset", async () => {
+      await warp(env, SECOND_PER_MONTH * BigInt(2) + BigInt(30));
+
+      await claimBuilder(env, schedule, 0).rpc();
+
+      const allocated = schedule.beneficiaries[0].allocatedTokens;
+      assert.equal(
+        (await getTokenBalance(schedule.users[0][1], env.provider)).toString(),
+        allocated.divn(9).toString()
+      );
+    });
+
+    it("holds back until an absolute cliff later than the month offset", async () => {
+      await warp(env, SECOND_PER_MONTH * BigInt(2));
+
+      await expectError(
+        claimBuilder(env, schedule, 1).rpc(),
+        "CliffNotReached"
+      );
+    });
+
+    it("rejects a cliff_time that is not after the start", async () => {
+      const startTime = (await currentTime(env)) + BUFFER_SECONDS;
+
+      await expectError(
+        setUpSchedule(
+          env,
+          [
+            {
+              amount: new BN(900),
+              cliff: 3,
+              duration: 12,
+              cliffTime: new BN(startTime),
+            },
+          ],
+          { startTime }
+        ),
+        "InvalidCliffTime"
+      );
+    });
+  });
+
+  describe("blackout windows", () => {
+    let schedule: Schedule;
+
+    function addBlackout(start: number, end: number) {
+      return env.program.methods
+        .addBlackout(schedule.dataBump, new BN(start), new BN(end))
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+    });
+
+    it("rejects a claim inside a window", async () => {
+      const now = await currentTime(env);
+      await addBlackout(now - 10, now + 3_600);
+
+      await expectError(claimBuilder(env, schedule, 0).rpc(), "BlackoutActive");
+    });
+
+    it("rejects a window overlapping the previous one", async () => {
+      const now = await currentTime(env);
+      await expectError(
+        addBlackout(now + 1_800, now + 7_200),
+        "InvalidBlackoutWindow"
+      );
+    });
+
+    it("claims everything accrued once the window ends", async () => {
+      await warp(env, SECOND_PER_MONTH);
+
+      await claimBuilder(env, schedule, 0).rpc();
+
+      const balance = await getTokenBalance(schedule.users[0][1], env.provider);
+      assert.equal(
+        balance.toString(),
+        schedule.beneficiaries[0].allocatedTokens.divn(6).toString()
+      );
+    });
+
+    it("clear_blackouts lifts an active window", async () => {
+      await warp(env, SECOND_PER_MONTH);
+      const now = await currentTime(env);
+      await addBlackout(now - 10, now + 3_600);
+      await expectError(claimBuilder(env, schedule, 0).rpc(), "BlackoutActive");
+
+      await env.program.methods
+        .clearBlackouts(schedule.dataBump)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+
+      await claimBuilder(env, schedule, 0).rpc();
+    });
+  });
+
+  describe("unlock_first_period_at_cliff", () => {
+    const configs = [{ amount: new BN(900), cliff: 3, duration: 12 }];
+    let standard: Schedule;
+    let unlockAtCliff: Schedule;
+    let startTime: number;
+
+    before(async () => {
+      startTime = (await currentTime(env)) + BUFFER_SECONDS;
+      standard = await setUpSchedule(env, configs, { startTime });
+      unlockAtCliff = await setUpSchedule(env, configs, {
+        startTime,
+        options: { ...defaultOptions(), unlockFirstPeriodAtCliff: true },
+      });
+      // Just past the cliff boundary: months_elapsed == cliff_months
+      await warpToMonth(env, startTime, 3);
+    });
+
+    it("unlocks nothing at the cliff boundary by default", async () => {
+      await expectError(
+        claimBuilder(env, standard, 0).rpc(),
+        "ClaimNotAllowed"
+      );
+    });
+
+    it("releases the first period at the cliff boundary when set", async () => {
+      await claimBuilder(env, unlockAtCliff, 0).rpc();
+
+      const balance = await getTokenBalance(
+        unlockAtCliff.users[0][1],
+        env.provider
+      );
+      assert.equal(
+        balance.toString(),
+        unlockAtCliff.beneficiaries[0].allocatedTokens.divn(9).toString()
+      );
+    });
+  });
+
+  describe("compact_on_complete", () => {
+    let schedule: Schedule;
+
+    before(async () => {
+      schedule = await setUpSchedule(
+        env,
+        [
+          { amount: new BN(1_000), cliff: 0, duration: 1 },
+          { amount: new BN(2_000), cliff: 0, duration: 12 },
+        ],
+        {
+          surplus: toRawUnitFromBN(new BN(1_000)),
+          options: { ...defaultOptions(), compactOnComplete: true },
+        }
+      );
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+    });
+
+    it("removes a fully claimed entry and moves the last one into its slot", async () => {
+      const events = await sendWithEvents(
+        env,
+        claimBuilder(env, schedule, 0),
+        [schedule.users[0][0]]
+      );
+
+      const compacted = events.find((e) => e.name === "beneficiaryCompacted");
+      assert.equal(compacted.data.index, 0);
+      assert.equal(compacted.data.originalIndex, 0);
+      assert.equal(compacted.data.movedFrom, 1);
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(account.beneficiaries.length, 1);
+      assert.equal(account.beneficiaryCount, 1);
+      assert.isTrue(
+        account.beneficiaries[0].key.equals(schedule.beneficiaries[1].key)
+      );
+      // The moved entry keeps its identity
+      assert.equal(account.beneficiaries[0].originalIndex, 1);
+      // Only the remaining allocation and the surplus stay funded
+      assert.equal(
+        account.tokenAmount.toString(),
+        schedule.beneficiaries[1].allocatedTokens
+          .add(toRawUnitFromBN(new BN(1_000)))
+          .toString()
+      );
+    });
+
+    it("re-adds a beneficiary into the freed slot", async () => {
+      const [newUser] = await createUserAndATA(
+        env.ctx,
+        env.provider,
+        schedule.mint
+      );
+      const startTime = (await currentTime(env)) + BUFFER_SECONDS;
+      const entry = {
+        ...schedule.beneficiaries[0],
+        key: newUser.publicKey,
+        startTime: new BN(startTime),
+      };
+
+      await env.program.methods
+        .addBeneficiaries([entry], new BN(0), schedule.dataBump)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          walletToWithdrawFrom: schedule.senderATA,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(account.beneficiaries.length, 2);
+      assert.isTrue(account.beneficiaries[1].key.equals(newUser.publicKey));
+      // Freed indices are never reused, whatever the client passed in
+      assert.equal(account.beneficiaries[0].originalIndex, 1);
+      assert.equal(account.beneficiaries[1].originalIndex, 2);
+    });
+  });
+
+  describe("convert_to_milestones", () => {
+    let schedule: Schedule;
+    let milestoneSchedule: PublicKey;
+
+    function convert(
+      index: number,
+      milestones: { unlockTime: BN; amount: BN }[]
+    ) {
+      const beneficiary = schedule.users[index][0].publicKey;
+      const [account] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("milestones"),
+          schedule.dataAccount.toBuffer(),
+          beneficiary.toBuffer(),
+        ],
+        env.program.programId
+      );
+      return {
+        account,
+        rpc: () =>
+          env.program.methods
+            .convertToMilestones(beneficiary, milestones, schedule.dataBump)
+            .accounts({
+              dataAccount: schedule.dataAccount,
+              milestoneSchedule: account,
+              admin: schedule.sender,
+              tokenMint: schedule.mint,
+              systemProgram: SystemProgram.programId,
+            })
+            .rpc(),
+      };
+    }
+
+    function claimMilestones() {
+      const [user, userATA] = schedule.users[0];
+      return env.program.methods
+        .claim(schedule.dataBump, schedule.escrowBump, null)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          sender: user.publicKey,
+          tokenMint: schedule.mint,
+          walletToDepositTo: userATA,
+          milestoneSchedule,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+    });
+
+    it("replaces the linear release with milestones before start", async () => {
+      const start = schedule.beneficiaries[0].startTime;
+      const month = new BN(SECOND_PER_MONTH.toString());
+      const allocated = schedule.beneficiaries[0].allocatedTokens;
+
+      const { account, rpc } = convert(0, [
+        { unlockTime: start.add(month), amount: allocated.divn(4) },
+        {
+          unlockTime: start.add(month.muln(3)),
+          amount: allocated.muln(3).divn(4),
+        },
+      ]);
+      await rpc();
+      milestoneSchedule = account;
+
+      const stored = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.deepEqual(stored.beneficiaries[0].curve, { milestones: {} });
+    });
+
+    it("rejects milestones that do not sum to the allocation", async () => {
+      const start = schedule.beneficiaries[1].startTime;
+      const { rpc } = convert(1, [{ unlockTime: start, amount: new BN(1) }]);
+
+      await expectError(rpc(), "InvalidMilestones");
+    });
+
+    it("claims by milestone instead of the linear formula", async () => {
+      const allocated = schedule.beneficiaries[0].allocatedTokens;
+      const userATA = schedule.users[0][1];
+
+      // Two months in: linear would give 2/12, milestones give 1/4
+      await warp(env, SECOND_PER_MONTH * BigInt(2) + BigInt(30));
+      await expectError(
+        claimBuilder(env, schedule, 0).rpc(),
+        "MilestonesRequired"
+      );
+      await claimMilestones();
+      assert.equal(
+        (await getTokenBalance(userATA, env.provider)).toString(),
+        allocated.divn(4).toString()
+      );
+
+      await warp(env, SECOND_PER_MONTH);
+      await claimMilestones();
+      assert.equal(
+        (await getTokenBalance(userATA, env.provider)).toString(),
+        allocated.toString()
+      );
+    });
+
+    it("rejects conversion once vesting has started", async () => {
+      const start = schedule.beneficiaries[1].startTime;
+      const { rpc } = convert(1, [
+        {
+          unlockTime: start.add(new BN(SECOND_PER_MONTH.toString())),
+          amount: schedule.beneficiaries[1].allocatedTokens,
+        },
+      ]);
+
+      await expectError(rpc(), "VestingAlreadyStarted");
+    });
+  });
+
+  describe("initialize_percentage_milestones", () => {
+    // 25% by month 3, 50% by month 6, 100% by month 12
+    const table = [
+      { month: 3, cumulativeBps: 2_500 },
+      { month: 6, cumulativeBps: 5_000 },
+      { month: 12, cumulativeBps: 10_000 },
+    ];
+
+    function claimMilestones(schedule: Schedule) {
+      const [user, userATA] = schedule.users[0];
+      return env.program.methods
+        .claim(schedule.dataBump, schedule.escrowBump, null)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          sender: user.publicKey,
+          tokenMint: schedule.mint,
+          walletToDepositTo: userATA,
+          milestoneSchedule: milestoneSchedulePDA(
+            env,
+            schedule.dataAccount,
+            user.publicKey
+          ),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+    }
+
+    it("converts the table and claims at each milestone boundary", async () => {
+      const schedule = await setUpSchedule(
+        env,
+        [{ amount: new BN(1_000), cliff: 0, duration: 12 }],
+        {
+          percentageMilestones: {
+            tables: [table],
+            interpolation: { step: {} },
+          },
+        }
+      );
+      const startTime = schedule.beneficiaries[0].startTime.toNumber();
+      const allocated = schedule.beneficiaries[0].allocatedTokens;
+      const userATA = schedule.users[0][1];
+
+      const stored = await env.program.account.milestoneSchedule.fetch(
+        milestoneSchedulePDA(
+          env,
+          schedule.dataAccount,
+          schedule.users[0][0].publicKey
+        )
+      );
+      assert.deepEqual(
+        stored.milestones.map((m) => m.amount.toString()),
+        [allocated.divn(4), allocated.divn(4), allocated.divn(2)].map((a) =>
+          a.toString()
+        )
+      );
+
+      // Just before month 3 nothing is unlocked under step release
+      await warpToMonth(env, startTime, 3, -1);
+      await expectError(claimMilestones(schedule), "ClaimNotAllowed");
+
+      for (const [month, bps] of [
+        [3, 2_500],
+        [6, 5_000],
+        [12, 10_000],
+      ]) {
+        await warpToMonth(env, startTime, month);
+        await claimMilestones(schedule);
+        assert.equal(
+          (await getTokenBalance(userATA, env.provider)).toString(),
+          allocated.muln(bps).divn(10_000).toString()
+        );
+      }
+    });
+
+    it("releases linearly towards each milestone when interpolated", async () => {
+      const schedule = await setUpSchedule(
+        env,
+        [{ amount: new BN(1_000), cliff: 0, duration: 12 }],
+        {
+          percentageMilestones: {
+            tables: [table],
+            interpolation: { linear: {} },
+          },
+        }
+      );
+      const startTime = schedule.beneficiaries[0].startTime.toNumber();
+      const allocated = schedule.beneficiaries[0].allocatedTokens;
+
+      // Halfway to month 6: 25% plus half of the next 25%
+      await warpToMonth(env, startTime, 4, Number(SECOND_PER_MONTH) / 2);
+      await claimMilestones(schedule);
+      assert.equal(
+        (await getTokenBalance(schedule.users[0][1], env.provider)).toString(),
+        allocated.muln(3_750).divn(10_000).toString()
+      );
+    });
+
+    it("rejects a table that does not end at 100%", async () => {
+      await expectError(
+        setUpSchedule(
+          env,
+          [{ amount: new BN(1_000), cliff: 0, duration: 12 }],
+          {
+            percentageMilestones: {
+              tables: [
+                [
+                  { month: 6, cumulativeBps: 5_000 },
+                  { month: 12, cumulativeBps: 9_000 },
+                ],
+              ],
+              interpolation: { step: {} },
+            },
+          }
+        ),
+        "InvalidMilestones"
+      );
+    });
+  });
+
+  describe("vesting curves", () => {
+    let schedule: Schedule;
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_600), cliff: 0, duration: 4 },
+        {
+          amount: new BN(1_600),
+          cliff: 0,
+          duration: 4,
+          curve: { frontLoaded: {} },
+        },
+        {
+          amount: new BN(1_600),
+          cliff: 0,
+          duration: 4,
+          curve: { backLoaded: {} },
+        },
+      ]);
+    });
+
+    it("releases linear, front-loaded and back-loaded shares at t = 0.25, 0.5, 0.75", async () => {
+      // Whole tokens unlocked per curve after 1, 2 and 3 of 4 months
+      const expected = [
+        [400, 700, 100],
+        [800, 1_200, 400],
+        [1_200, 1_500, 900],
+      ];
+
+      await warp(env, BigInt(30));
+      for (const unlocked of expected) {
+        await warp(env, SECOND_PER_MONTH);
+        for (let i = 0; i < 3; i++) {
+          await claimBuilder(env, schedule, i).rpc();
+          const balance = await getTokenBalance(
+            schedule.users[i][1],
+            env.provider
+          );
+          assert.equal(
+            balance.toString(),
+            toRawUnitFromBN(new BN(unlocked[i])).toString()
+          );
+        }
+      }
+    });
+  });
+
+  describe("slot-based vesting", () => {
+    const SLOTS_PER_PERIOD = 100;
+    let schedule: Schedule;
+    let startSlot: number;
+
+    before(async () => {
+      const clock = await env.client.getClock();
+      startSlot = Number(clock.slot) + 10;
+
+      schedule = await setUpSchedule(
+        env,
+        [{ amount: new BN(1_200), cliff: 0, duration: 12 }],
+        {
+          startTime: startSlot,
+          options: {
+            ...defaultOptions(),
+            useSlots: true,
+            slotsPerPeriod: new BN(SLOTS_PER_PERIOD),
+          },
+        }
+      );
+    });
+
+    it("nothing is claimable before the first period in slots", async () => {
+      env.ctx.warpToSlot(BigInt(startSlot + SLOTS_PER_PERIOD - 1));
+      await expectError(
+        claimBuilder(env, schedule, 0).rpc(),
+        "ClaimNotAllowed"
+      );
+    });
+
+    it("vests by elapsed slots regardless of the timestamp", async () => {
+      env.ctx.warpToSlot(BigInt(startSlot + 3 * SLOTS_PER_PERIOD));
+      await claimBuilder(env, schedule, 0).rpc();
+
+      const balance = await getTokenBalance(
+        schedule.users[0][1],
+        env.provider
+      );
+      const expected = schedule.beneficiaries[0].allocatedTokens
+        .muln(3)
+        .divn(12);
+      assert.equal(balance.toString(), expected.toString());
+    });
+
+    it("rejects slot mode without a period length", async () => {
+      await expectError(
+        setUpSchedule(env, [{ amount: new BN(1), cliff: 0, duration: 12 }], {
+          options: { ...defaultOptions(), useSlots: true },
+        }),
+        "InvalidSlotsPerPeriod"
+      );
+    });
+  });
+
+  describe("clock_override", () => {
+    const month = Number(SECOND_PER_MONTH);
+    let schedule: Schedule;
+    let clockSource: PublicKey;
+    let startTime: number;
+
+    function advanceClock(unixTimestamp: number) {
+      return env.program.methods
+        .advanceClockSource(new BN(unixTimestamp))
+        .accounts({ clockSource, authority: env.provider.wallet.publicKey })
+        .rpc();
+    }
+
+    function syncClock() {
+      return env.program.methods
+        .syncClock()
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          clockSource,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+    }
+
+    async function claimable(): Promise<string> {
+      const [amount] = await env.program.methods
+        .getClaimableBatch([schedule.users[0][0].publicKey])
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          tokenMint: schedule.mint,
+        })
+        .view();
+      return amount.toString();
+    }
+
+    before(async () => {
+      [clockSource] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("clock_source"),
+          env.provider.wallet.publicKey.toBuffer(),
+        ],
+        env.program.programId
+      );
+      await env.program.methods
+        .createClockSource(new BN(await currentTime(env)))
+        .accounts({
+          clockSource,
+          authority: env.provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      schedule = await setUpSchedule(
+        env,
+        [{ amount: new BN(1_200), cliff: 0, duration: 12 }],
+        { clockSource }
+      );
+      startTime = schedule.beneficiaries[0].startTime.toNumber();
+    });
+
+    it("vests by the override clock instead of the validator clock", async () => {
+      // The validator clock moving on changes nothing
+      await warp(env, SECOND_PER_MONTH * BigInt(6));
+      assert.equal(await claimable(), "0");
+
+      // Nor does the source until the schedule syncs it
+      await advanceClock(startTime + 3 * month + BUFFER_SECONDS);
+      assert.equal(await claimable(), "0");
+
+      await syncClock();
+      const vested = schedule.beneficiaries[0].allocatedTokens
+        .muln(3)
+        .divn(12);
+      assert.equal(await claimable(), vested.toString());
+
+      await claimBuilder(env, schedule, 0).rpc();
+      const balance = await getTokenBalance(schedule.users[0][1], env.provider);
+      assert.equal(balance.toString(), vested.toString());
+    });
+
+    it("never moves the clock backwards", async () => {
+      await expectError(advanceClock(startTime), "ClockSourceRewound");
+    });
+  });
+
+  describe("negative clock", () => {
+    let schedule: Schedule;
+    let now: bigint;
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 1 },
+      ]);
+      const startTime = schedule.beneficiaries[0].startTime.toNumber();
+      await warpToMonth(env, startTime, 8);
+
+      // Move the validator clock to one second before the epoch
+      now = BigInt(await currentTime(env));
+      await warp(env, -now - BigInt(1));
+    });
+
+    after(async () => {
+      await warp(env, now + BigInt(1));
+    });
+
+    it("rejects a negative clock source", async () => {
+      const [authority] = await createUserAndATA(
+        env.ctx,
+        env.provider,
+        schedule.mint
+      );
+      const [clockSource] = PublicKey.findProgramAddressSync(
+        [Buffer.from("clock_source"), authority.publicKey.toBuffer()],
+        env.program.programId
+      );
+      await expectError(
+        env.program.methods
+          .createClockSource(new BN(-1))
+          .accounts({
+            clockSource,
+            authority: authority.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc(),
+        "InvalidClock"
+      );
+    });
+
+    it("rejects initialize", async () => {
+      await expectError(
+        setUpSchedule(env, [{ amount: new BN(1_000), cliff: 0, duration: 1 }]),
+        "InvalidClock"
+      );
+    });
+
+    it("rejects claim", async () => {
+      await expectError(claimBuilder(env, schedule, 0).rpc(), "InvalidClock");
+    });
+
+    it("rejects withdraw", async () => {
+      await expectError(
+        env.program.methods
+          .withdraw(schedule.dataBump, schedule.escrowBump, false, null)
+          .accounts({
+            dataAccount: schedule.dataAccount,
+            escrowWallet: schedule.escrowWallet,
+            adminWallet: schedule.senderATA,
+            admin: schedule.sender,
+            tokenMint: schedule.mint,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc(),
+        "InvalidClock"
+      );
+    });
+  });
+
+  describe("debug-claim logging", () => {
+    // Needs a `--features debug-claim` build; see `anchor run test_debug_claim`
+    const debugClaim = process.env.DEBUG_CLAIM === "1";
+
+    (debugClaim ? it : it.skip)("logs the claim math", async () => {
+      const schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      const startTime = schedule.beneficiaries[0].startTime.toNumber();
+      await warpToMonth(env, startTime, 3);
+
+      const logs = await sendWithLogs(env, claimBuilder(env, schedule, 0), [
+        schedule.users[0][0],
+      ]);
+      const claimable = schedule.beneficiaries[0].allocatedTokens.divn(4);
+      assert.include(
+        logs.join("\n"),
+        "claim debug: months_elapsed=3 months_vested=3 " +
+          `unlocked=${claimable} claimed=0 claimable=${claimable}`
+      );
+    });
+  });
+});
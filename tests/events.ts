@@ -0,0 +1,444 @@
+import { BN } from "@coral-xyz/anchor";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { Keypair, SystemProgram } from "@solana/web3.js";
+import { assert } from "chai";
+import { BUFFER_SECONDS, DECIMALS, SECOND_PER_MONTH } from "./constant";
+import {
+  claimBuilder,
+  createAndFundSenderATA,
+  createMint,
+  createPDA,
+  currentTime,
+  defaultOptions,
+  Schedule,
+  randomBeneficiary,
+  sendWithEvents,
+  setUpSchedule,
+  startVesting,
+  toRawUnitFromBN,
+  VestingEnv,
+  warp,
+} from "./utils";
+
+describe("events", () => {
+  let env: VestingEnv;
+
+  before(async () => {
+    env = await startVesting();
+  });
+
+  describe("event_seq", () => {
+    let schedule: Schedule;
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+    });
+
+    it("increases monotonically across mixed operations", async () => {
+      const afterInit = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      // VestingInitialized and EscrowBalanceChanged
+      assert.equal(afterInit.eventSeq.toNumber(), 2);
+
+      const seqs: number[] = [];
+
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+      let events = await sendWithEvents(
+        env,
+        claimBuilder(env, schedule, 0),
+        [schedule.users[0][0]]
+      );
+      seqs.push(events[0].data.eventSeq.toNumber());
+
+      const newAdmin = Keypair.generate();
+      events = await sendWithEvents(
+        env,
+        env.program.methods.changeAdmin(schedule.dataBump).accounts({
+          dataAccount: schedule.dataAccount,
+          currentAdmin: schedule.sender,
+          newAdmin: newAdmin.publicKey,
+          tokenMint: schedule.mint,
+        })
+      );
+      assert.equal(events[0].name, "adminChanged");
+      seqs.push(events[0].data.eventSeq.toNumber());
+
+      await warp(env, SECOND_PER_MONTH);
+      events = await sendWithEvents(
+        env,
+        claimBuilder(env, schedule, 0),
+        [schedule.users[0][0]]
+      );
+      seqs.push(events[0].data.eventSeq.toNumber());
+
+      // Each claim also emits EscrowBalanceChanged and ScheduleStateChanged
+      assert.deepEqual(seqs, [3, 6, 7]);
+      const stored = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(stored.eventSeq.toNumber(), 9);
+    });
+  });
+
+  describe("VestingInitialized", () => {
+    it("reports the total in whole tokens as effective_token_amount", async () => {
+      const mint = await createMint(env.provider, DECIMALS);
+      const totalAmount = toRawUnitFromBN(new BN(1_500));
+      const senderATA = await createAndFundSenderATA(
+        env.provider,
+        mint,
+        BigInt(totalAmount.toString())
+      );
+      const [dataAccount] = await createPDA(
+        [Buffer.from("data_account"), mint.toBuffer()],
+        env.program.programId
+      );
+      const [escrowWallet] = await createPDA(
+        [Buffer.from("escrow_wallet"), mint.toBuffer()],
+        env.program.programId
+      );
+      const startTime = (await currentTime(env)) + BUFFER_SECONDS;
+
+      const events = await sendWithEvents(
+        env,
+        env.program.methods
+          .initialize(
+            [randomBeneficiary(startTime, totalAmount)],
+            totalAmount,
+            DECIMALS,
+            defaultOptions()
+          )
+          .accounts({
+            dataAccount,
+            escrowWallet,
+            walletToWithdrawFrom: senderATA,
+            tokenMint: mint,
+            sender: env.provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+      );
+
+      assert.equal(events[0].name, "vestingInitialized");
+      assert.equal(events[0].data.effectiveTokenAmount.toNumber(), 1_500);
+    });
+  });
+
+  describe("ScheduleStateChanged", () => {
+    let schedule: Schedule;
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 1 },
+      ]);
+    });
+
+    it("reflects post-operation state after a claim", async () => {
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+      const events = await sendWithEvents(
+        env,
+        claimBuilder(env, schedule, 0),
+        [schedule.users[0][0]]
+      );
+
+      const state = events[events.length - 1];
+      assert.equal(state.name, "scheduleStateChanged");
+      assert.isTrue(state.data.schedule.equals(schedule.dataAccount));
+      const allocated = schedule.beneficiaries[0].allocatedTokens;
+      assert.equal(state.data.totalAllocated.toString(), allocated.toString());
+      assert.equal(state.data.totalClaimed.toString(), allocated.toString());
+      assert.equal(
+        state.data.escrowBalance.toString(),
+        (await getTokenBalance(schedule.escrowWallet, env.provider)).toString()
+      );
+      assert.equal(state.data.escrowBalance.toNumber(), 0);
+    });
+  });
+
+  describe("EscrowBalanceChanged", () => {
+    let schedule: Schedule;
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+    });
+
+    it("reports the escrow delta of a claim", async () => {
+      const escrowBefore = await getTokenBalance(
+        schedule.escrowWallet,
+        env.provider
+      );
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+      const events = await sendWithEvents(
+        env,
+        claimBuilder(env, schedule, 0),
+        [schedule.users[0][0]]
+      );
+
+      const claimed = events.find((e) => e.name === "tokensClaimed");
+      const change = events.find((e) => e.name === "escrowBalanceChanged");
+      assert.isTrue(change.data.schedule.equals(schedule.dataAccount));
+      assert.deepEqual(change.data.reason, { claim: {} });
+      assert.equal(
+        change.data.oldBalance.toString(),
+        escrowBefore.toString()
+      );
+      assert.equal(
+        change.data.newBalance.toString(),
+        (await getTokenBalance(schedule.escrowWallet, env.provider)).toString()
+      );
+      assert.equal(
+        change.data.oldBalance.sub(change.data.newBalance).toString(),
+        claimed.data.amount.toString()
+      );
+      assert.equal(
+        claimed.data.amount.toString(),
+        schedule.beneficiaries[0].allocatedTokens.divn(12).toString()
+      );
+    });
+  });
+
+  describe("TokensClaimed reference", () => {
+    const reference = Array.from(
+      Buffer.from("HR-GRANT-2024-000123".padEnd(24, "\0"))
+    );
+    let schedule: Schedule;
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12, reference },
+      ]);
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+    });
+
+    it("stores the reference and echoes it in the event", async () => {
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.deepEqual(
+        Array.from(account.beneficiaries[0].reference),
+        reference
+      );
+
+      const events = await sendWithEvents(
+        env,
+        claimBuilder(env, schedule, 0),
+        [schedule.users[0][0]]
+      );
+      assert.deepEqual(Array.from(events[0].data.reference), reference);
+    });
+  });
+
+  describe("TokensClaimed notify_tag", () => {
+    const tag = Array.from({ length: 16 }, (_, i) => i + 1);
+    let schedule: Schedule;
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12, notifyTag: tag },
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+    });
+
+    it("carries the beneficiary's tag into the event", async () => {
+      const events = await sendWithEvents(
+        env,
+        claimBuilder(env, schedule, 0),
+        [schedule.users[0][0]]
+      );
+
+      assert.deepEqual(Array.from(events[0].data.notifyTag), tag);
+    });
+
+    it("is all zero when no tag is set", async () => {
+      const events = await sendWithEvents(
+        env,
+        claimBuilder(env, schedule, 1),
+        [schedule.users[1][0]]
+      );
+
+      assert.deepEqual(Array.from(events[0].data.notifyTag), Array(16).fill(0));
+    });
+  });
+
+  describe("claim_count", () => {
+    let schedule: Schedule;
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+    });
+
+    it("increments on every claim and is reported in TokensClaimed", async () => {
+      const [user] = schedule.users[0];
+
+      for (let expected = 1; expected <= 3; expected++) {
+        await warp(env, SECOND_PER_MONTH + BigInt(30));
+        const events = await sendWithEvents(
+          env,
+          claimBuilder(env, schedule, 0),
+          [user]
+        );
+        assert.equal(events[0].data.claimCount, expected);
+      }
+
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(account.beneficiaries[0].claimCount, 3);
+    });
+  });
+
+  describe("fixed-rate usd_value", () => {
+    let schedule: Schedule;
+
+    before(async () => {
+      schedule = await setUpSchedule(
+        env,
+        [{ amount: new BN(1_200), cliff: 0, duration: 12 }],
+        {
+          options: {
+            ...defaultOptions(),
+            fixedRateNumerator: new BN(3),
+            fixedRateDenominator: new BN(2),
+          },
+        }
+      );
+    });
+
+    it("stores the rate set at initialization", async () => {
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(account.fixedRateNumerator.toNumber(), 3);
+      assert.equal(account.fixedRateDenominator.toNumber(), 2);
+    });
+
+    it("emits amount * rate in TokensClaimed", async () => {
+      await warp(env, SECOND_PER_MONTH * BigInt(2) + BigInt(30));
+      const events = await sendWithEvents(
+        env,
+        claimBuilder(env, schedule, 0),
+        [schedule.users[0][0]]
+      );
+
+      const { amount, usdValue } = events[0].data;
+      assert.equal(usdValue.toString(), amount.muln(3).divn(2).toString());
+    });
+  });
+
+  describe("TokensClaimed running totals", () => {
+    let schedule: Schedule;
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+    });
+
+    it("reports cumulative claimed and remaining allocation", async () => {
+      const [user] = schedule.users[0];
+
+      for (const months of [2, 3]) {
+        await warp(env, SECOND_PER_MONTH * BigInt(months) + BigInt(30));
+        const events = await sendWithEvents(
+          env,
+          claimBuilder(env, schedule, 0),
+          [user]
+        );
+        const stored = await env.program.account.dataAccount.fetch(
+          schedule.dataAccount
+        );
+        const b = stored.beneficiaries[0];
+
+        const { cumulativeClaimed, remainingAllocation } = events[0].data;
+        assert.equal(cumulativeClaimed.toString(), b.claimedTokens.toString());
+        assert.equal(
+          remainingAllocation.toString(),
+          b.allocatedTokens.sub(b.claimedTokens).toString()
+        );
+      }
+    });
+  });
+
+  describe("TokensClaimed net_received", () => {
+    it("equals the gross amount for a standard mint", async () => {
+      const schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      const [user, userATA] = schedule.users[0];
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+      const before = await getTokenBalance(userATA, env.provider);
+
+      const events = await sendWithEvents(
+        env,
+        claimBuilder(env, schedule, 0),
+        [user]
+      );
+
+      const received = (await getTokenBalance(userATA, env.provider)).sub(
+        before
+      );
+      const { amount, netReceived } = events[0].data;
+      assert.equal(netReceived.toString(), received.toString());
+      assert.equal(netReceived.toString(), amount.toString());
+    });
+  });
+
+  describe("active_beneficiaries", () => {
+    let schedule: Schedule;
+
+    async function activeBeneficiaries(): Promise<number> {
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      return account.activeBeneficiaries;
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 2 },
+        { amount: new BN(1_200), cliff: 0, duration: 2 },
+      ]);
+    });
+
+    it("decrements exactly once per fully claimed beneficiary", async () => {
+      assert.equal(await activeBeneficiaries(), 2);
+
+      // A partial claim leaves the beneficiary active
+      await warp(env, SECOND_PER_MONTH + BigInt(30));
+      await claimBuilder(env, schedule, 0).rpc();
+      assert.equal(await activeBeneficiaries(), 2);
+
+      await warp(env, SECOND_PER_MONTH);
+      let events = await sendWithEvents(
+        env,
+        claimBuilder(env, schedule, 0),
+        [schedule.users[0][0]]
+      );
+      assert.equal(await activeBeneficiaries(), 1);
+      assert.notInclude(events.map((e) => e.name), "scheduleExhausted");
+
+      events = await sendWithEvents(
+        env,
+        claimBuilder(env, schedule, 1),
+        [schedule.users[1][0]]
+      );
+      assert.equal(await activeBeneficiaries(), 0);
+      const names = events.map((e) => e.name);
+      assert.deepEqual(names, [
+        "tokensClaimed",
+        "scheduleExhausted",
+        "escrowBalanceChanged",
+        "scheduleStateChanged",
+      ]);
+      assert.isTrue(events[1].data.schedule.equals(schedule.dataAccount));
+    });
+  });
+});
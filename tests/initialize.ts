@@ -0,0 +1,750 @@
+import { BN } from "@coral-xyz/anchor";
+import {
+  createMintToInstruction,
+  getAssociatedTokenAddressSync,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import {
+  AccountMeta,
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
+import { assert } from "chai";
+import { BUFFER_SECONDS, DECIMALS, SECOND_PER_MONTH } from "./constant";
+import {
+  BeneficiaryInput,
+  claimBuilder,
+  claimFor,
+  createAndFundSenderATA,
+  createMint,
+  createPDA,
+  createUserAndATA,
+  currentTime,
+  defaultOptions,
+  expectError,
+  getTokenBalance,
+  randomBeneficiary,
+  Schedule,
+  sendWithEvents,
+  setUpSchedule,
+  startVesting,
+  toRawUnitFromBN,
+  VestingEnv,
+  warp,
+  warpToMonth,
+} from "./utils";
+
+describe("initialize", () => {
+  let env: VestingEnv;
+
+  before(async () => {
+    env = await startVesting();
+  });
+
+  describe("idempotency", () => {
+    let schedule: Schedule;
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 12 },
+      ]);
+    });
+
+    it("rejects a second initialize for the same mint (AlreadyInitialized)", async () => {
+      await expectError(
+        env.program.methods
+          .initialize(
+            schedule.beneficiaries,
+            schedule.totalAmount,
+            DECIMALS,
+            defaultOptions()
+          )
+          .accounts({
+            dataAccount: schedule.dataAccount,
+            escrowWallet: schedule.escrowWallet,
+            walletToWithdrawFrom: schedule.senderATA,
+            tokenMint: schedule.mint,
+            sender: schedule.sender,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc(),
+        "AlreadyInitialized"
+      );
+
+      const escrowBalance = await getTokenBalance(
+        schedule.escrowWallet,
+        env.provider
+      );
+      assert.equal(escrowBalance.toString(), schedule.totalAmount.toString());
+    });
+  });
+
+  describe("validation", () => {
+    it("rejects a schedule longer than MAX_TOTAL_MONTHS", async () => {
+      await expectError(
+        setUpSchedule(env, [{ amount: new BN(1_000), cliff: 0, duration: 121 }]),
+        "TotalMonthsTooLong"
+      );
+    });
+
+    it("rejects funding from a wallet the sender does not own (InvalidSender)", async () => {
+      const mint = await createMint(env.provider, DECIMALS);
+      const amount = toRawUnitFromBN(new BN(1_000));
+      await createAndFundSenderATA(
+        env.provider,
+        mint,
+        BigInt(amount.toString())
+      );
+      const [, strangerATA] = await createUserAndATA(
+        env.ctx,
+        env.provider,
+        mint
+      );
+      const [dataAccount] = await createPDA(
+        [Buffer.from("data_account"), mint.toBuffer()],
+        env.program.programId
+      );
+      const [escrowWallet] = await createPDA(
+        [Buffer.from("escrow_wallet"), mint.toBuffer()],
+        env.program.programId
+      );
+      const startTime = (await currentTime(env)) + BUFFER_SECONDS;
+
+      await expectError(
+        env.program.methods
+          .initialize(
+            [randomBeneficiary(startTime, amount)],
+            amount,
+            DECIMALS,
+            defaultOptions()
+          )
+          .accounts({
+            dataAccount,
+            escrowWallet,
+            walletToWithdrawFrom: strangerATA,
+            tokenMint: mint,
+            sender: env.provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc(),
+        "InvalidSender"
+      );
+    });
+
+    it("funds from a separate funder while the sender becomes admin", async () => {
+      const mint = await createMint(env.provider, DECIMALS);
+      const amount = toRawUnitFromBN(new BN(1_000));
+      const [funder, funderATA] = await createUserAndATA(
+        env.ctx,
+        env.provider,
+        mint
+      );
+      await env.provider.sendAndConfirm(
+        new Transaction().add(
+          createMintToInstruction(
+            mint,
+            funderATA,
+            env.provider.wallet.publicKey,
+            BigInt(amount.toString())
+          )
+        )
+      );
+      const [dataAccount] = await createPDA(
+        [Buffer.from("data_account"), mint.toBuffer()],
+        env.program.programId
+      );
+      const [escrowWallet] = await createPDA(
+        [Buffer.from("escrow_wallet"), mint.toBuffer()],
+        env.program.programId
+      );
+      const startTime = (await currentTime(env)) + BUFFER_SECONDS;
+      const admin = env.provider.wallet.publicKey;
+
+      const events = await sendWithEvents(
+        env,
+        env.program.methods
+          .initialize(
+            [randomBeneficiary(startTime, amount)],
+            amount,
+            DECIMALS,
+            defaultOptions()
+          )
+          .accounts({
+            dataAccount,
+            escrowWallet,
+            walletToWithdrawFrom: funderATA,
+            tokenMint: mint,
+            sender: admin,
+            funder: funder.publicKey,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          }),
+        [funder]
+      );
+
+      assert.equal(events[0].name, "vestingInitialized");
+      assert.isTrue(events[0].data.admin.equals(admin));
+      assert.isTrue(events[0].data.funder.equals(funder.publicKey));
+
+      const data = await env.program.account.dataAccount.fetch(dataAccount);
+      assert.isTrue(data.authority.equals(admin));
+      const escrowBalance = await getTokenBalance(escrowWallet, env.provider);
+      assert.equal(escrowBalance.toString(), amount.toString());
+    });
+
+    describe("high-decimal mints", () => {
+      async function initializeWithDecimals(decimals: number, amount: BN) {
+        const mint = await createMint(env.provider, decimals);
+        const senderATA = await createAndFundSenderATA(
+          env.provider,
+          mint,
+          BigInt(amount.toString())
+        );
+        const [dataAccount] = await createPDA(
+          [Buffer.from("data_account"), mint.toBuffer()],
+          env.program.programId
+        );
+        const [escrowWallet] = await createPDA(
+          [Buffer.from("escrow_wallet"), mint.toBuffer()],
+          env.program.programId
+        );
+        const startTime = (await currentTime(env)) + BUFFER_SECONDS;
+
+        await env.program.methods
+          .initialize(
+            [randomBeneficiary(startTime, amount)],
+            amount,
+            decimals,
+            defaultOptions()
+          )
+          .accounts({
+            dataAccount,
+            escrowWallet,
+            walletToWithdrawFrom: senderATA,
+            tokenMint: mint,
+            sender: env.provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+        return dataAccount;
+      }
+
+      it("accepts an 18-decimal mint", async () => {
+        // 5 tokens at 18 decimals, well within u64
+        const amount = new BN(5).mul(new BN(10).pow(new BN(18)));
+        const dataAccount = await initializeWithDecimals(18, amount);
+
+        const account = await env.program.account.dataAccount.fetch(
+          dataAccount
+        );
+        assert.equal(account.decimals, 18);
+        assert.equal(account.tokenAmount.toString(), amount.toString());
+      });
+
+      it("rejects more than 18 decimals", async () => {
+        await expectError(
+          initializeWithDecimals(19, new BN(1_000)),
+          "InvalidDecimals"
+        );
+      });
+    });
+
+    it("accepts exactly MAX_TOTAL_MONTHS", async () => {
+      await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 120 },
+      ]);
+    });
+
+    it("rejects a schedule shorter than min_total_months (VestingTooShort)", async () => {
+      const options = { ...defaultOptions(), minTotalMonths: 6 };
+      await expectError(
+        setUpSchedule(
+          env,
+          [
+            { amount: new BN(1_000), cliff: 0, duration: 12 },
+            { amount: new BN(1_000), cliff: 0, duration: 5 },
+          ],
+          { options }
+        ),
+        "VestingTooShort"
+      );
+
+      const schedule = await setUpSchedule(
+        env,
+        [{ amount: new BN(1_000), cliff: 0, duration: 6 }],
+        { options }
+      );
+      const data = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(data.minTotalMonths, 6);
+    });
+
+    it("rejects a freezable mint when require_no_freeze_authority is set", async () => {
+      const options = { ...defaultOptions(), requireNoFreezeAuthority: true };
+      const configs = [{ amount: new BN(1_000), cliff: 0, duration: 12 }];
+      await expectError(
+        setUpSchedule(env, configs, { options }),
+        "MintHasFreezeAuthority"
+      );
+
+      const schedule = await setUpSchedule(env, configs, {
+        options,
+        freezeAuthority: null,
+      });
+      const data = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.isTrue(data.requireNoFreezeAuthority);
+    });
+  });
+
+  describe("initialize_empty / append_beneficiaries", () => {
+    const allocation = toRawUnitFromBN(new BN(1_200));
+    let mint: PublicKey;
+    let dataAccount: PublicKey;
+    let dataBump: number;
+    let escrowWallet: PublicKey;
+    let escrowBump: number;
+    let users: [Keypair, PublicKey][];
+    let beneficiaries: BeneficiaryInput[];
+
+    function appendBeneficiaries(chunk: BeneficiaryInput[]) {
+      return env.program.methods
+        .appendBeneficiaries(dataBump, chunk)
+        .accounts({
+          dataAccount,
+          admin: env.provider.wallet.publicKey,
+          tokenMint: mint,
+        })
+        .rpc();
+    }
+
+    function claim(index: number) {
+      const [user, userATA] = users[index];
+      return env.program.methods
+        .claim(dataBump, escrowBump, null)
+        .accounts({
+          dataAccount,
+          escrowWallet,
+          sender: user.publicKey,
+          tokenMint: mint,
+          walletToDepositTo: userATA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+    }
+
+    before(async () => {
+      mint = await createMint(env.provider, DECIMALS);
+      const totalAmount = allocation.muln(3);
+      const senderATA = await createAndFundSenderATA(
+        env.provider,
+        mint,
+        BigInt(totalAmount.toString())
+      );
+      [dataAccount, dataBump] = await createPDA(
+        [Buffer.from("data_account"), mint.toBuffer()],
+        env.program.programId
+      );
+      [escrowWallet, escrowBump] = await createPDA(
+        [Buffer.from("escrow_wallet"), mint.toBuffer()],
+        env.program.programId
+      );
+
+      users = [];
+      for (let i = 0; i < 3; i++) {
+        users.push(await createUserAndATA(env.ctx, env.provider, mint));
+      }
+      const startTime = (await currentTime(env)) + BUFFER_SECONDS;
+      beneficiaries = users.map(([user]) => ({
+        ...randomBeneficiary(startTime, allocation),
+        key: user.publicKey,
+      }));
+
+      await env.program.methods
+        .initializeEmpty(3, totalAmount, DECIMALS)
+        .accounts({
+          dataAccount,
+          escrowWallet,
+          walletToWithdrawFrom: senderATA,
+          tokenMint: mint,
+          sender: env.provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+    });
+
+    it("funds the escrow but stays inactive while partially populated", async () => {
+      await appendBeneficiaries(beneficiaries.slice(0, 2));
+
+      const account = await env.program.account.dataAccount.fetch(dataAccount);
+      assert.equal(account.beneficiaries.length, 2);
+      assert.isFalse(account.activated);
+      assert.equal(
+        (await getTokenBalance(escrowWallet, env.provider)).toString(),
+        allocation.muln(3).toString()
+      );
+
+      await warp(env, SECOND_PER_MONTH + BigInt(BUFFER_SECONDS * 2));
+      await expectError(claim(0), "ScheduleNotActivated");
+    });
+
+    it("activates once the last chunk is appended", async () => {
+      await appendBeneficiaries(beneficiaries.slice(2));
+
+      const account = await env.program.account.dataAccount.fetch(dataAccount);
+      assert.equal(account.beneficiaries.length, 3);
+      assert.isTrue(account.activated);
+
+      await claim(0);
+      assert.equal(
+        (await getTokenBalance(users[0][1], env.provider)).toString(),
+        allocation.divn(12).toString()
+      );
+    });
+
+    it("rejects appending after activation", async () => {
+      await expectError(
+        appendBeneficiaries([randomBeneficiary(await currentTime(env))]),
+        "AlreadyActivated"
+      );
+    });
+  });
+
+  describe("initialize_prefunded", () => {
+    const allocation = toRawUnitFromBN(new BN(1_000));
+
+    async function createFundedEscrow(fundedAmount: BN) {
+      const mint = await createMint(env.provider, DECIMALS);
+      const [dataAccount] = await createPDA(
+        [Buffer.from("data_account"), mint.toBuffer()],
+        env.program.programId
+      );
+      const [escrowWallet] = await createPDA(
+        [Buffer.from("escrow_wallet"), mint.toBuffer()],
+        env.program.programId
+      );
+
+      await env.program.methods
+        .createEscrow()
+        .accounts({
+          dataAccount,
+          escrowWallet,
+          tokenMint: mint,
+          payer: env.provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      // Funding happens in its own transaction, independent of the schedule
+      await env.provider.sendAndConfirm(
+        new Transaction().add(
+          createMintToInstruction(
+            mint,
+            escrowWallet,
+            env.provider.wallet.publicKey,
+            BigInt(fundedAmount.toString())
+          )
+        )
+      );
+
+      return { mint, dataAccount, escrowWallet };
+    }
+
+    async function initializePrefunded(
+      mint: PublicKey,
+      dataAccount: PublicKey,
+      escrowWallet: PublicKey
+    ) {
+      const startTime = (await currentTime(env)) + BUFFER_SECONDS;
+      return env.program.methods
+        .initializePrefunded(
+          [randomBeneficiary(startTime, allocation)],
+          DECIMALS,
+          defaultOptions()
+        )
+        .accounts({
+          dataAccount,
+          escrowWallet,
+          tokenMint: mint,
+          sender: env.provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    it("configures a schedule against an escrow funded beforehand", async () => {
+      const funded = allocation.muln(2);
+      const { mint, dataAccount, escrowWallet } = await createFundedEscrow(
+        funded
+      );
+
+      await initializePrefunded(mint, dataAccount, escrowWallet);
+
+      const account = await env.program.account.dataAccount.fetch(dataAccount);
+      assert.equal(account.tokenAmount.toString(), funded.toString());
+      assert.isTrue(account.escrowWallet.equals(escrowWallet));
+      assert.isTrue(account.authority.equals(env.provider.wallet.publicKey));
+    });
+
+    it("rejects an escrow that does not cover the allocations", async () => {
+      const { mint, dataAccount, escrowWallet } = await createFundedEscrow(
+        allocation.subn(1)
+      );
+
+      await expectError(
+        initializePrefunded(mint, dataAccount, escrowWallet),
+        "UnderCollateralized"
+      );
+    });
+  });
+
+  describe("schedule limit", () => {
+    let registry: PublicKey;
+
+    function setScheduleLimit(maxSchedules: number) {
+      return env.program.methods
+        .setScheduleLimit(maxSchedules)
+        .accounts({
+          adminRegistry: registry,
+          admin: env.provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    function releaseScheduleSlot(schedule: Schedule) {
+      return env.program.methods
+        .releaseScheduleSlot(schedule.dataBump)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          admin: schedule.sender,
+          adminRegistry: registry,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+    }
+
+    function registered() {
+      return setUpSchedule(
+        env,
+        [{ amount: new BN(1_000), cliff: 0, duration: 1 }],
+        { adminRegistry: registry }
+      );
+    }
+
+    before(async () => {
+      [registry] = await createPDA(
+        [
+          Buffer.from("admin_registry"),
+          env.provider.wallet.publicKey.toBuffer(),
+        ],
+        env.program.programId
+      );
+      await setScheduleLimit(2);
+    });
+
+    it("rejects the schedule past the limit (MaxSchedulesReached)", async () => {
+      const first = await registered();
+      await registered();
+
+      await expectError(registered(), "MaxSchedulesReached");
+
+      const account = await env.program.account.adminRegistry.fetch(registry);
+      assert.equal(account.scheduleCount, 2);
+      assert.equal(account.maxSchedules, 2);
+
+      // Releasing a finished schedule frees its slot
+      await expectError(releaseScheduleSlot(first), "ScheduleNotClosed");
+      const startTime = first.beneficiaries[0].startTime.toNumber();
+      await warpToMonth(env, startTime, 1);
+      await claimFor(env, first, 0);
+      await releaseScheduleSlot(first);
+      await expectError(releaseScheduleSlot(first), "InvalidAdminRegistry");
+
+      await registered();
+      const updated = await env.program.account.adminRegistry.fetch(registry);
+      assert.equal(updated.scheduleCount, 2);
+    });
+
+    it("does not count schedules initialized without the registry", async () => {
+      await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 1 },
+      ]);
+
+      const account = await env.program.account.adminRegistry.fetch(registry);
+      assert.equal(account.scheduleCount, 2);
+    });
+  });
+
+  describe("initialize_multi", () => {
+    it("bootstraps two-asset vesting for a shared cohort", async () => {
+      const sender = env.provider.wallet.publicKey;
+      const startTime = (await currentTime(env)) + BUFFER_SECONDS;
+      const [mintA, mintB] = [
+        await createMint(env.provider, DECIMALS),
+        await createMint(env.provider, DECIMALS),
+      ];
+      const cohort = [
+        await createUserAndATA(env.ctx, env.provider, mintA),
+        await createUserAndATA(env.ctx, env.provider, mintA),
+      ];
+      const beneficiaries = cohort.map(([user]) => ({
+        ...randomBeneficiary(startTime),
+        key: user.publicKey,
+      }));
+
+      const allocations = {
+        a: [new BN(1_200), new BN(2_400)].map(toRawUnitFromBN),
+        b: [new BN(120), new BN(240)].map(toRawUnitFromBN),
+      };
+      const schedules: Schedule[] = [];
+      const remainingAccounts: AccountMeta[] = [];
+      const assets: {
+        mint: PublicKey;
+        amount: BN;
+        decimals: number;
+        allocations: BN[];
+      }[] = [];
+      for (const [mint, amounts] of [
+        [mintA, allocations.a],
+        [mintB, allocations.b],
+      ] as [PublicKey, BN[]][]) {
+        const amount = amounts[0].add(amounts[1]);
+        const senderATA = await createAndFundSenderATA(
+          env.provider,
+          mint,
+          BigInt(amount.toString())
+        );
+        const [dataAccount, dataBump] = await createPDA(
+          [Buffer.from("data_account"), mint.toBuffer()],
+          env.program.programId
+        );
+        const [escrowWallet, escrowBump] = await createPDA(
+          [Buffer.from("escrow_wallet"), mint.toBuffer()],
+          env.program.programId
+        );
+
+        assets.push({ mint, amount, decimals: DECIMALS, allocations: amounts });
+        remainingAccounts.push(
+          { pubkey: mint, isSigner: false, isWritable: false },
+          { pubkey: dataAccount, isSigner: false, isWritable: true },
+          { pubkey: escrowWallet, isSigner: false, isWritable: true },
+          { pubkey: senderATA, isSigner: false, isWritable: true }
+        );
+        schedules.push({
+          mint,
+          sender,
+          senderATA,
+          dataAccount,
+          dataBump,
+          escrowWallet,
+          escrowBump,
+          users: cohort.map(
+            ([user]): [Keypair, PublicKey] => [
+              user,
+              getAssociatedTokenAddressSync(mint, user.publicKey),
+            ]
+          ),
+          beneficiaries: beneficiaries.map((b, i) => ({
+            ...b,
+            allocatedTokens: amounts[i],
+          })),
+          totalAmount: amount,
+          auditLog: null,
+        });
+      }
+
+      await env.program.methods
+        .initializeMulti(beneficiaries, assets, defaultOptions())
+        .accounts({
+          sender,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(remainingAccounts)
+        .rpc();
+
+      for (const schedule of schedules) {
+        const account = await env.program.account.dataAccount.fetch(
+          schedule.dataAccount
+        );
+        assert.isTrue(account.authority.equals(sender));
+        assert.isTrue(account.tokenMint.equals(schedule.mint));
+        assert.deepEqual(
+          account.beneficiaries.map((b) => b.key.toBase58()),
+          cohort.map(([user]) => user.publicKey.toBase58())
+        );
+        assert.deepEqual(
+          account.beneficiaries.map((b) => b.allocatedTokens.toString()),
+          schedule.beneficiaries.map((b) => b.allocatedTokens.toString())
+        );
+        const escrow = await getTokenBalance(
+          schedule.escrowWallet,
+          env.provider
+        );
+        assert.equal(escrow.toString(), schedule.totalAmount.toString());
+      }
+
+      // Each schedule vests independently on the shared timeline
+      await warpToMonth(env, startTime, 6);
+      for (const schedule of schedules) {
+        await claimBuilder(env, schedule, 0).rpc();
+        const received = await getTokenBalance(
+          schedule.users[0][1],
+          env.provider
+        );
+        assert.equal(
+          received.toString(),
+          schedule.beneficiaries[0].allocatedTokens.divn(2).toString()
+        );
+      }
+    });
+
+    it("rejects a data account that is not the mint's PDA", async () => {
+      const mint = await createMint(env.provider, DECIMALS);
+      const amount = toRawUnitFromBN(new BN(1_000));
+      const senderATA = await createAndFundSenderATA(
+        env.provider,
+        mint,
+        BigInt(amount.toString())
+      );
+      const [escrowWallet] = await createPDA(
+        [Buffer.from("escrow_wallet"), mint.toBuffer()],
+        env.program.programId
+      );
+      const startTime = (await currentTime(env)) + BUFFER_SECONDS;
+
+      await expectError(
+        env.program.methods
+          .initializeMulti(
+            [randomBeneficiary(startTime, amount)],
+            [{ mint, amount, decimals: DECIMALS, allocations: [amount] }],
+            defaultOptions()
+          )
+          .accounts({
+            sender: env.provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .remainingAccounts([
+            { pubkey: mint, isSigner: false, isWritable: false },
+            { pubkey: escrowWallet, isSigner: false, isWritable: true },
+            { pubkey: escrowWallet, isSigner: false, isWritable: true },
+            { pubkey: senderATA, isSigner: false, isWritable: true },
+          ])
+          .rpc(),
+        "InvalidAssetAccounts"
+      );
+    });
+  });
+});
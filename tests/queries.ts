@@ -0,0 +1,985 @@
+import { BN } from "@coral-xyz/anchor";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { assert } from "chai";
+import { BUFFER_SECONDS, SECOND_PER_MONTH } from "./constant";
+import {
+  claimFor,
+  currentTime,
+  defaultOptions,
+  expectError,
+  randomBeneficiary,
+  Schedule,
+  setUpSchedule,
+  startVesting,
+  toRawUnitFromBN,
+  VestingEnv,
+  warp,
+  warpToMonth,
+} from "./utils";
+
+describe("read-only queries", () => {
+  let env: VestingEnv;
+
+  before(async () => {
+    env = await startVesting();
+  });
+
+  describe("assert_claimable", () => {
+    let schedule: Schedule;
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      await warp(env, SECOND_PER_MONTH * BigInt(3) + BigInt(30));
+    });
+
+    it("passes when the expected amount matches", async () => {
+      const expected = schedule.beneficiaries[0].allocatedTokens.muln(3).divn(12);
+
+      await env.program.methods
+        .assertClaimable(schedule.users[0][0].publicKey, expected)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+    });
+
+    it("fails when the expected amount does not match", async () => {
+      const expected = schedule.beneficiaries[0].allocatedTokens.muln(4).divn(12);
+
+      await expectError(
+        env.program.methods
+          .assertClaimable(schedule.users[0][0].publicKey, expected)
+          .accounts({
+            dataAccount: schedule.dataAccount,
+            tokenMint: schedule.mint,
+          })
+          .rpc(),
+        "ClaimableAssertionFailed"
+      );
+    });
+
+    it("fails for a non-beneficiary", async () => {
+      await expectError(
+        env.program.methods
+          .assertClaimable(Keypair.generate().publicKey, new BN(0))
+          .accounts({
+            dataAccount: schedule.dataAccount,
+            tokenMint: schedule.mint,
+          })
+          .rpc(),
+        "BeneficiaryNotFound"
+      );
+    });
+  });
+
+  describe("export_schedule", () => {
+    const EXPORT_FORMAT_VERSION = 1;
+    let schedule: Schedule;
+
+    function decodePage(bytes: Buffer) {
+      let offset = 0;
+      const version = bytes.readUInt8(offset);
+      offset += 1;
+      const page = bytes.readUInt16LE(offset);
+      offset += 2;
+      const totalPages = bytes.readUInt16LE(offset);
+      offset += 2;
+      const count = bytes.readUInt16LE(offset);
+      offset += 2;
+
+      let header = null;
+      if (page === 0) {
+        header = {
+          tokenAmount: new BN(bytes.subarray(offset, offset + 8), "le"),
+          authority: new PublicKey(bytes.subarray(offset + 8, offset + 40)),
+          tokenMint: new PublicKey(bytes.subarray(offset + 40, offset + 72)),
+          decimals: bytes.readUInt8(offset + 72),
+        };
+        offset += 73;
+      }
+
+      const entries = [];
+      while (offset < bytes.length) {
+        entries.push({
+          key: new PublicKey(bytes.subarray(offset, offset + 32)),
+          allocatedTokens: new BN(bytes.subarray(offset + 32, offset + 40), "le"),
+          claimedTokens: new BN(bytes.subarray(offset + 40, offset + 48), "le"),
+          startTime: new BN(bytes.subarray(offset + 48, offset + 56), "le"),
+          cliffMonths: bytes.readUInt8(offset + 56),
+          totalMonths: bytes.readUInt8(offset + 57),
+        });
+        offset += 58;
+      }
+
+      return { version, page, totalPages, count, header, entries };
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 12 },
+        { amount: new BN(2_000), cliff: 6, duration: 24 },
+      ]);
+    });
+
+    it("round-trips the schedule through the wire format", async () => {
+      const bytes: Buffer = await env.program.methods
+        .exportSchedule(0)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          tokenMint: schedule.mint,
+        })
+        .view();
+
+      const decoded = decodePage(Buffer.from(bytes));
+      const stored = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+
+      assert.equal(decoded.version, EXPORT_FORMAT_VERSION);
+      assert.equal(decoded.page, 0);
+      assert.equal(decoded.totalPages, 1);
+      assert.equal(decoded.count, 2);
+      assert.equal(
+        decoded.header.tokenAmount.toString(),
+        stored.tokenAmount.toString()
+      );
+      assert.isTrue(decoded.header.authority.equals(stored.authority));
+      assert.isTrue(decoded.header.tokenMint.equals(schedule.mint));
+      assert.equal(decoded.header.decimals, stored.decimals);
+
+      decoded.entries.forEach((entry, i) => {
+        const b = stored.beneficiaries[i];
+        assert.isTrue(entry.key.equals(b.key));
+        assert.equal(entry.allocatedTokens.toString(), b.allocatedTokens.toString());
+        assert.equal(entry.claimedTokens.toString(), b.claimedTokens.toString());
+        assert.equal(entry.startTime.toString(), b.startTime.toString());
+        assert.equal(entry.cliffMonths, b.cliffMonths);
+        assert.equal(entry.totalMonths, b.totalMonths);
+      });
+    });
+
+    it("rejects an out-of-range page", async () => {
+      await expectError(
+        env.program.methods
+          .exportSchedule(1)
+          .accounts({
+            dataAccount: schedule.dataAccount,
+            tokenMint: schedule.mint,
+          })
+          .view(),
+        "InvalidPage"
+      );
+    });
+  });
+
+  describe("get_schedule_hash", () => {
+    let schedule: Schedule;
+
+    async function scheduleHash(): Promise<string> {
+      const hash = await env.program.methods
+        .getScheduleHash()
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          tokenMint: schedule.mint,
+        })
+        .view();
+      return Buffer.from(hash).toString("hex");
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(
+        env,
+        [
+          { amount: new BN(1_000), cliff: 3, duration: 12 },
+          { amount: new BN(2_000), cliff: 0, duration: 24 },
+        ],
+        { surplus: new BN(1_000_000) }
+      );
+    });
+
+    it("is stable across reads", async () => {
+      const first = await scheduleHash();
+      assert.lengthOf(first, 64);
+      assert.equal(await scheduleHash(), first);
+
+      await warp(env, SECOND_PER_MONTH);
+      assert.equal(await scheduleHash(), first);
+    });
+
+    it("changes when a beneficiary is added", async () => {
+      const original = await scheduleHash();
+      const startTime = (await currentTime(env)) + BUFFER_SECONDS;
+
+      await env.program.methods
+        .addBeneficiaries(
+          [randomBeneficiary(startTime)],
+          new BN(0),
+          schedule.dataBump
+        )
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          walletToWithdrawFrom: schedule.senderATA,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      assert.notEqual(await scheduleHash(), original);
+    });
+  });
+
+  describe("is_complete", () => {
+    let schedule: Schedule;
+
+    function isComplete(): Promise<boolean> {
+      return env.program.methods
+        .isComplete()
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          tokenMint: schedule.mint,
+        })
+        .view();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 6 },
+        { amount: new BN(1_000), cliff: 0, duration: 12 },
+      ]);
+    });
+
+    it("is false until the last beneficiary finishes vesting", async () => {
+      const startTime = schedule.beneficiaries[0].startTime.toNumber();
+
+      assert.isFalse(await isComplete());
+
+      await warpToMonth(env, startTime, 6);
+      assert.isFalse(await isComplete());
+
+      await warpToMonth(env, startTime, 12);
+      assert.isTrue(await isComplete());
+    });
+  });
+
+  describe("find_my_schedule", () => {
+    let schedule: Schedule;
+
+    function findMySchedule(beneficiary: PublicKey) {
+      return env.program.methods
+        .findMySchedule(beneficiary)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          tokenMint: schedule.mint,
+        })
+        .view();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 12 },
+        { amount: new BN(2_000), cliff: 6, duration: 24 },
+      ]);
+    });
+
+    it("returns the member's own entry", async () => {
+      const expected = schedule.beneficiaries[1];
+      const entry = await findMySchedule(expected.key);
+
+      assert.isTrue(entry.key.equals(expected.key));
+      assert.equal(
+        entry.allocatedTokens.toString(),
+        expected.allocatedTokens.toString()
+      );
+      assert.equal(entry.claimedTokens.toNumber(), 0);
+      assert.equal(entry.startTime.toString(), expected.startTime.toString());
+      assert.equal(entry.cliffMonths, expected.cliffMonths);
+      assert.equal(entry.totalMonths, expected.totalMonths);
+    });
+
+    it("rejects a non-member", async () => {
+      await expectError(
+        findMySchedule(Keypair.generate().publicKey),
+        "NotAMember"
+      );
+    });
+  });
+
+  describe("get_constants", () => {
+    it("returns the compiled program constants", async () => {
+      const constants = await env.program.methods.getConstants().view();
+
+      assert.equal(
+        constants.secondsPerMonth.toString(),
+        SECOND_PER_MONTH.toString()
+      );
+      assert.equal(constants.gracePeriodMonths.toNumber(), 6);
+      assert.equal(
+        constants.gracePeriod.toString(),
+        (SECOND_PER_MONTH * BigInt(6)).toString()
+      );
+      assert.equal(constants.maxStartDelay.toNumber(), 365 * 24 * 60 * 60);
+      assert.equal(constants.maxBeneficiaries, 50);
+      assert.equal(constants.maxTotalMonths, 120);
+      assert.equal(constants.minTotalMonths, 1);
+      assert.equal(constants.maxCliffMonths, 48);
+      assert.equal(constants.maxDecimals, 18);
+      assert.equal(constants.maxMilestones, 24);
+      assert.equal(constants.maxMemoLen, 128);
+      assert.equal(constants.maxBeneficiaryPageEntries, 10);
+      assert.equal(constants.maxCurveSamples, 63);
+      assert.equal(constants.exportFormatVersion, 1);
+      assert.equal(constants.exportEntriesPerPage, 15);
+      assert.equal(
+        constants.escrowMigrationTimelock.toNumber(),
+        2 * 24 * 60 * 60
+      );
+      assert.equal(constants.dataAccountVersion, 2);
+      assert.equal(constants.maxBlackoutWindows, 8);
+      assert.equal(constants.dustThreshold.toString(), "1000");
+      assert.equal(constants.maxClaimableBatch, 24);
+      assert.equal(constants.maxBatchSize, 10);
+    });
+  });
+
+  describe("sample_curve", () => {
+    let schedule: Schedule;
+
+    function sampleCurve(points: number) {
+      return env.program.methods
+        .sampleCurve(schedule.beneficiaries[0].key, points)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          tokenMint: schedule.mint,
+        })
+        .view();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+    });
+
+    it("samples from nothing vested to the full allocation", async () => {
+      const { allocatedTokens, startTime } = schedule.beneficiaries[0];
+      const samples = await sampleCurve(13);
+
+      assert.equal(samples.length, 13);
+      assert.equal(samples[0].timestamp.toString(), startTime.toString());
+      assert.equal(samples[0].cumulativeVested.toNumber(), 0);
+      assert.equal(
+        samples[12].cumulativeVested.toString(),
+        allocatedTokens.toString()
+      );
+      // One sample per month: linear release of a twelfth each
+      samples.forEach((sample, i) => {
+        assert.equal(
+          sample.timestamp.sub(startTime).toString(),
+          (SECOND_PER_MONTH * BigInt(i)).toString()
+        );
+        assert.equal(
+          sample.cumulativeVested.toString(),
+          allocatedTokens.muln(i).divn(12).toString()
+        );
+      });
+    });
+
+    it("clamps the number of points to MAX_CURVE_SAMPLES", async () => {
+      assert.equal((await sampleCurve(255)).length, 63);
+    });
+
+    it("rejects fewer than two points", async () => {
+      await expectError(sampleCurve(1), "InvalidSampleCount");
+    });
+  });
+
+  describe("get_beneficiary_count / get_beneficiary_page", () => {
+    let schedule: Schedule;
+
+    function getBeneficiaryPage(start: number, limit: number) {
+      return env.program.methods
+        .getBeneficiaryPage(start, limit)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          tokenMint: schedule.mint,
+        })
+        .view();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 12 },
+        { amount: new BN(2_000), cliff: 0, duration: 12 },
+        { amount: new BN(3_000), cliff: 0, duration: 12 },
+      ]);
+    });
+
+    it("returns the beneficiary count", async () => {
+      const count = await env.program.methods
+        .getBeneficiaryCount()
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          tokenMint: schedule.mint,
+        })
+        .view();
+
+      assert.equal(count, 3);
+    });
+
+    it("clamps a page that straddles the end of the list", async () => {
+      const page = await getBeneficiaryPage(1, 5);
+
+      assert.equal(page.length, 2);
+      assert.isTrue(page[0].key.equals(schedule.beneficiaries[1].key));
+      assert.isTrue(page[1].key.equals(schedule.beneficiaries[2].key));
+    });
+
+    it("returns an empty page at the end of the list", async () => {
+      assert.equal((await getBeneficiaryPage(3, 5)).length, 0);
+    });
+
+    it("rejects a start past the end of the list", async () => {
+      await expectError(getBeneficiaryPage(4, 1), "InvalidPage");
+    });
+  });
+
+  describe("verify_pdas", () => {
+    let schedule: Schedule;
+
+    function verifyPdas(
+      dataAccount: PublicKey,
+      escrowWallet: PublicKey,
+      dataBump = schedule.dataBump,
+      escrowBump = schedule.escrowBump
+    ) {
+      return env.program.methods
+        .verifyPdas(dataBump, escrowBump)
+        .accounts({ dataAccount, escrowWallet, tokenMint: schedule.mint })
+        .rpc();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 12 },
+      ]);
+    });
+
+    it("succeeds for the correct accounts and bumps", async () => {
+      await verifyPdas(schedule.dataAccount, schedule.escrowWallet);
+    });
+
+    it("reports swapped accounts as a data PDA mismatch", async () => {
+      await expectError(
+        verifyPdas(schedule.escrowWallet, schedule.dataAccount),
+        "DataPdaMismatch"
+      );
+    });
+
+    it("reports a wrong escrow as an escrow PDA mismatch", async () => {
+      await expectError(
+        verifyPdas(schedule.dataAccount, schedule.dataAccount),
+        "EscrowPdaMismatch"
+      );
+      await expectError(
+        verifyPdas(
+          schedule.dataAccount,
+          schedule.escrowWallet,
+          schedule.dataBump,
+          (schedule.escrowBump + 1) % 256
+        ),
+        "EscrowPdaMismatch"
+      );
+    });
+  });
+
+  describe("get_vested_at", () => {
+    let schedule: Schedule;
+    let startTime: number;
+
+    function getVestedAt(timestamp: number) {
+      return env.program.methods
+        .getVestedAt(schedule.users[0][0].publicKey, new BN(timestamp))
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          tokenMint: schedule.mint,
+        })
+        .view();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      startTime = schedule.beneficiaries[0].startTime.toNumber();
+      await warpToMonth(env, startTime, 6);
+    });
+
+    it("reconstructs the vested amount at historical timestamps", async () => {
+      const allocated = schedule.beneficiaries[0].allocatedTokens;
+      const month = Number(SECOND_PER_MONTH);
+
+      assert.equal((await getVestedAt(startTime - 1)).toNumber(), 0);
+      for (const months of [1, 3, 5]) {
+        const vested = await getVestedAt(startTime + months * month + 1);
+        assert.equal(
+          vested.toString(),
+          allocated.muln(months).divn(12).toString()
+        );
+      }
+    });
+
+    it("rejects a timestamp in the future", async () => {
+      await expectError(
+        getVestedAt(startTime + 7 * Number(SECOND_PER_MONTH)),
+        "TimestampInFuture"
+      );
+    });
+  });
+
+  describe("get_total_vested_at", () => {
+    let schedule: Schedule;
+    let startTime: number;
+
+    function getTotalVestedAt(timestamp: number) {
+      return env.program.methods
+        .getTotalVestedAt(new BN(timestamp))
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          tokenMint: schedule.mint,
+        })
+        .view();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+        { amount: new BN(2_400), cliff: 3, duration: 12 },
+        { amount: new BN(600), cliff: 0, duration: 6 },
+      ]);
+      startTime = schedule.beneficiaries[0].startTime.toNumber();
+      await warpToMonth(env, startTime, 7);
+    });
+
+    it("sums every beneficiary's vested amount at a past date", async () => {
+      const [a, b, c] = schedule.beneficiaries.map((x) => x.allocatedTokens);
+      const month = Number(SECOND_PER_MONTH);
+
+      // Month 5: 5/12, 2/9 after a 3-month cliff, and 5/6
+      const expected = a
+        .muln(5)
+        .divn(12)
+        .add(b.muln(2).divn(9))
+        .add(c.muln(5).divn(6));
+      const total = await getTotalVestedAt(startTime + 5 * month + 1);
+      assert.equal(total.toString(), expected.toString());
+
+      assert.equal((await getTotalVestedAt(startTime - 1)).toNumber(), 0);
+    });
+
+    it("rejects a timestamp in the future", async () => {
+      await expectError(
+        getTotalVestedAt(startTime + 8 * Number(SECOND_PER_MONTH)),
+        "TimestampInFuture"
+      );
+    });
+  });
+
+  describe("get_next_period_claimable", () => {
+    let schedule: Schedule;
+    let startTime: number;
+    const month = Number(SECOND_PER_MONTH);
+
+    function getNextPeriodClaimable() {
+      return env.program.methods
+        .getNextPeriodClaimable(schedule.users[0][0].publicKey)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          tokenMint: schedule.mint,
+        })
+        .view();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      startTime = schedule.beneficiaries[0].startTime.toNumber();
+    });
+
+    it("projects the next monthly unlock from mid-period", async () => {
+      await warpToMonth(env, startTime, 4, month / 2);
+
+      const projection = await getNextPeriodClaimable();
+      assert.equal(projection.nextBoundary.toNumber(), startTime + 5 * month);
+      assert.equal(
+        projection.additional.toString(),
+        schedule.beneficiaries[0].allocatedTokens.divn(12).toString()
+      );
+    });
+
+    it("returns 0 once fully vested", async () => {
+      await warpToMonth(env, startTime, 12);
+
+      const projection = await getNextPeriodClaimable();
+      assert.equal(projection.nextBoundary.toNumber(), startTime + 12 * month);
+      assert.equal(projection.additional.toNumber(), 0);
+    });
+  });
+
+  describe("get_claimable_batch", () => {
+    let schedule: Schedule;
+
+    function getClaimableBatch(beneficiaries: PublicKey[]) {
+      return env.program.methods
+        .getClaimableBatch(beneficiaries)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          tokenMint: schedule.mint,
+        })
+        .view();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+        { amount: new BN(2_400), cliff: 0, duration: 12 },
+      ]);
+      const startTime = schedule.beneficiaries[0].startTime.toNumber();
+      await warpToMonth(env, startTime, 6);
+    });
+
+    it("returns claimable amounts in input order, 0 for non-members", async () => {
+      const [first, second] = schedule.users.map(([user]) => user.publicKey);
+      const outsider = Keypair.generate().publicKey;
+
+      const amounts = await getClaimableBatch([second, outsider, first]);
+      assert.deepEqual(
+        amounts.map((a: BN) => a.toString()),
+        [
+          schedule.beneficiaries[1].allocatedTokens.divn(2).toString(),
+          "0",
+          schedule.beneficiaries[0].allocatedTokens.divn(2).toString(),
+        ]
+      );
+    });
+
+    it("rejects more than MAX_CLAIMABLE_BATCH addresses", async () => {
+      const addresses = Array.from(
+        { length: 25 },
+        () => Keypair.generate().publicKey
+      );
+      await expectError(getClaimableBatch(addresses), "BatchTooLarge");
+    });
+  });
+
+  describe("get_claim_eligibility", () => {
+    let schedule: Schedule;
+    let startTime: number;
+
+    async function eligibility(beneficiary: PublicKey) {
+      const status = await env.program.methods
+        .getClaimEligibility(beneficiary)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          tokenMint: schedule.mint,
+        })
+        .view();
+      const [kind] = Object.keys(status);
+      return { kind, fields: status[kind] };
+    }
+
+    function setPaused(paused: boolean) {
+      return env.program.methods
+        .setPaused(schedule.dataBump, paused)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          operator: schedule.sender,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 3, duration: 12 },
+      ]);
+      startTime = schedule.beneficiaries[0].startTime.toNumber();
+    });
+
+    it("reports an unknown address as not a member", async () => {
+      const { kind } = await eligibility(Keypair.generate().publicKey);
+      assert.equal(kind, "notAMember");
+    });
+
+    it("reports the time left until the cliff", async () => {
+      await warpToMonth(env, startTime, 1);
+
+      const { kind, fields } = await eligibility(schedule.users[0][0].publicKey);
+      assert.equal(kind, "cliffNotReached");
+      assert.isAbove(fields.remaining.toNumber(), 0);
+    });
+
+    it("reports the claimable amount once vested", async () => {
+      await warpToMonth(env, startTime, 4);
+
+      const { kind, fields } = await eligibility(schedule.users[0][0].publicKey);
+      assert.equal(kind, "eligible");
+      assert.isAbove(fields.amount.toNumber(), 0);
+    });
+
+    it("reports a paused schedule", async () => {
+      await setPaused(true);
+      const { kind } = await eligibility(schedule.users[0][0].publicKey);
+      assert.equal(kind, "paused");
+      await setPaused(false);
+    });
+
+    it("reports a fully claimed allocation", async () => {
+      await warpToMonth(env, startTime, 12);
+      await claimFor(env, schedule, 0);
+
+      const { kind } = await eligibility(schedule.users[0][0].publicKey);
+      assert.equal(kind, "fullyClaimed");
+    });
+  });
+
+  describe("get_net_claimable", () => {
+    let schedule: Schedule;
+    const raw = (amount: number) => toRawUnitFromBN(new BN(amount));
+
+    async function netClaimable() {
+      const result = await env.program.methods
+        .getNetClaimable(schedule.users[0][0].publicKey)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          tokenMint: schedule.mint,
+        })
+        .view();
+      const [kind] = Object.keys(result.status);
+      return { amount: result.amount.toString(), kind };
+    }
+
+    function updateConfig(
+      method: "setMaxPerClaim" | "setMinClaimAmount",
+      value: BN
+    ) {
+      return env.program.methods[method](schedule.dataBump, value)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_200), cliff: 0, duration: 12 },
+      ]);
+      const startTime = schedule.beneficiaries[0].startTime.toNumber();
+      await warpToMonth(env, startTime, 4);
+    });
+
+    it("returns the whole vested amount without limits", async () => {
+      const { amount, kind } = await netClaimable();
+      assert.equal(kind, "eligible");
+      assert.equal(amount, raw(400).toString());
+    });
+
+    it("reduces the amount to max_per_claim", async () => {
+      await updateConfig("setMaxPerClaim", raw(150));
+
+      const { amount, kind } = await netClaimable();
+      assert.equal(kind, "eligible");
+      assert.equal(amount, raw(150).toString());
+
+      await updateConfig("setMaxPerClaim", new BN(0));
+    });
+
+    it("returns 0 below the minimum claim", async () => {
+      await updateConfig("setMinClaimAmount", raw(500));
+
+      const { amount, kind } = await netClaimable();
+      assert.equal(kind, "belowMinClaim");
+      assert.equal(amount, "0");
+    });
+  });
+
+  describe("get_rent_recoverable", () => {
+    it("sums the data account and escrow lamports", async () => {
+      const schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 12 },
+      ]);
+
+      const recoverable = await env.program.methods
+        .getRentRecoverable()
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          tokenMint: schedule.mint,
+        })
+        .view();
+
+      const connection = env.provider.connection;
+      const dataInfo = await connection.getAccountInfo(schedule.dataAccount);
+      const escrowInfo = await connection.getAccountInfo(schedule.escrowWallet);
+      assert.equal(
+        recoverable.toNumber(),
+        dataInfo.lamports + escrowInfo.lamports
+      );
+    });
+  });
+
+  describe("get_schedule_summary", () => {
+    async function health(schedule: Schedule): Promise<string> {
+      const summary = await env.program.methods
+        .getScheduleSummary()
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          tokenMint: schedule.mint,
+        })
+        .view();
+      return Object.keys(summary.health)[0];
+    }
+
+    it("walks through every state of a recoverable schedule", async () => {
+      const schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 3, duration: 6 },
+      ]);
+      const startTime = schedule.beneficiaries[0].startTime.toNumber();
+
+      assert.equal(await health(schedule), "notStarted");
+
+      await warpToMonth(env, startTime, 1);
+      assert.equal(await health(schedule), "inCliff");
+
+      await warpToMonth(env, startTime, 4);
+      assert.equal(await health(schedule), "vesting");
+
+      // Vesting ends at month 6, the grace period at month 12
+      await warpToMonth(env, startTime, 7);
+      assert.equal(await health(schedule), "inGracePeriod");
+
+      await warpToMonth(env, startTime, 13);
+      assert.equal(await health(schedule), "recoverable");
+
+      await env.program.methods
+        .withdraw(schedule.dataBump, schedule.escrowBump, false, null)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          adminWallet: schedule.senderATA,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      assert.equal(await health(schedule), "closed");
+    });
+
+    it("reports FullyVested for a no-withdraw schedule", async () => {
+      const schedule = await setUpSchedule(
+        env,
+        [{ amount: new BN(1_000), cliff: 0, duration: 1 }],
+        { options: { ...defaultOptions(), noWithdraw: true } }
+      );
+      const startTime = schedule.beneficiaries[0].startTime.toNumber();
+
+      await warpToMonth(env, startTime, 2);
+      assert.equal(await health(schedule), "fullyVested");
+    });
+  });
+
+  describe("get_roles", () => {
+    let schedule: Schedule;
+
+    const roles = () =>
+      env.program.methods
+        .getRoles()
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          tokenMint: schedule.mint,
+        })
+        .view();
+
+    const setRole = (
+      role: { claimManager: {} } | { fundManager: {} },
+      holder: PublicKey | null
+    ) =>
+      env.program.methods
+        .setRole(schedule.dataBump, role, holder)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 6 },
+      ]);
+    });
+
+    it("returns only the admin on a new schedule", async () => {
+      const result = await roles();
+      assert.equal(result.authority.toBase58(), schedule.sender.toBase58());
+      assert.isNull(result.coAdmin);
+      assert.isNull(result.claimManager);
+      assert.isNull(result.fundManager);
+    });
+
+    it("matches the stored state after several role changes", async () => {
+      const [coAdmin, claimManager, fundManager, newAdmin] = Array.from(
+        { length: 4 },
+        () => Keypair.generate().publicKey
+      );
+
+      await env.program.methods
+        .setCoAdmin(schedule.dataBump, coAdmin)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          admin: schedule.sender,
+          coAdmin: null,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+      await setRole({ claimManager: {} }, claimManager);
+      await setRole({ fundManager: {} }, fundManager);
+      await setRole({ claimManager: {} }, null);
+      await env.program.methods
+        .changeAdmin(schedule.dataBump)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          currentAdmin: schedule.sender,
+          newAdmin,
+          tokenMint: schedule.mint,
+        })
+        .rpc();
+
+      const result = await roles();
+      const stored = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(result.authority.toBase58(), newAdmin.toBase58());
+      assert.equal(result.coAdmin.toBase58(), coAdmin.toBase58());
+      assert.isNull(result.claimManager);
+      assert.equal(result.fundManager.toBase58(), fundManager.toBase58());
+
+      assert.equal(result.authority.toBase58(), stored.authority.toBase58());
+      assert.equal(result.coAdmin.toBase58(), stored.coAdmin.toBase58());
+      assert.equal(
+        result.fundManager.toBase58(),
+        stored.fundManager.toBase58()
+      );
+    });
+  });
+});
@@ -17,12 +17,21 @@ import {
   Transaction,
 } from "@solana/web3.js";
 import { BankrunProvider } from "anchor-bankrun";
-import { ProgramTestContext } from "solana-bankrun";
-import { SECOND_PER_MONTH } from "./constant";
+import { assert } from "chai";
+import {
+  BanksClient,
+  Clock,
+  ProgramTestContext,
+  startAnchor,
+} from "solana-bankrun";
+import * as IDL from "../target/idl/vesting.json";
+import { Vesting } from "../target/types/vesting";
+import { BUFFER_SECONDS, DECIMALS, SECOND_PER_MONTH } from "./constant";
 
 export const createMint = async (
   provider: BankrunProvider,
-  decimals: number
+  decimals: number,
+  freezeAuthority: PublicKey | null = provider.wallet.publicKey
 ): Promise<PublicKey> => {
   const tokenMint = new anchor.web3.Keypair();
   const lamportForMint =
@@ -46,7 +55,7 @@ export const createMint = async (
       tokenMint.publicKey,
       decimals,
       provider.wallet.publicKey,
-      provider.wallet.publicKey,
+      freezeAuthority,
       spl.TOKEN_PROGRAM_ID
     )
   );
@@ -197,3 +206,460 @@ export function getPassedMonths(
 export function sleep(ms: number = 150): Promise<void> {
   return new Promise((resolve) => setTimeout(resolve, ms));
 }
+
+export interface BeneficiaryInput {
+  key: PublicKey;
+  allocatedTokens: BN;
+  claimedTokens: BN;
+  startTime: BN;
+  cliffMonths: number;
+  totalMonths: number;
+  lastClaimTime: BN;
+  curve: VestingCurveInput;
+  assigned: boolean;
+  notifyTag: number[];
+  cliffTime: BN | null;
+  claimCount: number;
+  originalIndex: number;
+  reference: number[];
+  mode: VestingModeInput;
+  settledFrom: BN;
+}
+
+/** Anchor encoding of the program's `VestingCurve` enum */
+export type VestingCurveInput =
+  | { linear: {} }
+  | { frontLoaded: {} }
+  | { backLoaded: {} }
+  | { milestones: {} };
+
+/** Anchor encoding of the program's `VestingMode` enum */
+export type VestingModeInput = { monthly: {} } | { continuous: {} };
+
+/** Anchor encoding of the program's `SurplusPolicy` enum */
+export type SurplusPolicyInput =
+  | { adminSweep: {} }
+  | { proRataBeneficiaries: {} }
+  | { locked: {} };
+
+/** Mirrors the program's `calculate_vesting_space!` macro. */
+export function vestingSpace(beneficiariesCount: number): number {
+  const beneficiarySize =
+    32 + 8 + 8 + 8 + 1 + 1 + 8 + 1 + 1 + 16 + 9 + 4 + 4 + 24 + 1 + 8;
+  const fixedSize =
+    8 + 8 + 32 + 32 + 32 + 1 + 8 + 8 + 1 + 8 + 8 + 8 + 8 + 1 + 1 + 8 + 1 + 33 +
+    1 + 8 + 4 + 1 + 1 + 1 + 1 + 1 + 1 + 8 + 4 + 4 + 1 + 1 + 33 + 1;
+  // Room for MAX_BLACKOUT_WINDOWS (8) windows
+  const blackoutSize = 4 + 8 * (8 + 8);
+  return (
+    fixedSize +
+    blackoutSize +
+    1 +
+    4 +
+    1 +
+    33 +
+    33 +
+    1 +
+    1 +
+    33 +
+    8 +
+    33 +
+    8 +
+    2 +
+    (4 + beneficiariesCount * beneficiarySize + 1)
+  );
+}
+
+export function randomBeneficiary(
+  startTime: number,
+  amount: BN = new BN(1_000_000)
+): BeneficiaryInput {
+  return {
+    key: Keypair.generate().publicKey,
+    allocatedTokens: amount,
+    claimedTokens: new BN(0),
+    startTime: new BN(startTime),
+    cliffMonths: 0,
+    totalMonths: 12,
+    lastClaimTime: new BN(0),
+    curve: { linear: {} },
+    assigned: true,
+    notifyTag: Array(16).fill(0),
+    cliffTime: null,
+    claimCount: 0,
+    originalIndex: 0,
+    reference: Array(24).fill(0),
+    mode: { monthly: {} },
+    settledFrom: new BN(0),
+  };
+}
+
+export interface BeneficiaryConfig {
+  amount: BN;
+  cliff: number;
+  duration: number;
+  /** Defaults to linear */
+  curve?: VestingCurveInput;
+  /** 16 bytes, defaults to all zero (no tag) */
+  notifyTag?: number[];
+  /** Absolute cliff date overriding `cliff`, defaults to none */
+  cliffTime?: BN;
+  /** 24 bytes, defaults to all zero (no reference) */
+  reference?: number[];
+}
+
+export interface VestingEnv {
+  ctx: ProgramTestContext;
+  client: BanksClient;
+  provider: BankrunProvider;
+  program: anchor.Program<Vesting>;
+}
+
+export interface Schedule {
+  mint: PublicKey;
+  sender: PublicKey;
+  senderATA: PublicKey;
+  dataAccount: PublicKey;
+  dataBump: number;
+  escrowWallet: PublicKey;
+  escrowBump: number;
+  users: [Keypair, PublicKey][];
+  beneficiaries: BeneficiaryInput[];
+  totalAmount: BN;
+  /** Audit log PDA, null unless initialized with `auditLog` */
+  auditLog: PublicKey | null;
+}
+
+/** Test-only staking program used for `auto_stake_program` (programs/mock-staking). */
+export const MOCK_STAKING_PROGRAM_ID = new PublicKey(
+  "4cfvFCn3YJkbajEi3Dfik6t8SReDb3JSJTzVMpQdj3tp"
+);
+
+export async function startVesting(): Promise<VestingEnv> {
+  const ctx = await startAnchor(
+    "",
+    [
+      { name: "vesting", programId: new PublicKey(IDL.address) },
+      { name: "mock_staking", programId: MOCK_STAKING_PROGRAM_ID },
+    ],
+    []
+  );
+  const provider = new BankrunProvider(ctx);
+  anchor.setProvider(provider);
+  const program = new anchor.Program<Vesting>(IDL as Vesting, provider);
+
+  return { ctx, client: ctx.banksClient, provider, program };
+}
+
+export async function currentTime(env: VestingEnv): Promise<number> {
+  const clock = await env.client.getClock();
+  return Number(clock.unixTimestamp);
+}
+
+export async function warp(env: VestingEnv, seconds: bigint): Promise<void> {
+  const clock = await env.client.getClock();
+  // Advance one slot so repeated identical transactions get a fresh blockhash
+  env.ctx.warpToSlot(clock.slot + BigInt(1));
+  env.ctx.setClock(
+    new Clock(
+      clock.slot + BigInt(1),
+      clock.epochStartTimestamp,
+      clock.epoch,
+      clock.leaderScheduleEpoch,
+      BigInt(clock.unixTimestamp) + seconds
+    )
+  );
+}
+
+export async function warpToMonth(
+  env: VestingEnv,
+  startTime: number,
+  month: number,
+  extraSeconds: number = BUFFER_SECONDS
+): Promise<void> {
+  const target =
+    BigInt(startTime) + SECOND_PER_MONTH * BigInt(month) + BigInt(extraSeconds);
+  const now = BigInt(await currentTime(env));
+  await warp(env, target - now);
+}
+
+export interface ScheduleOptionsInput {
+  useSlots: boolean;
+  slotsPerPeriod: BN;
+  fixedRateNumerator: BN;
+  fixedRateDenominator: BN;
+  requireExistingAta: boolean;
+  noWithdraw: boolean;
+  autoStakeProgram: PublicKey | null;
+  emergencyEnabled: boolean;
+  storeReceipts: boolean;
+  surplusPolicy: SurplusPolicyInput;
+  compactOnComplete: boolean;
+  minTotalMonths: number;
+  unlockFirstPeriodAtCliff: boolean;
+  continuousLinear: boolean;
+  auditLog: boolean;
+  requireNoFreezeAuthority: boolean;
+  inactivityWindowSeconds: BN;
+}
+
+export function defaultOptions(): ScheduleOptionsInput {
+  return {
+    useSlots: false,
+    slotsPerPeriod: new BN(0),
+    fixedRateNumerator: new BN(0),
+    fixedRateDenominator: new BN(0),
+    requireExistingAta: false,
+    noWithdraw: false,
+    autoStakeProgram: null,
+    emergencyEnabled: false,
+    storeReceipts: false,
+    surplusPolicy: { adminSweep: {} },
+    compactOnComplete: false,
+    minTotalMonths: 0,
+    unlockFirstPeriodAtCliff: false,
+    continuousLinear: false,
+    auditLog: false,
+    requireNoFreezeAuthority: false,
+    inactivityWindowSeconds: new BN(0),
+  };
+}
+
+export interface SetUpScheduleParams {
+  /** Raw units funded on top of the allocations */
+  surplus?: BN;
+  options?: ScheduleOptionsInput;
+  /** Defaults to the bank clock + BUFFER_SECONDS */
+  startTime?: number;
+  /** Freeze authority of the new mint (defaults to the admin, null = none) */
+  freezeAuthority?: PublicKey | null;
+  /** Initializes with `initializePercentageMilestones`, one table per config */
+  percentageMilestones?: {
+    tables: PercentageMilestoneInput[][];
+    interpolation: { step: {} } | { linear: {} };
+  };
+  /** Counts the schedule against this `AdminRegistry` */
+  adminRegistry?: PublicKey;
+  /** Makes the schedule follow this `ClockSource` */
+  clockSource?: PublicKey;
+}
+
+export interface PercentageMilestoneInput {
+  month: number;
+  cumulativeBps: number;
+}
+
+/** Milestone side account of `beneficiary` (also created by percentage init). */
+export function milestoneSchedulePDA(
+  env: VestingEnv,
+  dataAccount: PublicKey,
+  beneficiary: PublicKey
+): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("milestones"), dataAccount.toBuffer(), beneficiary.toBuffer()],
+    env.program.programId
+  )[0];
+}
+
+/**
+ * Creates a fresh mint, funds the admin, creates one user + ATA per config
+ * and initializes a vesting schedule for them.
+ */
+export async function setUpSchedule(
+  env: VestingEnv,
+  configs: BeneficiaryConfig[],
+  params: SetUpScheduleParams = {}
+): Promise<Schedule> {
+  const { ctx, provider, program } = env;
+  const surplus = params.surplus ?? new BN(0);
+  const options = params.options ?? defaultOptions();
+  const mint = await createMint(
+    provider,
+    DECIMALS,
+    params.freezeAuthority === undefined
+      ? provider.wallet.publicKey
+      : params.freezeAuthority
+  );
+  const sender = provider.wallet.publicKey;
+  const startTime =
+    params.startTime ?? (await currentTime(env)) + BUFFER_SECONDS;
+
+  const users: [Keypair, PublicKey][] = [];
+  for (let i = 0; i < configs.length; i++) {
+    users.push(await createUserAndATA(ctx, provider, mint));
+  }
+
+  const beneficiaries: BeneficiaryInput[] = configs.map((config, i) => ({
+    key: users[i][0].publicKey,
+    allocatedTokens: toRawUnitFromBN(config.amount),
+    claimedTokens: new BN(0),
+    startTime: new BN(startTime),
+    cliffMonths: config.cliff,
+    totalMonths: config.duration,
+    lastClaimTime: new BN(0),
+    curve: config.curve ?? { linear: {} },
+    assigned: true,
+    notifyTag: config.notifyTag ?? Array(16).fill(0),
+    cliffTime: config.cliffTime ?? null,
+    claimCount: 0,
+    originalIndex: 0,
+    reference: config.reference ?? Array(24).fill(0),
+    mode: { monthly: {} },
+    settledFrom: new BN(0),
+  }));
+  const totalAmount = beneficiaries
+    .reduce((sum, b) => sum.add(b.allocatedTokens), new BN(0))
+    .add(surplus);
+
+  const senderATA = await createAndFundSenderATA(
+    provider,
+    mint,
+    BigInt(totalAmount.toString())
+  );
+  const [dataAccount, dataBump] = await createPDA(
+    [Buffer.from("data_account"), mint.toBuffer()],
+    program.programId
+  );
+  const [escrowWallet, escrowBump] = await createPDA(
+    [Buffer.from("escrow_wallet"), mint.toBuffer()],
+    program.programId
+  );
+  const [auditLogPda] = await createPDA(
+    [Buffer.from("audit_log"), dataAccount.toBuffer()],
+    program.programId
+  );
+  const auditLog = options.auditLog ? auditLogPda : null;
+
+  const accounts = {
+    dataAccount,
+    escrowWallet,
+    walletToWithdrawFrom: senderATA,
+    tokenMint: mint,
+    sender,
+    auditLog,
+    adminRegistry: params.adminRegistry ?? null,
+    clockSource: params.clockSource ?? null,
+    systemProgram: SystemProgram.programId,
+    tokenProgram: TOKEN_PROGRAM_ID,
+  };
+  const percentage = params.percentageMilestones;
+  if (percentage) {
+    await program.methods
+      .initializePercentageMilestones(
+        beneficiaries,
+        totalAmount,
+        DECIMALS,
+        options,
+        percentage.tables,
+        percentage.interpolation
+      )
+      .accounts(accounts)
+      .remainingAccounts(
+        users.map(([user]) => ({
+          pubkey: milestoneSchedulePDA(env, dataAccount, user.publicKey),
+          isSigner: false,
+          isWritable: true,
+        }))
+      )
+      .rpc();
+  } else {
+    await program.methods
+      .initialize(beneficiaries, totalAmount, DECIMALS, options)
+      .accounts(accounts)
+      .rpc();
+  }
+
+  return {
+    mint,
+    sender,
+    senderATA,
+    dataAccount,
+    dataBump,
+    escrowWallet,
+    escrowBump,
+    users,
+    beneficiaries,
+    totalAmount,
+    auditLog,
+  };
+}
+
+export async function claimFor(
+  env: VestingEnv,
+  schedule: Schedule,
+  index: number
+): Promise<void> {
+  await claimBuilder(env, schedule, index).rpc();
+}
+
+export async function expectError(
+  promise: Promise<unknown>,
+  code: string
+): Promise<void> {
+  try {
+    await promise;
+  } catch (err) {
+    assert.equal(err.error?.errorCode?.code, code);
+    return;
+  }
+  assert.fail(`expected ${code}`);
+}
+
+/**
+ * Sends the transaction built by `builder` through the banks client and
+ * returns its log messages.
+ */
+export async function sendWithLogs(
+  env: VestingEnv,
+  builder: { transaction(): Promise<Transaction> },
+  signers: Keypair[] = []
+): Promise<string[]> {
+  const tx = await builder.transaction();
+  const [blockhash] = await env.client.getLatestBlockhash();
+  tx.recentBlockhash = blockhash;
+  tx.feePayer = env.ctx.payer.publicKey;
+  tx.sign(env.ctx.payer, ...signers);
+
+  const meta = await env.client.processTransaction(tx);
+  return meta.logMessages;
+}
+
+/**
+ * Sends the transaction built by `builder` through the banks client and
+ * returns the Anchor events decoded from its logs.
+ */
+export async function sendWithEvents(
+  env: VestingEnv,
+  builder: { transaction(): Promise<Transaction> },
+  signers: Keypair[] = []
+): Promise<anchor.Event[]> {
+  const logs = await sendWithLogs(env, builder, signers);
+  const parser = new anchor.EventParser(
+    env.program.programId,
+    env.program.coder
+  );
+  return Array.from(parser.parseLogs(logs));
+}
+
+/** `createAta: false` omits the ATA program, so the claim only accepts an existing ATA. */
+export function claimBuilder(
+  env: VestingEnv,
+  schedule: Schedule,
+  index: number,
+  createAta = true
+) {
+  const [user, userATA] = schedule.users[index];
+  return env.program.methods
+    .claim(schedule.dataBump, schedule.escrowBump, null)
+    .accounts({
+      dataAccount: schedule.dataAccount,
+      escrowWallet: schedule.escrowWallet,
+      sender: user.publicKey,
+      tokenMint: schedule.mint,
+      walletToDepositTo: userATA,
+      auditLog: schedule.auditLog,
+      associatedTokenProgram: createAta ? ASSOCIATED_TOKEN_PROGRAM_ID : null,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([user]);
+}
@@ -19,6 +19,7 @@ import {
   createMint,
   createPDA,
   createUserAndATA,
+  defaultOptions,
   getPassedMonths,
   getTokenBalance,
   sleep,
@@ -32,6 +33,16 @@ interface BeneficiaryInput {
   startTime: BN;
   cliffMonths: number;
   totalMonths: number;
+  lastClaimTime: BN;
+  curve: { linear: {} };
+  assigned: boolean;
+  notifyTag: number[];
+  cliffTime: BN | null;
+  claimCount: number;
+  originalIndex: number;
+  reference: number[];
+  mode: { monthly: {} };
+  settledFrom: BN;
 }
 
 describe("vesting with bank run", () => {
@@ -138,6 +149,16 @@ describe("vesting with bank run", () => {
       startTime: new BN(START_TIME),
       cliffMonths: config.cliff,
       totalMonths: config.duration,
+      lastClaimTime: new BN(0),
+      curve: { linear: {} },
+      assigned: true,
+      notifyTag: Array(16).fill(0),
+      cliffTime: null,
+      claimCount: 0,
+      originalIndex: 0,
+      reference: Array(24).fill(0),
+      mode: { monthly: {} },
+      settledFrom: new BN(0),
     }));
 
     totalVestingAmount = beneficiaryArray.reduce(
@@ -191,7 +212,7 @@ describe("vesting with bank run", () => {
     signer: Keypair
   ): Promise<void> {
     await program.methods
-      .claim(dataBump, escrowBump)
+      .claim(dataBump, escrowBump, null)
       .accounts({
         dataAccount,
         escrowWallet,
@@ -220,10 +241,25 @@ describe("vesting with bank run", () => {
       cliffMonths: 13,
       totalMonths: 24,
       startTime: new BN(START_TIME),
+      lastClaimTime: new BN(0),
+      curve: { linear: {} },
+      assigned: true,
+      notifyTag: Array(16).fill(0),
+      cliffTime: null,
+      claimCount: 0,
+      originalIndex: 0,
+      reference: Array(24).fill(0),
+      mode: { monthly: {} },
+      settledFrom: new BN(0),
     });
     try {
       await program.methods
-        .initialize(updateBeneficiaries, totalVestingAmount, DECIMALS)
+        .initialize(
+          updateBeneficiaries,
+          totalVestingAmount,
+          DECIMALS,
+          defaultOptions()
+        )
         .accounts({
           dataAccount,
           escrowWallet,
@@ -244,7 +280,12 @@ describe("vesting with bank run", () => {
   it("Test Initialize", async () => {
     sleep();
     await program.methods
-      .initialize(beneficiaryArray, totalVestingAmount, DECIMALS)
+      .initialize(
+        beneficiaryArray,
+        totalVestingAmount,
+        DECIMALS,
+        defaultOptions()
+      )
       .accounts({
         dataAccount,
         escrowWallet,
@@ -350,7 +391,7 @@ describe("vesting with bank run", () => {
 
     try {
       await program.methods
-        .withdraw(dataBump, escrowBump)
+        .withdraw(dataBump, escrowBump, false, null)
         .accounts({
           dataAccount,
           escrowWallet,
@@ -422,7 +463,7 @@ describe("vesting with bank run", () => {
     sleep();
     try {
       await program.methods
-        .withdraw(dataBump, escrowBump)
+        .withdraw(dataBump, escrowBump, false, null)
         .accounts({
           dataAccount,
           escrowWallet,
@@ -445,7 +486,7 @@ describe("vesting with bank run", () => {
     await warpToMonth(SECOND_PER_MONTH * BigInt(3));
 
     await program.methods
-      .withdraw(dataBump, escrowBump)
+      .withdraw(dataBump, escrowBump, false, null)
       .accounts({
         dataAccount,
         escrowWallet,
@@ -0,0 +1,749 @@
+import { BN } from "@coral-xyz/anchor";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { assert } from "chai";
+import { SECOND_PER_MONTH } from "./constant";
+import {
+  claimBuilder,
+  createUserAndATA,
+  defaultOptions,
+  expectError,
+  getTokenBalance,
+  Schedule,
+  sendWithEvents,
+  setUpSchedule,
+  startVesting,
+  toRawUnitFromBN,
+  VestingEnv,
+  warp,
+  warpToMonth,
+} from "./utils";
+
+describe("withdraw variants", () => {
+  let env: VestingEnv;
+
+  before(async () => {
+    env = await startVesting();
+  });
+
+  function withdrawAmount(schedule: Schedule, amount: BN) {
+    return withdrawAmountBuilder(schedule, amount).rpc();
+  }
+
+  function withdrawAmountBuilder(schedule: Schedule, amount: BN) {
+    return env.program.methods
+      .withdrawAmount(
+        schedule.users[0][0].publicKey,
+        amount,
+        schedule.dataBump,
+        schedule.escrowBump
+      )
+      .accounts({
+        dataAccount: schedule.dataAccount,
+        escrowWallet: schedule.escrowWallet,
+        adminWallet: schedule.senderATA,
+        admin: schedule.sender,
+        tokenMint: schedule.mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      });
+  }
+
+  function withdrawAll(schedule: Schedule) {
+    return env.program.methods
+      .withdraw(schedule.dataBump, schedule.escrowBump, false, null)
+      .accounts({
+        dataAccount: schedule.dataAccount,
+        escrowWallet: schedule.escrowWallet,
+        adminWallet: schedule.senderATA,
+        admin: schedule.sender,
+        tokenMint: schedule.mint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+  }
+
+  describe("withdraw_amount", () => {
+    let schedule: Schedule;
+    let startTime: number;
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 1 },
+      ]);
+      startTime = schedule.beneficiaries[0].startTime.toNumber();
+    });
+
+    it("is rejected before the grace period ends", async () => {
+      await warpToMonth(env, startTime, 6);
+      await expectError(
+        withdrawAmount(schedule, new BN(1)),
+        "NoUnclaimedTokens"
+      );
+    });
+
+    it("recovers half, then the rest", async () => {
+      await warpToMonth(env, startTime, 8);
+      const allocated = schedule.beneficiaries[0].allocatedTokens;
+      const half = allocated.divn(2);
+
+      await withdrawAmount(schedule, half);
+      let adminBalance = await getTokenBalance(schedule.senderATA, env.provider);
+      assert.equal(adminBalance.toString(), half.toString());
+
+      let account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(
+        account.beneficiaries[0].claimedTokens.toString(),
+        half.toString()
+      );
+
+      await expectError(
+        withdrawAmount(schedule, allocated),
+        "AmountExceedsUnclaimed"
+      );
+
+      await withdrawAmount(schedule, allocated.sub(half));
+      adminBalance = await getTokenBalance(schedule.senderATA, env.provider);
+      assert.equal(adminBalance.toString(), allocated.toString());
+
+      account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(
+        account.beneficiaries[0].claimedTokens.toString(),
+        allocated.toString()
+      );
+    });
+
+    it("has nothing left to recover", async () => {
+      await expectError(
+        withdrawAmount(schedule, new BN(1)),
+        "NoUnclaimedTokens"
+      );
+    });
+
+    it("emits ScheduleStateChanged with the escrow left", async () => {
+      const target = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 1 },
+      ]);
+      const start = target.beneficiaries[0].startTime.toNumber();
+      await warpToMonth(env, start, 8);
+
+      const amount = target.beneficiaries[0].allocatedTokens.divn(4);
+      const events = await sendWithEvents(
+        env,
+        withdrawAmountBuilder(target, amount)
+      );
+
+      const state = events[events.length - 1];
+      assert.equal(state.name, "scheduleStateChanged");
+      const escrow = await getTokenBalance(target.escrowWallet, env.provider);
+      assert.equal(state.data.escrowBalance.toString(), escrow.toString());
+      assert.equal(state.data.totalClaimed.toString(), amount.toString());
+    });
+  });
+
+  describe("recoverable amount", () => {
+    let schedule: Schedule;
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 1 },
+        { amount: new BN(2_400), cliff: 0, duration: 24 },
+      ]);
+    });
+
+    it("recovers vested-unclaimed tokens but never a still-vesting allocation", async () => {
+      const startTime = schedule.beneficiaries[0].startTime.toNumber();
+      await warpToMonth(env, startTime, 8);
+      const adminBefore = await getTokenBalance(
+        schedule.senderATA,
+        env.provider
+      );
+
+      await withdrawAll(schedule);
+
+      const recovered = (
+        await getTokenBalance(schedule.senderATA, env.provider)
+      ).sub(adminBefore);
+      assert.equal(
+        recovered.toString(),
+        schedule.beneficiaries[0].allocatedTokens.toString()
+      );
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(
+        account.beneficiaries[0].claimedTokens.toString(),
+        schedule.beneficiaries[0].allocatedTokens.toString()
+      );
+      // Eight of 24 months are vested but still inside their grace period
+      assert.equal(account.beneficiaries[1].claimedTokens.toNumber(), 0);
+    });
+
+    describe("claimed above allocation", () => {
+      before(async () => {
+        schedule = await setUpSchedule(env, [
+          { amount: new BN(1_000), cliff: 0, duration: 1 },
+          { amount: new BN(2_000), cliff: 0, duration: 1 },
+        ]);
+        await corruptBeneficiary(1, {
+          claimedTokens: schedule.beneficiaries[1].allocatedTokens.addn(1),
+        });
+
+        const startTime = schedule.beneficiaries[0].startTime.toNumber();
+        await warpToMonth(env, startTime, 8);
+      });
+
+      it("aborts with InvariantViolation instead of recovering 0", async () => {
+        await expectError(withdrawBuilder(false).rpc(), "InvariantViolation");
+      });
+
+      it("reports the corrupt beneficiary as skipped", async () => {
+        const events = await sendWithEvents(env, withdrawBuilder(true));
+
+        assert.equal(events[1].name, "beneficiarySkipped");
+        assert.equal(events[1].data.index, 1);
+        assert.equal(
+          events[2].data.totalAmount.toString(),
+          schedule.beneficiaries[0].allocatedTokens.toString()
+        );
+      });
+    });
+  });
+
+  describe("withdraw events", () => {
+    let schedule: Schedule;
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 1 },
+        { amount: new BN(2_000), cliff: 0, duration: 1 },
+        { amount: new BN(3_000), cliff: 0, duration: 1 },
+      ]);
+    });
+
+    it("emits one event per beneficiary in stored order, then the totals", async () => {
+      const startTime = schedule.beneficiaries[0].startTime.toNumber();
+      await warpToMonth(env, startTime, 8);
+
+      const events = await sendWithEvents(
+        env,
+        env.program.methods
+          .withdraw(schedule.dataBump, schedule.escrowBump, false, null)
+          .accounts({
+            dataAccount: schedule.dataAccount,
+            escrowWallet: schedule.escrowWallet,
+            adminWallet: schedule.senderATA,
+            admin: schedule.sender,
+            tokenMint: schedule.mint,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+      );
+
+      const perBeneficiary = events.slice(0, 3);
+      assert.deepEqual(
+        perBeneficiary.map((e) => e.name),
+        Array(3).fill("beneficiaryUnclaimedWithdrawn")
+      );
+      perBeneficiary.forEach((e, i) => {
+        assert.equal(e.data.index, i);
+        assert.isTrue(e.data.beneficiary.equals(schedule.beneficiaries[i].key));
+        assert.equal(
+          e.data.amount.toString(),
+          schedule.beneficiaries[i].allocatedTokens.toString()
+        );
+      });
+
+      const total = events[3];
+      assert.equal(total.name, "allUnclaimedWithdrawn");
+      assert.equal(total.data.beneficiariesProcessed, 3);
+
+      // Every beneficiary is now fully recovered
+      assert.equal(events[4].name, "scheduleExhausted");
+
+      const escrowChange = events[5];
+      assert.equal(escrowChange.name, "escrowBalanceChanged");
+      assert.deepEqual(escrowChange.data.reason, { withdraw: {} });
+      assert.equal(
+        escrowChange.data.oldBalance.sub(escrowChange.data.newBalance).toString(),
+        total.data.totalAmount.toString()
+      );
+
+      const state = events[6];
+      assert.equal(state.name, "scheduleStateChanged");
+      assert.equal(
+        state.data.totalClaimed.toString(),
+        state.data.totalAllocated.toString()
+      );
+      assert.equal(
+        state.data.escrowBalance.toString(),
+        (await getTokenBalance(schedule.escrowWallet, env.provider)).toString()
+      );
+
+      const seqs = events.map((e) => e.data.eventSeq.toNumber());
+      // Initialize used 1 (VestingInitialized) and 2 (EscrowBalanceChanged)
+      assert.deepEqual(seqs, [3, 4, 5, 6, 7, 8, 9]);
+    });
+  });
+
+  describe("skip_on_error", () => {
+    let schedule: Schedule;
+
+    /** Overwrites fields of the stored entry at `index`, bypassing validation. */
+    async function corruptBeneficiary(index: number, fields: object) {
+      const info = await env.provider.connection.getAccountInfo(
+        schedule.dataAccount
+      );
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      const beneficiaries = [...account.beneficiaries];
+      beneficiaries[index] = { ...beneficiaries[index], ...fields };
+      const corrupted = await env.program.coder.accounts.encode("DataAccount", {
+        ...account,
+        beneficiaries,
+      });
+      const data = Buffer.alloc(info.data.length);
+      corrupted.copy(data);
+      env.ctx.setAccount(schedule.dataAccount, { ...info, data });
+    }
+
+    function withdrawBuilder(skipOnError: boolean) {
+      return env.program.methods
+        .withdraw(schedule.dataBump, schedule.escrowBump, skipOnError, null)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          adminWallet: schedule.senderATA,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        });
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 1 },
+        { amount: new BN(2_000), cliff: 0, duration: 1 },
+      ]);
+
+      // Corrupt the second allocation so the running total overflows u64
+      await corruptBeneficiary(1, {
+        allocatedTokens: new BN("18446744073709551615"),
+      });
+
+      const startTime = schedule.beneficiaries[0].startTime.toNumber();
+      await warpToMonth(env, startTime, 8);
+    });
+
+    it("aborts on the overflowing beneficiary by default", async () => {
+      await expectError(withdrawBuilder(false).rpc(), "MathOverflow");
+    });
+
+    it("skips the overflowing beneficiary and recovers the rest", async () => {
+      const events = await sendWithEvents(env, withdrawBuilder(true));
+
+      assert.equal(events[0].name, "beneficiaryUnclaimedWithdrawn");
+      assert.equal(events[0].data.index, 0);
+      assert.equal(events[1].name, "beneficiarySkipped");
+      assert.equal(events[1].data.index, 1);
+      assert.isTrue(
+        events[1].data.beneficiary.equals(schedule.beneficiaries[1].key)
+      );
+      assert.equal(events[2].name, "allUnclaimedWithdrawn");
+      assert.equal(
+        events[2].data.totalAmount.toString(),
+        schedule.beneficiaries[0].allocatedTokens.toString()
+      );
+
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(account.beneficiaries[1].claimedTokens.toNumber(), 0);
+    });
+  });
+
+  describe("co_admin", () => {
+    let schedule: Schedule;
+    let coAdmin: Keypair;
+
+    function setCoAdmin(newCoAdmin: PublicKey | null, signer?: Keypair) {
+      return env.program.methods
+        .setCoAdmin(schedule.dataBump, newCoAdmin)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          admin: schedule.sender,
+          coAdmin: signer?.publicKey ?? null,
+          tokenMint: schedule.mint,
+        })
+        .signers(signer ? [signer] : [])
+        .rpc();
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 1 },
+      ]);
+      coAdmin = Keypair.generate();
+      await setCoAdmin(coAdmin.publicKey);
+
+      const startTime = schedule.beneficiaries[0].startTime.toNumber();
+      await warpToMonth(env, startTime, 8);
+    });
+
+    it("rejects a withdraw signed by the admin alone", async () => {
+      await expectError(withdrawAll(schedule), "CoAdminSignatureRequired");
+      await expectError(
+        withdrawAmount(schedule, new BN(1)),
+        "CoAdminSignatureRequired"
+      );
+    });
+
+    it("rejects clearing the co-admin without their signature", async () => {
+      await expectError(setCoAdmin(null), "CoAdminSignatureRequired");
+    });
+
+    it("withdraws once both sign", async () => {
+      await env.program.methods
+        .withdraw(schedule.dataBump, schedule.escrowBump, false, null)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          adminWallet: schedule.senderATA,
+          admin: schedule.sender,
+          coAdmin: coAdmin.publicKey,
+          tokenMint: schedule.mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([coAdmin])
+        .rpc();
+
+      const escrowBalance = await getTokenBalance(
+        schedule.escrowWallet,
+        env.provider
+      );
+      assert.equal(escrowBalance.toNumber(), 0);
+    });
+  });
+
+  describe("force_unlock_dust", () => {
+    function sweepDust(schedule: Schedule) {
+      return env.program.methods
+        .forceUnlockDust(schedule.dataBump, schedule.escrowBump)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          adminWallet: schedule.senderATA,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        });
+    }
+
+    async function settledSchedule(surplus: BN) {
+      const schedule = await setUpSchedule(
+        env,
+        [{ amount: new BN(1_000), cliff: 0, duration: 1 }],
+        { surplus }
+      );
+      const startTime = schedule.beneficiaries[0].startTime.toNumber();
+      await warpToMonth(env, startTime, 1);
+      await claimBuilder(env, schedule, 0).rpc();
+      return schedule;
+    }
+
+    it("rejects while an allocation is still unclaimed", async () => {
+      const schedule = await setUpSchedule(
+        env,
+        [{ amount: new BN(1_000), cliff: 0, duration: 1 }],
+        { surplus: new BN(999) }
+      );
+      await expectError(sweepDust(schedule).rpc(), "VestingNotComplete");
+    });
+
+    it("rejects a leftover at the dust threshold", async () => {
+      const schedule = await settledSchedule(new BN(1_000));
+      await expectError(sweepDust(schedule).rpc(), "NotDust");
+    });
+
+    it("empties the escrow of sub-threshold dust once all claims are done", async () => {
+      const schedule = await settledSchedule(new BN(999));
+      // Every allocation is claimed, but the escrow cannot be emptied by any claim
+      const dust = await getTokenBalance(schedule.escrowWallet, env.provider);
+      assert.equal(dust.toString(), "999");
+      await expectError(claimBuilder(env, schedule, 0).rpc(), "ClaimNotAllowed");
+
+      const adminBefore = await getTokenBalance(
+        schedule.senderATA,
+        env.provider
+      );
+      const events = await sendWithEvents(env, sweepDust(schedule));
+
+      const swept = events.find((e) => e.name === "dustSwept");
+      assert.equal(swept.data.amount.toString(), "999");
+      assert.isTrue(swept.data.recipient.equals(schedule.senderATA));
+      const escrow = await getTokenBalance(schedule.escrowWallet, env.provider);
+      assert.equal(escrow.toString(), "0");
+      const adminAfter = await getTokenBalance(schedule.senderATA, env.provider);
+      assert.equal(adminAfter.sub(adminBefore).toString(), "999");
+
+      await expectError(sweepDust(schedule).rpc(), "NotDust");
+    });
+  });
+
+  describe("no_withdraw", () => {
+    const options = { ...defaultOptions(), noWithdraw: true };
+    let schedule: Schedule;
+
+    before(async () => {
+      schedule = await setUpSchedule(
+        env,
+        [{ amount: new BN(1_000), cliff: 0, duration: 1 }],
+        { options }
+      );
+    });
+
+    it("rejects withdraw even after the grace period", async () => {
+      const startTime = schedule.beneficiaries[0].startTime.toNumber();
+      await warpToMonth(env, startTime, 8);
+
+      await expectError(withdrawAll(schedule), "WithdrawDisabled");
+      await expectError(
+        withdrawAmount(schedule, new BN(1)),
+        "WithdrawDisabled"
+      );
+    });
+
+    it("requires funding to match allocations exactly", async () => {
+      await expectError(
+        setUpSchedule(
+          env,
+          [{ amount: new BN(1_000), cliff: 0, duration: 1 }],
+          { options, surplus: new BN(1) }
+        ),
+        "UnallocatedSurplus"
+      );
+    });
+  });
+
+  describe("withdraw_inactive", () => {
+    const month = Number(SECOND_PER_MONTH);
+    let schedule: Schedule;
+    let startTime: number;
+
+    function withdrawInactive(target: Schedule, index: number) {
+      return env.program.methods
+        .withdrawInactive(
+          target.users[index][0].publicKey,
+          target.dataBump,
+          target.escrowBump
+        )
+        .accounts({
+          dataAccount: target.dataAccount,
+          escrowWallet: target.escrowWallet,
+          adminWallet: target.senderATA,
+          admin: target.sender,
+          tokenMint: target.mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        });
+    }
+
+    before(async () => {
+      schedule = await setUpSchedule(
+        env,
+        [
+          { amount: new BN(1_200), cliff: 0, duration: 12 },
+          { amount: new BN(1_200), cliff: 0, duration: 12 },
+        ],
+        {
+          options: {
+            ...defaultOptions(),
+            inactivityWindowSeconds: new BN(2 * month),
+          },
+        }
+      );
+      startTime = schedule.beneficiaries[0].startTime.toNumber();
+    });
+
+    it("rejects schedules without an inactivity window", async () => {
+      const plain = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 12 },
+      ]);
+      await expectError(
+        withdrawInactive(plain, 0).rpc(),
+        "InactivityRecoveryDisabled"
+      );
+    });
+
+    it("rejects a window as long as the grace period", async () => {
+      await expectError(
+        setUpSchedule(
+          env,
+          [{ amount: new BN(1_000), cliff: 0, duration: 12 }],
+          {
+            options: {
+              ...defaultOptions(),
+              inactivityWindowSeconds: new BN(6 * month),
+            },
+          }
+        ),
+        "InvalidInactivityWindow"
+      );
+    });
+
+    it("rejects before the window has passed", async () => {
+      await warpToMonth(env, startTime, 1);
+      await expectError(
+        withdrawInactive(schedule, 1).rpc(),
+        "BeneficiaryNotInactive"
+      );
+    });
+
+    it("recovers only the vested part mid-vesting, where grace recovery cannot", async () => {
+      await warpToMonth(env, startTime, 3);
+      await claimBuilder(env, schedule, 0).rpc();
+      await warpToMonth(env, startTime, 4);
+
+      // Still vesting: nothing is recoverable through the grace period yet
+      await expectError(withdrawAll(schedule), "NoUnclaimedTokens");
+
+      const adminBefore = await getTokenBalance(
+        schedule.senderATA,
+        env.provider
+      );
+      const events = await sendWithEvents(env, withdrawInactive(schedule, 1));
+      const vested = schedule.beneficiaries[1].allocatedTokens
+        .muln(4)
+        .divn(12);
+
+      assert.equal(events[0].name, "inactiveUnclaimedWithdrawn");
+      assert.equal(events[0].data.amount.toString(), vested.toString());
+      assert.equal(events[0].data.lastActivity.toNumber(), startTime);
+      const state = events[events.length - 1];
+      assert.equal(state.name, "scheduleStateChanged");
+      assert.equal(
+        state.data.escrowBalance.toString(),
+        (await getTokenBalance(schedule.escrowWallet, env.provider)).toString()
+      );
+      const adminAfter = await getTokenBalance(
+        schedule.senderATA,
+        env.provider
+      );
+      assert.equal(adminAfter.sub(adminBefore).toString(), vested.toString());
+
+      const account = await env.program.account.dataAccount.fetch(
+        schedule.dataAccount
+      );
+      assert.equal(
+        account.beneficiaries[1].claimedTokens.toString(),
+        vested.toString()
+      );
+      assert.equal(
+        account.beneficiaries[1].allocatedTokens.toString(),
+        schedule.beneficiaries[1].allocatedTokens.toString()
+      );
+    });
+
+    it("leaves a recently active beneficiary alone", async () => {
+      await expectError(
+        withdrawInactive(schedule, 0).rpc(),
+        "BeneficiaryNotInactive"
+      );
+    });
+
+    it("rejects when nothing new has vested", async () => {
+      await warp(env, BigInt(1));
+      await expectError(
+        withdrawInactive(schedule, 1).rpc(),
+        "NoUnclaimedTokens"
+      );
+    });
+  });
+
+  describe("treasury split", () => {
+    let schedule: Schedule;
+    let treasuries: [Keypair, PublicKey][];
+
+    before(async () => {
+      schedule = await setUpSchedule(env, [
+        { amount: new BN(1_000), cliff: 0, duration: 1 },
+      ]);
+      treasuries = [
+        await createUserAndATA(env.ctx, env.provider, schedule.mint),
+        await createUserAndATA(env.ctx, env.provider, schedule.mint),
+      ];
+      const startTime = schedule.beneficiaries[0].startTime.toNumber();
+      await warpToMonth(env, startTime, 8);
+    });
+
+    function withdrawSplit(bps: number[], accounts: PublicKey[]) {
+      const split = bps.map((share, i) => ({
+        wallet: treasuries[i][0].publicKey,
+        bps: share,
+      }));
+      return env.program.methods
+        .withdraw(schedule.dataBump, schedule.escrowBump, false, split)
+        .accounts({
+          dataAccount: schedule.dataAccount,
+          escrowWallet: schedule.escrowWallet,
+          adminWallet: schedule.senderATA,
+          admin: schedule.sender,
+          tokenMint: schedule.mint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(
+          accounts.map((pubkey) => ({
+            pubkey,
+            isWritable: true,
+            isSigner: false,
+          }))
+        );
+    }
+
+    it("rejects shares that do not sum to 10_000 bps", async () => {
+      const accounts = treasuries.map(([, ata]) => ata);
+      await expectError(
+        withdrawSplit([7_000, 2_000], accounts).rpc(),
+        "InvalidTreasurySplit"
+      );
+    });
+
+    it("rejects a token account not owned by its treasury", async () => {
+      const swapped = [treasuries[1][1], treasuries[0][1]];
+      await expectError(
+        withdrawSplit([7_000, 3_000], swapped).rpc(),
+        "InvalidTreasurySplit"
+      );
+    });
+
+    it("splits the recovered tokens 70/30", async () => {
+      const adminBefore = await getTokenBalance(
+        schedule.senderATA,
+        env.provider
+      );
+      const accounts = treasuries.map(([, ata]) => ata);
+      const events = await sendWithEvents(
+        env,
+        withdrawSplit([7_000, 3_000], accounts)
+      );
+
+      const total = toRawUnitFromBN(new BN(1_000));
+      const first = await getTokenBalance(accounts[0], env.provider);
+      const second = await getTokenBalance(accounts[1], env.provider);
+      assert.equal(first.toString(), total.muln(7).divn(10).toString());
+      assert.equal(second.toString(), total.muln(3).divn(10).toString());
+
+      const paid = events.filter((e) => e.name === "treasuryPaid");
+      assert.equal(paid.length, 2);
+      assert.isTrue(paid[0].data.treasury.equals(accounts[0]));
+      assert.equal(paid[1].data.amount.toString(), second.toString());
+
+      const adminAfter = await getTokenBalance(schedule.senderATA, env.provider);
+      assert.equal(adminAfter.toString(), adminBefore.toString());
+      const escrow = await getTokenBalance(schedule.escrowWallet, env.provider);
+      assert.equal(escrow.toString(), "0");
+    });
+  });
+});